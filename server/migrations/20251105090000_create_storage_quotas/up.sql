@@ -0,0 +1,16 @@
+-- 创建存储配额表
+-- 每条记录只针对一个用户或一个组（二者必须且只能设置一个）
+CREATE TABLE storage_quotas (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID UNIQUE REFERENCES users(id) ON DELETE CASCADE,
+    group_id UUID UNIQUE REFERENCES groups(id) ON DELETE CASCADE,
+    max_bytes BIGINT NOT NULL CHECK (max_bytes >= 0),
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((user_id IS NULL) <> (group_id IS NULL))
+);
+
+-- 创建索引
+CREATE INDEX idx_storage_quotas_user ON storage_quotas(user_id);
+CREATE INDEX idx_storage_quotas_group ON storage_quotas(group_id);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_documents_locked_by;
+ALTER TABLE documents DROP COLUMN IF EXISTS lock_expires_at;
+ALTER TABLE documents DROP COLUMN IF EXISTS locked_at;
+ALTER TABLE documents DROP COLUMN IF EXISTS locked_by;
@@ -0,0 +1,6 @@
+-- 文档签出锁：持有者、签出时间与过期时间
+ALTER TABLE documents ADD COLUMN locked_by UUID REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE documents ADD COLUMN locked_at TIMESTAMP;
+ALTER TABLE documents ADD COLUMN lock_expires_at TIMESTAMP;
+
+CREATE INDEX idx_documents_locked_by ON documents(locked_by);
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS document_status_history;
+DROP TABLE IF EXISTS document_status_transitions;
+ALTER TABLE documents ALTER COLUMN status SET DEFAULT 'active';
+UPDATE documents SET status = 'active' WHERE status = 'draft';
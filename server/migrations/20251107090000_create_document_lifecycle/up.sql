@@ -0,0 +1,36 @@
+-- 文档生命周期：draft -> in_review -> approved -> archived -> obsolete
+UPDATE documents SET status = 'draft' WHERE status = 'active';
+ALTER TABLE documents ALTER COLUMN status SET DEFAULT 'draft';
+
+-- 创建状态流转规则表（可由管理员配置）
+CREATE TABLE document_status_transitions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    from_status VARCHAR(50) NOT NULL,
+    to_status VARCHAR(50) NOT NULL,
+    required_permission TEXT NOT NULL DEFAULT 'write' CHECK (required_permission IN ('read', 'write', 'delete', 'share', 'admin')),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(from_status, to_status)
+);
+
+-- 默认流转规则
+INSERT INTO document_status_transitions (from_status, to_status, required_permission) VALUES
+    ('draft', 'in_review', 'write'),
+    ('in_review', 'draft', 'write'),
+    ('in_review', 'approved', 'admin'),
+    ('approved', 'draft', 'write'),
+    ('approved', 'archived', 'admin'),
+    ('archived', 'obsolete', 'admin');
+
+-- 创建状态变更历史表
+CREATE TABLE document_status_history (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    document_id UUID NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    from_status VARCHAR(50) NOT NULL,
+    to_status VARCHAR(50) NOT NULL,
+    comment TEXT,
+    changed_by UUID NOT NULL REFERENCES users(id),
+    changed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 创建索引
+CREATE INDEX idx_document_status_history_document ON document_status_history(document_id);
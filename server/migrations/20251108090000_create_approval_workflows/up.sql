@@ -0,0 +1,35 @@
+-- 创建审批流程表
+-- 每次提交审批都会记录提交时的文档版本，用于证明审批的是哪个版本
+CREATE TABLE approval_workflows (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    document_id UUID NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    document_version INTEGER NOT NULL,
+    submitted_by UUID NOT NULL REFERENCES users(id),
+    mode VARCHAR(20) NOT NULL CHECK (mode IN ('sequential', 'parallel')),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected', 'cancelled')),
+    comment TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMP
+);
+
+-- 创建审批步骤表
+-- 同一 step_order 的步骤并行审批，不同 step_order 按顺序审批
+CREATE TABLE approval_steps (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workflow_id UUID NOT NULL REFERENCES approval_workflows(id) ON DELETE CASCADE,
+    step_order INTEGER NOT NULL,
+    reviewer_user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    reviewer_group_id UUID REFERENCES groups(id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected', 'skipped')),
+    decided_by UUID REFERENCES users(id),
+    decision_comment TEXT,
+    decided_at TIMESTAMP,
+    CHECK ((reviewer_user_id IS NULL) <> (reviewer_group_id IS NULL))
+);
+
+-- 创建索引
+CREATE INDEX idx_approval_workflows_document ON approval_workflows(document_id);
+CREATE INDEX idx_approval_workflows_status ON approval_workflows(status);
+CREATE INDEX idx_approval_steps_workflow ON approval_steps(workflow_id);
+CREATE INDEX idx_approval_steps_reviewer_user ON approval_steps(reviewer_user_id);
+CREATE INDEX idx_approval_steps_reviewer_group ON approval_steps(reviewer_group_id);
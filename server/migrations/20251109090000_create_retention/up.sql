@@ -0,0 +1,45 @@
+-- 创建保留策略表
+-- folder_id / tag / mime_type 至少设置一个；同时设置多个时需全部匹配
+-- mime_type 支持通配形式，例如 'image/*'
+CREATE TABLE retention_policies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL,
+    folder_id UUID REFERENCES documents(id) ON DELETE CASCADE,
+    tag VARCHAR(100),
+    mime_type VARCHAR(100),
+    retention_days INTEGER NOT NULL CHECK (retention_days > 0),
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK (folder_id IS NOT NULL OR tag IS NOT NULL OR mime_type IS NOT NULL)
+);
+
+-- 创建法律保留表（文档或文件夹）
+CREATE TABLE legal_holds (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    document_id UUID NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    released_by UUID REFERENCES users(id),
+    released_at TIMESTAMP
+);
+
+-- 创建保留执行审计表
+-- 不对 documents 建外键，保证审计记录不随文档删除
+CREATE TABLE retention_audit_logs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    policy_id UUID REFERENCES retention_policies(id) ON DELETE SET NULL,
+    document_id UUID NOT NULL,
+    document_name VARCHAR(255) NOT NULL,
+    action VARCHAR(50) NOT NULL,
+    details TEXT,
+    executed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 创建索引
+CREATE INDEX idx_retention_policies_folder ON retention_policies(folder_id);
+CREATE INDEX idx_legal_holds_document ON legal_holds(document_id);
+CREATE INDEX idx_retention_audit_logs_document ON retention_audit_logs(document_id);
+CREATE INDEX idx_retention_audit_logs_executed_at ON retention_audit_logs(executed_at);
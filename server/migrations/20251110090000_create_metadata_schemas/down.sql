@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS metadata_schema_bindings;
+DROP TABLE IF EXISTS metadata_schemas;
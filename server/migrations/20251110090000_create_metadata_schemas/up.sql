@@ -0,0 +1,25 @@
+-- 创建元数据模式表
+-- fields 为字段定义数组，例如:
+-- [{"name": "counterparty", "type": "string", "required": true}]
+CREATE TABLE metadata_schemas (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    fields JSONB NOT NULL DEFAULT '[]',
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 创建元数据模式绑定表（绑定到文件夹或文档类型）
+CREATE TABLE metadata_schema_bindings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    schema_id UUID NOT NULL REFERENCES metadata_schemas(id) ON DELETE CASCADE,
+    folder_id UUID UNIQUE REFERENCES documents(id) ON DELETE CASCADE,
+    mime_type VARCHAR(100) UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((folder_id IS NULL) <> (mime_type IS NULL))
+);
+
+-- 创建索引
+CREATE INDEX idx_metadata_schema_bindings_schema ON metadata_schema_bindings(schema_id);
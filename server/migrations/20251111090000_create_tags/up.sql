@@ -0,0 +1,11 @@
+-- 创建标签词表（受控词表模式下只能使用这里定义的标签）
+CREATE TABLE tags (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(50) NOT NULL UNIQUE,
+    description TEXT,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 按标签查找文档
+CREATE INDEX idx_documents_tags ON documents USING GIN (tags);
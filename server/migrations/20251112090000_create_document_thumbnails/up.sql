@@ -0,0 +1,12 @@
+-- 创建文档缩略图表（每个文档只保留当前版本的缩略图）
+-- status: ready / failed / unsupported
+CREATE TABLE document_thumbnails (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    document_id UUID NOT NULL UNIQUE REFERENCES documents(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    file_path VARCHAR(500),
+    error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
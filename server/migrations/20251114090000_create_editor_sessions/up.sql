@@ -0,0 +1,13 @@
+-- 创建 OnlyOffice 协同编辑会话表
+CREATE TABLE editor_sessions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    document_id UUID NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    document_key VARCHAR(128) NOT NULL,
+    started_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_seen_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    ended_at TIMESTAMP
+);
+
+-- 每个用户在同一文档上最多只有一个进行中的会话
+CREATE UNIQUE INDEX idx_editor_sessions_active ON editor_sessions(document_id, user_id) WHERE ended_at IS NULL;
@@ -0,0 +1,5 @@
+ALTER TABLE document_versions
+    DROP COLUMN IF EXISTS history,
+    DROP COLUMN IF EXISTS changes_path,
+    DROP COLUMN IF EXISTS is_forcesave,
+    DROP COLUMN IF EXISTS document_key;
@@ -0,0 +1,9 @@
+-- 记录 OnlyOffice 保存的版本信息，用于编辑器内置的版本历史
+-- document_key: 产生该版本的编辑会话 key
+-- changes_path: 修改记录压缩包（changesurl）在存储中的路径
+-- history: 回调中的 history 数据（serverVersion、changes）
+ALTER TABLE document_versions
+    ADD COLUMN document_key VARCHAR(128),
+    ADD COLUMN is_forcesave BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN changes_path VARCHAR(500),
+    ADD COLUMN history JSONB;
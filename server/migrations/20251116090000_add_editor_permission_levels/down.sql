@@ -0,0 +1,21 @@
+DELETE FROM document_permissions WHERE permission IN ('comment', 'review', 'fill_forms');
+DELETE FROM group_permissions WHERE permission IN ('comment', 'review', 'fill_forms');
+UPDATE share_links SET permission = 'read' WHERE permission IN ('comment', 'review', 'fill_forms');
+UPDATE document_status_transitions SET required_permission = 'write'
+    WHERE required_permission IN ('comment', 'review', 'fill_forms');
+
+ALTER TABLE document_permissions DROP CONSTRAINT IF EXISTS document_permissions_permission_check;
+ALTER TABLE document_permissions ADD CONSTRAINT document_permissions_permission_check
+    CHECK (permission IN ('read', 'write', 'delete', 'share', 'admin'));
+
+ALTER TABLE group_permissions DROP CONSTRAINT IF EXISTS group_permissions_permission_check;
+ALTER TABLE group_permissions ADD CONSTRAINT group_permissions_permission_check
+    CHECK (permission IN ('read', 'write', 'delete', 'share', 'admin'));
+
+ALTER TABLE share_links DROP CONSTRAINT IF EXISTS share_links_permission_check;
+ALTER TABLE share_links ADD CONSTRAINT share_links_permission_check
+    CHECK (permission IN ('read', 'write', 'delete', 'share', 'admin'));
+
+ALTER TABLE document_status_transitions DROP CONSTRAINT IF EXISTS document_status_transitions_required_permission_check;
+ALTER TABLE document_status_transitions ADD CONSTRAINT document_status_transitions_required_permission_check
+    CHECK (required_permission IN ('read', 'write', 'delete', 'share', 'admin'));
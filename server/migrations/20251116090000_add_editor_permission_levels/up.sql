@@ -0,0 +1,16 @@
+-- 新增编辑器权限级别：comment（仅批注）、review（修订模式）、fill_forms（仅填写表单）
+ALTER TABLE document_permissions DROP CONSTRAINT IF EXISTS document_permissions_permission_check;
+ALTER TABLE document_permissions ADD CONSTRAINT document_permissions_permission_check
+    CHECK (permission IN ('read', 'comment', 'review', 'fill_forms', 'write', 'delete', 'share', 'admin'));
+
+ALTER TABLE group_permissions DROP CONSTRAINT IF EXISTS group_permissions_permission_check;
+ALTER TABLE group_permissions ADD CONSTRAINT group_permissions_permission_check
+    CHECK (permission IN ('read', 'comment', 'review', 'fill_forms', 'write', 'delete', 'share', 'admin'));
+
+ALTER TABLE share_links DROP CONSTRAINT IF EXISTS share_links_permission_check;
+ALTER TABLE share_links ADD CONSTRAINT share_links_permission_check
+    CHECK (permission IN ('read', 'comment', 'review', 'fill_forms', 'write', 'delete', 'share', 'admin'));
+
+ALTER TABLE document_status_transitions DROP CONSTRAINT IF EXISTS document_status_transitions_required_permission_check;
+ALTER TABLE document_status_transitions ADD CONSTRAINT document_status_transitions_required_permission_check
+    CHECK (required_permission IN ('read', 'comment', 'review', 'fill_forms', 'write', 'delete', 'share', 'admin'));
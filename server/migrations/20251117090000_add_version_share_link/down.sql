@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_document_versions_share_link;
+
+ALTER TABLE document_versions DROP COLUMN IF EXISTS share_link_id;
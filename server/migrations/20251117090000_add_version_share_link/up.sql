@@ -0,0 +1,5 @@
+-- 记录通过分享链接在 OnlyOffice 中保存的版本来自哪个链接
+ALTER TABLE document_versions
+    ADD COLUMN share_link_id UUID REFERENCES share_links(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_document_versions_share_link ON document_versions(share_link_id);
@@ -0,0 +1,19 @@
+-- 创建文档模板表（“新建 → 文档/表格/演示文稿”使用）
+-- 模板文件存放在存储的 templates/ 目录下
+-- group_id: 按部门（组）提供的模板，为空表示所有用户可用
+CREATE TABLE document_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('document', 'spreadsheet', 'presentation')),
+    file_name VARCHAR(255) NOT NULL,
+    file_path VARCHAR(500) NOT NULL,
+    file_size BIGINT NOT NULL,
+    mime_type VARCHAR(255) NOT NULL,
+    group_id UUID REFERENCES groups(id) ON DELETE CASCADE,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_document_templates_kind ON document_templates(kind);
+CREATE INDEX idx_document_templates_group ON document_templates(group_id);
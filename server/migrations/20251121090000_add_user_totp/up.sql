@@ -0,0 +1,19 @@
+-- 用户 TOTP 两步验证
+-- totp_secret: Base32 编码的共享密钥，登记时生成，验证通过后 totp_enabled 才置为 TRUE
+-- totp_required: 管理员强制要求该用户启用两步验证
+-- totp_last_step: 最近一次验证通过的时间步，防止同一验证码被重复使用
+ALTER TABLE users ADD COLUMN totp_secret VARCHAR(64);
+ALTER TABLE users ADD COLUMN totp_enabled BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE users ADD COLUMN totp_required BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE users ADD COLUMN totp_last_step BIGINT;
+
+-- 创建恢复码表（每个恢复码只能使用一次，只保存 SHA-256 摘要）
+CREATE TABLE totp_recovery_codes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_totp_recovery_codes_user ON totp_recovery_codes(user_id);
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS oidc_login_requests;
+
+ALTER TABLE group_members DROP COLUMN IF EXISTS source;
+ALTER TABLE users DROP COLUMN IF EXISTS oidc_subject;
@@ -0,0 +1,19 @@
+-- OpenID Connect 单点登录
+-- oidc_subject: 身份提供方（IdP）中用户的 sub，首次单点登录时绑定
+ALTER TABLE users ADD COLUMN oidc_subject VARCHAR(255) UNIQUE;
+
+-- 组成员来源：local 为本地维护，oidc 为登录时根据 IdP 组声明同步（同步时只增删 oidc 来源的成员）
+ALTER TABLE group_members ADD COLUMN source VARCHAR(20) NOT NULL DEFAULT 'local'
+    CHECK (source IN ('local', 'oidc'));
+
+-- 创建进行中的单点登录请求表（保存 PKCE 校验码和 nonce，state 只保存 SHA-256 摘要，回调时一次性取出）
+CREATE TABLE oidc_login_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    state_hash VARCHAR(64) NOT NULL UNIQUE,
+    pkce_verifier VARCHAR(128) NOT NULL,
+    nonce VARCHAR(128) NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_oidc_login_requests_expires ON oidc_login_requests(expires_at);
@@ -0,0 +1,12 @@
+-- 没有创建者的组归到最早的管理员名下
+UPDATE groups
+SET created_by = (SELECT id FROM users WHERE role = 'admin' ORDER BY created_at LIMIT 1)
+WHERE created_by IS NULL;
+ALTER TABLE groups ALTER COLUMN created_by SET NOT NULL;
+
+DELETE FROM group_members WHERE source = 'ldap';
+ALTER TABLE group_members DROP CONSTRAINT group_members_source_check;
+ALTER TABLE group_members ADD CONSTRAINT group_members_source_check
+    CHECK (source IN ('local', 'oidc'));
+
+ALTER TABLE users DROP COLUMN IF EXISTS ldap_dn;
@@ -0,0 +1,11 @@
+-- LDAP / Active Directory 认证与组同步
+-- ldap_dn: 目录中用户条目的 DN（统一小写），非空表示该用户由目录管理，从目录中消失时会被停用
+ALTER TABLE users ADD COLUMN ldap_dn VARCHAR(512) UNIQUE;
+
+-- 组成员来源增加 ldap（由目录同步维护）
+ALTER TABLE group_members DROP CONSTRAINT group_members_source_check;
+ALTER TABLE group_members ADD CONSTRAINT group_members_source_check
+    CHECK (source IN ('local', 'oidc', 'ldap'));
+
+-- 目录同步自动创建的组没有创建者
+ALTER TABLE groups ALTER COLUMN created_by DROP NOT NULL;
@@ -0,0 +1,21 @@
+-- 个人访问令牌与服务账号
+-- is_service_account: 服务账号只能通过访问令牌调用 API，不能交互式登录
+ALTER TABLE users ADD COLUMN is_service_account BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- 访问令牌只保存 SHA-256 摘要，token_prefix 用于在列表中辨认令牌
+-- scope: read 只允许读取，write 与用户本身的权限相同
+-- folder_id: 非空时只能访问该文件夹及其子孙文档
+CREATE TABLE api_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    token_prefix VARCHAR(16) NOT NULL,
+    scope VARCHAR(20) NOT NULL DEFAULT 'write' CHECK (scope IN ('read', 'write')),
+    folder_id UUID REFERENCES documents(id) ON DELETE CASCADE,
+    expires_at TIMESTAMP,
+    last_used_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_api_tokens_user ON api_tokens(user_id);
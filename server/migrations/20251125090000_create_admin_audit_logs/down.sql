@@ -0,0 +1,3 @@
+ALTER TABLE user_sessions DROP COLUMN IF EXISTS impersonator_id;
+
+DROP TABLE IF EXISTS admin_audit_logs;
@@ -0,0 +1,19 @@
+-- 管理员用户管理审计表
+-- 不对 users 建外键，保证审计记录不随用户删除；用户名在记录时保存一份
+CREATE TABLE admin_audit_logs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    admin_id UUID NOT NULL,
+    admin_username VARCHAR(100) NOT NULL,
+    action VARCHAR(50) NOT NULL,
+    target_user_id UUID NOT NULL,
+    target_username VARCHAR(100) NOT NULL,
+    details TEXT,
+    ip_address VARCHAR(45),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_admin_audit_logs_target ON admin_audit_logs(target_user_id);
+CREATE INDEX idx_admin_audit_logs_created ON admin_audit_logs(created_at);
+
+-- 管理员以其他用户身份登录（代登录）时创建的会话，记录发起的管理员
+ALTER TABLE user_sessions ADD COLUMN impersonator_id UUID REFERENCES users(id) ON DELETE CASCADE;
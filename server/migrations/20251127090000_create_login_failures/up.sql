@@ -0,0 +1,10 @@
+-- 登录失败计数，用于按账号和按 IP 限制暴力破解
+-- key: user:<用户名小写> 或 ip:<客户端地址>
+CREATE TABLE login_failures (
+    key VARCHAR(150) PRIMARY KEY,
+    failure_count INTEGER NOT NULL DEFAULT 0,
+    last_failure_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    locked_until TIMESTAMP
+);
+
+CREATE INDEX idx_login_failures_last_failure ON login_failures(last_failure_at);
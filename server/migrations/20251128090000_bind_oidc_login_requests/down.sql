@@ -0,0 +1 @@
+ALTER TABLE oidc_login_requests DROP COLUMN IF EXISTS login_request_hash;
@@ -0,0 +1,7 @@
+-- 单点登录请求绑定发起登录的浏览器：发起时返回 login_request_id，由前端保存并在回调时一并提交，
+-- 防止攻击者将自己的授权码和 state 塞给受害者，使其登录到攻击者的账号
+-- login_request_hash: login_request_id 的 SHA-256 摘要
+-- 进行中的请求无法补上绑定，直接清除，用户重新发起登录即可
+DELETE FROM oidc_login_requests;
+
+ALTER TABLE oidc_login_requests ADD COLUMN login_request_hash VARCHAR(64) NOT NULL;
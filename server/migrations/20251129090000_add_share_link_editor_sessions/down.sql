@@ -0,0 +1,9 @@
+DELETE FROM editor_sessions WHERE user_id IS NULL;
+
+DROP INDEX IF EXISTS idx_editor_sessions_active;
+CREATE UNIQUE INDEX idx_editor_sessions_active ON editor_sessions(document_id, user_id) WHERE ended_at IS NULL;
+
+ALTER TABLE editor_sessions DROP CONSTRAINT IF EXISTS editor_sessions_editor_check;
+ALTER TABLE editor_sessions ALTER COLUMN user_id SET NOT NULL;
+ALTER TABLE editor_sessions DROP COLUMN IF EXISTS editor_id;
+ALTER TABLE editor_sessions DROP COLUMN IF EXISTS share_link_id;
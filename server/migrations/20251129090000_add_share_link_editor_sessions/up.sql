@@ -0,0 +1,14 @@
+-- 通过分享链接匿名编辑的编辑者也记录会话
+-- editor_id: Document Server 中的编辑者 ID，用户为用户 ID，分享链接编辑者为 share_<链接 ID>_<随机后缀>
+-- share_link_id: 分享链接编辑者所用的链接，此时 user_id 为空
+ALTER TABLE editor_sessions ADD COLUMN share_link_id UUID REFERENCES share_links(id) ON DELETE CASCADE;
+ALTER TABLE editor_sessions ADD COLUMN editor_id VARCHAR(128);
+UPDATE editor_sessions SET editor_id = user_id::text;
+ALTER TABLE editor_sessions ALTER COLUMN editor_id SET NOT NULL;
+ALTER TABLE editor_sessions ALTER COLUMN user_id DROP NOT NULL;
+ALTER TABLE editor_sessions ADD CONSTRAINT editor_sessions_editor_check
+    CHECK ((user_id IS NULL) <> (share_link_id IS NULL));
+
+-- 每个编辑者在同一文档上最多只有一个进行中的会话
+DROP INDEX IF EXISTS idx_editor_sessions_active;
+CREATE UNIQUE INDEX idx_editor_sessions_active ON editor_sessions(document_id, editor_id) WHERE ended_at IS NULL;
@@ -10,6 +10,17 @@ pub struct Config {
     pub meilisearch: MeilisearchConfig,
     pub onlyoffice: OnlyOfficeConfig,
     pub app: AppConfig,
+    pub retention: RetentionConfig,
+    pub tags: TagConfig,
+    pub thumbnail: ThumbnailConfig,
+    pub password: PasswordConfig,
+    pub mail: MailConfig,
+    pub totp: TotpConfig,
+    pub oidc: OidcConfig,
+    pub ldap: LdapConfig,
+    pub registration: RegistrationConfig,
+    pub login_throttle: LoginThrottleConfig,
+    pub rate_limit: RateLimitConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -26,7 +37,10 @@ pub struct ServerConfig {
 #[derive(Debug, Clone, Deserialize)]
 pub struct JwtConfig {
     pub secret: String,
+    /// Lifetime of access tokens in seconds
     pub expiration: i64,
+    /// Lifetime of refresh tokens (login sessions) in seconds
+    pub refresh_expiration: i64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -56,6 +70,115 @@ pub struct AppConfig {
     pub url: String,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetentionConfig {
+    pub sweep_interval_secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TagConfig {
+    pub controlled_vocabulary: bool, // 只允许使用管理员定义的标签
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThumbnailConfig {
+    pub interval_secs: u64,
+    pub pdftoppm_path: String, // poppler-utils 提供的 PDF 渲染工具
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PasswordConfig {
+    pub login_enabled: bool, // 是否允许本地密码登录，只使用单点登录的部署可关闭
+    pub min_length: usize,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    pub breached_list_path: Option<String>, // 泄露密码列表文件，每行一个密码
+    pub reset_expiration: i64,              // 重置密码令牌有效期（秒）
+    pub reset_url: String,                  // 邮件中的重置页面地址，令牌附加在 token 参数中
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MailConfig {
+    pub transport: String, // smtp 或 log（仅写入日志，用于开发环境）
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub from: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TotpConfig {
+    pub issuer: String,            // 身份验证器应用中显示的发行方名称，不能包含冒号
+    pub challenge_expiration: i64, // 两步登录中挑战令牌的有效期（秒）
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcConfig {
+    pub enabled: bool,
+    pub issuer_url: String,               // 通过 {issuer_url}/.well-known/openid-configuration 自动发现端点
+    pub client_id: String,
+    pub client_secret: Option<String>,    // 公共客户端可不设置，仅依赖 PKCE
+    pub redirect_url: String,             // 前端回调页面地址，需在 IdP 中登记
+    pub scopes: Vec<String>,
+    pub groups_claim: Option<String>,     // ID Token 中组声明的名称，未设置时不同步组
+    pub trust_unverified_email: bool,     // 是否按未验证（email_verified 不为 true）的邮箱关联已有用户
+    pub trust_idp_mfa: bool,              // 是否信任 IdP 的多因素认证，为 true 时单点登录不再要求本系统的两步验证
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LdapConfig {
+    pub enabled: bool,
+    pub url: String,                    // ldap:// 或 ldaps://
+    pub starttls: bool,
+    pub bind_dn: String,                // 用于查找用户和同步目录的服务账号
+    pub bind_password: String,
+    pub user_base_dn: String,
+    pub user_filter: String,            // 匹配所有可登录用户，AD 可用 (&(objectClass=user)(!(userAccountControl:1.2.840.113556.1.4.803:=2))) 排除已禁用账号
+    pub username_attribute: String,     // OpenLDAP 为 uid，AD 为 sAMAccountName
+    pub email_attribute: String,
+    pub name_attribute: String,
+    pub group_base_dn: String,
+    pub group_filter: String,
+    pub group_name_attribute: String,
+    pub group_member_attribute: String, // 组条目中保存成员 DN 的属性
+    pub sync_interval_secs: u64,        // 目录同步间隔（秒），0 表示不定时同步
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegistrationConfig {
+    pub mode: String,                 // open、invite（仅限受邀用户）、domain（仅限允许的邮箱域名）或 disabled
+    pub allowed_domains: Vec<String>, // mode 为 domain 时允许注册的邮箱域名
+    pub email_verification: bool,     // 自助注册的账号是否需要先验证邮箱才能登录
+    pub verification_expiration: i64, // 邮箱验证令牌有效期（秒）
+    pub verification_url: String,     // 邮件中的验证页面地址，令牌附加在 token 参数中
+    pub invitation_expiration: i64,   // 邀请链接有效期（秒）
+    pub invitation_url: String,       // 邀请邮件中的注册页面地址，令牌附加在 token 参数中
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoginThrottleConfig {
+    pub enabled: bool,
+    pub account_free_attempts: i32,    // 同一账号连续失败多少次后开始延迟
+    pub account_lockout_attempts: i32, // 同一账号连续失败多少次后临时锁定
+    pub ip_free_attempts: i32,         // 同一 IP 连续失败多少次后开始延迟，NAT 后的多个用户共用一个地址，应比账号宽松
+    pub ip_lockout_attempts: i32,
+    pub base_delay: i64,               // 首次延迟（秒），之后每失败一次翻倍
+    pub max_delay: i64,                // 延迟上限（秒）
+    pub lockout_duration: i64,         // 锁定时长（秒）
+    pub failure_window: i64,           // 距最后一次失败超过该时间（秒）后清零计数
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitConfig {
+    pub trust_proxy: bool,       // 是否按 X-Forwarded-For / X-Real-IP 识别客户端，仅在反向代理之后开启
+    pub auth_per_minute: u32,    // /api/auth/* 每个客户端每分钟的请求数，0 表示不限制
+    pub shares_per_minute: u32,  // /api/shares/access/*，限制猜测分享令牌
+    pub api_per_minute: u32,     // 其余 /api/* 路由
+}
+
 impl Config {
     pub fn from_env() -> Result<Self, config::ConfigError> {
         dotenv::dotenv().ok();
@@ -78,9 +201,13 @@ impl Config {
             secret: env::var("JWT_SECRET")
                 .expect("JWT_SECRET must be set"),
             expiration: env::var("JWT_EXPIRATION")
-                .unwrap_or_else(|_| "86400".to_string())
+                .unwrap_or_else(|_| "900".to_string())
                 .parse()
                 .expect("JWT_EXPIRATION must be a valid i64"),
+            refresh_expiration: env::var("JWT_REFRESH_EXPIRATION")
+                .unwrap_or_else(|_| "2592000".to_string())
+                .parse()
+                .expect("JWT_REFRESH_EXPIRATION must be a valid i64"),
         };
 
         let minio = MinioConfig {
@@ -116,6 +243,242 @@ impl Config {
                 .expect("APP_URL must be set"),
         };
 
+        let retention = RetentionConfig {
+            sweep_interval_secs: env::var("RETENTION_SWEEP_INTERVAL")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .expect("RETENTION_SWEEP_INTERVAL must be a valid u64"),
+        };
+
+        let tags = TagConfig {
+            controlled_vocabulary: env::var("TAGS_CONTROLLED_VOCABULARY")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .expect("TAGS_CONTROLLED_VOCABULARY must be true or false"),
+        };
+
+        let thumbnail = ThumbnailConfig {
+            interval_secs: env::var("THUMBNAIL_INTERVAL")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .expect("THUMBNAIL_INTERVAL must be a valid u64"),
+            pdftoppm_path: env::var("PDFTOPPM_PATH")
+                .unwrap_or_else(|_| "pdftoppm".to_string()),
+        };
+
+        let password = PasswordConfig {
+            login_enabled: env::var("PASSWORD_LOGIN_ENABLED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .expect("PASSWORD_LOGIN_ENABLED must be true or false"),
+            min_length: env::var("PASSWORD_MIN_LENGTH")
+                .unwrap_or_else(|_| "8".to_string())
+                .parse()
+                .expect("PASSWORD_MIN_LENGTH must be a valid usize"),
+            require_uppercase: env::var("PASSWORD_REQUIRE_UPPERCASE")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .expect("PASSWORD_REQUIRE_UPPERCASE must be true or false"),
+            require_lowercase: env::var("PASSWORD_REQUIRE_LOWERCASE")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .expect("PASSWORD_REQUIRE_LOWERCASE must be true or false"),
+            require_digit: env::var("PASSWORD_REQUIRE_DIGIT")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .expect("PASSWORD_REQUIRE_DIGIT must be true or false"),
+            require_symbol: env::var("PASSWORD_REQUIRE_SYMBOL")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .expect("PASSWORD_REQUIRE_SYMBOL must be true or false"),
+            breached_list_path: env::var("PASSWORD_BREACHED_LIST").ok().filter(|p| !p.is_empty()),
+            reset_expiration: env::var("PASSWORD_RESET_EXPIRATION")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .expect("PASSWORD_RESET_EXPIRATION must be a valid i64"),
+            reset_url: env::var("PASSWORD_RESET_URL")
+                .unwrap_or_else(|_| format!("{}/reset-password", app.url)),
+        };
+
+        let mail = MailConfig {
+            transport: env::var("MAIL_TRANSPORT")
+                .unwrap_or_else(|_| "log".to_string()),
+            smtp_host: env::var("SMTP_HOST")
+                .unwrap_or_else(|_| "localhost".to_string()),
+            smtp_port: env::var("SMTP_PORT")
+                .unwrap_or_else(|_| "1025".to_string())
+                .parse()
+                .expect("SMTP_PORT must be a valid u16"),
+            smtp_username: env::var("SMTP_USERNAME").ok(),
+            smtp_password: env::var("SMTP_PASSWORD").ok(),
+            from: env::var("MAIL_FROM")
+                .unwrap_or_else(|_| "noreply@localhost".to_string()),
+        };
+
+        let totp = TotpConfig {
+            issuer: env::var("TOTP_ISSUER")
+                .unwrap_or_else(|_| "Document Management System".to_string()),
+            challenge_expiration: env::var("TOTP_CHALLENGE_EXPIRATION")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .expect("TOTP_CHALLENGE_EXPIRATION must be a valid i64"),
+        };
+
+        let oidc = OidcConfig {
+            enabled: env::var("OIDC_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .expect("OIDC_ENABLED must be true or false"),
+            issuer_url: env::var("OIDC_ISSUER_URL")
+                .unwrap_or_default(),
+            client_id: env::var("OIDC_CLIENT_ID")
+                .unwrap_or_default(),
+            client_secret: env::var("OIDC_CLIENT_SECRET").ok().filter(|s| !s.is_empty()),
+            redirect_url: env::var("OIDC_REDIRECT_URL")
+                .unwrap_or_else(|_| format!("{}/oidc/callback", app.url)),
+            scopes: env::var("OIDC_SCOPES")
+                .unwrap_or_else(|_| "openid email profile".to_string())
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|scope| !scope.is_empty())
+                .map(|scope| scope.to_string())
+                .collect(),
+            groups_claim: Some(env::var("OIDC_GROUPS_CLAIM").unwrap_or_else(|_| "groups".to_string()))
+                .filter(|claim| !claim.is_empty()),
+            trust_unverified_email: env::var("OIDC_TRUST_UNVERIFIED_EMAIL")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .expect("OIDC_TRUST_UNVERIFIED_EMAIL must be true or false"),
+            trust_idp_mfa: env::var("OIDC_TRUST_IDP_MFA")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .expect("OIDC_TRUST_IDP_MFA must be true or false"),
+        };
+
+        let ldap = LdapConfig {
+            enabled: env::var("LDAP_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .expect("LDAP_ENABLED must be true or false"),
+            url: env::var("LDAP_URL")
+                .unwrap_or_else(|_| "ldap://localhost:389".to_string()),
+            starttls: env::var("LDAP_STARTTLS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .expect("LDAP_STARTTLS must be true or false"),
+            bind_dn: env::var("LDAP_BIND_DN")
+                .unwrap_or_default(),
+            bind_password: env::var("LDAP_BIND_PASSWORD")
+                .unwrap_or_default(),
+            user_base_dn: env::var("LDAP_USER_BASE_DN")
+                .unwrap_or_default(),
+            user_filter: env::var("LDAP_USER_FILTER")
+                .unwrap_or_else(|_| "(objectClass=inetOrgPerson)".to_string()),
+            username_attribute: env::var("LDAP_USERNAME_ATTRIBUTE")
+                .unwrap_or_else(|_| "uid".to_string()),
+            email_attribute: env::var("LDAP_EMAIL_ATTRIBUTE")
+                .unwrap_or_else(|_| "mail".to_string()),
+            name_attribute: env::var("LDAP_NAME_ATTRIBUTE")
+                .unwrap_or_else(|_| "cn".to_string()),
+            group_base_dn: env::var("LDAP_GROUP_BASE_DN")
+                .unwrap_or_default(),
+            group_filter: env::var("LDAP_GROUP_FILTER")
+                .unwrap_or_else(|_| "(objectClass=groupOfNames)".to_string()),
+            group_name_attribute: env::var("LDAP_GROUP_NAME_ATTRIBUTE")
+                .unwrap_or_else(|_| "cn".to_string()),
+            group_member_attribute: env::var("LDAP_GROUP_MEMBER_ATTRIBUTE")
+                .unwrap_or_else(|_| "member".to_string()),
+            sync_interval_secs: env::var("LDAP_SYNC_INTERVAL")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .expect("LDAP_SYNC_INTERVAL must be a valid u64"),
+        };
+
+        let registration = RegistrationConfig {
+            mode: env::var("REGISTRATION_MODE")
+                .unwrap_or_else(|_| "open".to_string())
+                .to_lowercase(),
+            allowed_domains: env::var("REGISTRATION_ALLOWED_DOMAINS")
+                .unwrap_or_default()
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|domain| !domain.is_empty())
+                .map(|domain| domain.trim_start_matches('@').to_lowercase())
+                .collect(),
+            email_verification: env::var("REGISTRATION_EMAIL_VERIFICATION")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .expect("REGISTRATION_EMAIL_VERIFICATION must be true or false"),
+            verification_expiration: env::var("REGISTRATION_VERIFICATION_EXPIRATION")
+                .unwrap_or_else(|_| "86400".to_string())
+                .parse()
+                .expect("REGISTRATION_VERIFICATION_EXPIRATION must be a valid i64"),
+            verification_url: env::var("REGISTRATION_VERIFICATION_URL")
+                .unwrap_or_else(|_| format!("{}/verify-email", app.url)),
+            invitation_expiration: env::var("REGISTRATION_INVITATION_EXPIRATION")
+                .unwrap_or_else(|_| "604800".to_string())
+                .parse()
+                .expect("REGISTRATION_INVITATION_EXPIRATION must be a valid i64"),
+            invitation_url: env::var("REGISTRATION_INVITATION_URL")
+                .unwrap_or_else(|_| format!("{}/register", app.url)),
+        };
+
+        let login_throttle = LoginThrottleConfig {
+            enabled: env::var("LOGIN_THROTTLE_ENABLED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_ENABLED must be true or false"),
+            account_free_attempts: env::var("LOGIN_THROTTLE_ACCOUNT_FREE_ATTEMPTS")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_ACCOUNT_FREE_ATTEMPTS must be a valid i32"),
+            account_lockout_attempts: env::var("LOGIN_THROTTLE_ACCOUNT_LOCKOUT_ATTEMPTS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_ACCOUNT_LOCKOUT_ATTEMPTS must be a valid i32"),
+            ip_free_attempts: env::var("LOGIN_THROTTLE_IP_FREE_ATTEMPTS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_IP_FREE_ATTEMPTS must be a valid i32"),
+            ip_lockout_attempts: env::var("LOGIN_THROTTLE_IP_LOCKOUT_ATTEMPTS")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_IP_LOCKOUT_ATTEMPTS must be a valid i32"),
+            base_delay: env::var("LOGIN_THROTTLE_BASE_DELAY")
+                .unwrap_or_else(|_| "1".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_BASE_DELAY must be a valid i64"),
+            max_delay: env::var("LOGIN_THROTTLE_MAX_DELAY")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_MAX_DELAY must be a valid i64"),
+            lockout_duration: env::var("LOGIN_THROTTLE_LOCKOUT_DURATION")
+                .unwrap_or_else(|_| "900".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_LOCKOUT_DURATION must be a valid i64"),
+            failure_window: env::var("LOGIN_THROTTLE_FAILURE_WINDOW")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_FAILURE_WINDOW must be a valid i64"),
+        };
+
+        let rate_limit = RateLimitConfig {
+            trust_proxy: env::var("RATE_LIMIT_TRUST_PROXY")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .expect("RATE_LIMIT_TRUST_PROXY must be true or false"),
+            auth_per_minute: env::var("RATE_LIMIT_AUTH_PER_MINUTE")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .expect("RATE_LIMIT_AUTH_PER_MINUTE must be a valid u32"),
+            shares_per_minute: env::var("RATE_LIMIT_SHARES_PER_MINUTE")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .expect("RATE_LIMIT_SHARES_PER_MINUTE must be a valid u32"),
+            api_per_minute: env::var("RATE_LIMIT_API_PER_MINUTE")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse()
+                .expect("RATE_LIMIT_API_PER_MINUTE must be a valid u32"),
+        };
+
         Ok(Config {
             database,
             server,
@@ -124,6 +487,17 @@ impl Config {
             meilisearch,
             onlyoffice,
             app,
+            retention,
+            tags,
+            thumbnail,
+            password,
+            mail,
+            totp,
+            oidc,
+            ldap,
+            registration,
+            login_throttle,
+            rate_limit,
         })
     }
 
@@ -9,6 +9,8 @@ pub type DbConnection = r2d2::PooledConnection<ConnectionManager<PgConnection>>;
 pub struct AppState {
     pub pool: Arc<DbPool>,
     pub config: Arc<crate::config::Config>,
+    /// Request counts for the rate-limiting layer
+    pub rate_limiter: Arc<crate::services::RateLimiter>,
 }
 
 pub fn create_pool(database_url: &str) -> Result<DbPool, diesel::r2d2::PoolError> {
@@ -23,6 +25,7 @@ impl AppState {
         Self {
             pool: Arc::new(pool),
             config: Arc::new(config),
+            rate_limiter: Arc::new(crate::services::RateLimiter::default()),
         }
     }
 
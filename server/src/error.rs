@@ -1,5 +1,5 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -15,6 +15,10 @@ pub enum AppError {
     BadRequest(String),
     InternalServerError(String),
     ValidationError(String),
+    QuotaExceeded(String),
+    Locked(String),
+    /// Seconds until the client may try again, for `Retry-After`
+    TooManyRequests(String, u64),
 }
 
 impl fmt::Display for AppError {
@@ -27,6 +31,9 @@ impl fmt::Display for AppError {
             AppError::BadRequest(msg) => write!(f, "Bad request: {}", msg),
             AppError::InternalServerError(msg) => write!(f, "Internal server error: {}", msg),
             AppError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
+            AppError::QuotaExceeded(msg) => write!(f, "Quota exceeded: {}", msg),
+            AppError::Locked(msg) => write!(f, "Locked: {}", msg),
+            AppError::TooManyRequests(msg, _) => write!(f, "Too many requests: {}", msg),
         }
     }
 }
@@ -35,6 +42,11 @@ impl std::error::Error for AppError {}
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let retry_after = match &self {
+            AppError::TooManyRequests(_, seconds) => Some(*seconds),
+            _ => None,
+        };
+
         let (status, error_message) = match self {
             AppError::DatabaseError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
@@ -43,13 +55,22 @@ impl IntoResponse for AppError {
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::InternalServerError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             AppError::ValidationError(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::QuotaExceeded(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg),
+            AppError::Locked(msg) => (StatusCode::LOCKED, msg),
+            AppError::TooManyRequests(msg, _) => (StatusCode::TOO_MANY_REQUESTS, msg),
         };
 
         let body = Json(json!({
             "error": error_message,
         }));
 
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+
+        if let Some(seconds) = retry_after {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+
+        response
     }
 }
 
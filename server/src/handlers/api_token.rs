@@ -0,0 +1,149 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    db::AppState,
+    error::{AppError, Result},
+    middleware::AuthUser,
+    models::{
+        api_token::{ApiToken, CreateApiTokenRequest, CreateApiTokenResponse, CreateServiceAccountRequest},
+        user::UserResponse,
+    },
+    services::ApiTokenService,
+};
+
+pub async fn list_api_tokens(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<ApiToken>>> {
+    let mut conn = state.get_connection()?;
+
+    let tokens = ApiTokenService::list(&mut conn, auth_user.claims.user_id()?)?;
+
+    Ok(Json(tokens))
+}
+
+pub async fn create_api_token(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateApiTokenRequest>,
+) -> Result<Json<CreateApiTokenResponse>> {
+    auth_user.ensure_not_impersonating()?;
+
+    payload.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let mut conn = state.get_connection()?;
+
+    let response = ApiTokenService::create(&mut conn, auth_user.claims.user_id()?, payload)?;
+
+    Ok(Json(response))
+}
+
+pub async fn revoke_api_token(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(token_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.ensure_not_impersonating()?;
+
+    let mut conn = state.get_connection()?;
+
+    if !ApiTokenService::revoke(&mut conn, auth_user.claims.user_id()?, token_id)? {
+        return Err(AppError::NotFound("API token not found".to_string()));
+    }
+
+    Ok(Json(serde_json::json!({
+        "message": "API token revoked"
+    })))
+}
+
+pub async fn list_service_accounts(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<UserResponse>>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let accounts = ApiTokenService::list_service_accounts(&mut conn)?
+        .into_iter()
+        .map(UserResponse::from)
+        .collect();
+
+    Ok(Json(accounts))
+}
+
+pub async fn create_service_account(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateServiceAccountRequest>,
+) -> Result<Json<UserResponse>> {
+    auth_user.require_admin()?;
+
+    payload.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let mut conn = state.get_connection()?;
+
+    let account = ApiTokenService::create_service_account(&mut conn, payload)?;
+
+    Ok(Json(account.into()))
+}
+
+pub async fn list_service_account_tokens(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(account_id): Path<Uuid>,
+) -> Result<Json<Vec<ApiToken>>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+    let account = ApiTokenService::find_service_account(&mut conn, account_id)?;
+
+    let tokens = ApiTokenService::list(&mut conn, account.id)?;
+
+    Ok(Json(tokens))
+}
+
+pub async fn create_service_account_token(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(account_id): Path<Uuid>,
+    Json(payload): Json<CreateApiTokenRequest>,
+) -> Result<Json<CreateApiTokenResponse>> {
+    auth_user.require_admin()?;
+
+    payload.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let mut conn = state.get_connection()?;
+    let account = ApiTokenService::find_service_account(&mut conn, account_id)?;
+
+    let response = ApiTokenService::create(&mut conn, account.id, payload)?;
+
+    Ok(Json(response))
+}
+
+pub async fn revoke_service_account_token(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path((account_id, token_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+    let account = ApiTokenService::find_service_account(&mut conn, account_id)?;
+
+    if !ApiTokenService::revoke(&mut conn, account.id, token_id)? {
+        return Err(AppError::NotFound("API token not found".to_string()));
+    }
+
+    Ok(Json(serde_json::json!({
+        "message": "API token revoked"
+    })))
+}
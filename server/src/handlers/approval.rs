@@ -0,0 +1,132 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    error::{AppError, Result},
+    middleware::AuthUser,
+    models::{
+        approval::{
+            ApprovalWorkflowResponse, DecideApprovalRequest, PendingReviewResponse,
+            SubmitApprovalRequest,
+        },
+        document::Document,
+        permission::PermissionType,
+    },
+    schema::documents,
+    services::{ApprovalService, PermissionService, SearchService},
+};
+
+pub async fn submit_for_approval(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+    Json(payload): Json<SubmitApprovalRequest>,
+) -> Result<Json<ApprovalWorkflowResponse>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let document = documents::table
+        .find(document_id)
+        .select(Document::as_select())
+        .first::<Document>(&mut conn)?;
+
+    let workflow = ApprovalService::submit(&mut conn, &document, user_id, auth_user.is_admin(), payload)?;
+
+    reindex_document(&state, &mut conn, document_id).await?;
+
+    Ok(Json(workflow))
+}
+
+pub async fn list_document_approvals(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+) -> Result<Json<Vec<ApprovalWorkflowResponse>>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    // Check read permission
+    let can_read = PermissionService::check_permission(
+        &mut conn,
+        user_id,
+        document_id,
+        PermissionType::Read,
+    )?;
+
+    if !can_read {
+        return Err(AppError::Forbidden("No permission to view this document".to_string()));
+    }
+
+    let history = ApprovalService::history_for_document(&mut conn, document_id)?;
+
+    Ok(Json(history))
+}
+
+pub async fn list_pending_reviews(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<PendingReviewResponse>>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let pending = ApprovalService::pending_for_user(&mut conn, user_id)?;
+
+    Ok(Json(pending))
+}
+
+pub async fn decide_approval_step(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(step_id): Path<Uuid>,
+    Json(payload): Json<DecideApprovalRequest>,
+) -> Result<Json<ApprovalWorkflowResponse>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let workflow = ApprovalService::decide(
+        &mut conn,
+        step_id,
+        user_id,
+        payload.decision,
+        payload.comment,
+    )?;
+
+    reindex_document(&state, &mut conn, workflow.workflow.document_id).await?;
+
+    Ok(Json(workflow))
+}
+
+pub async fn cancel_approval(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(workflow_id): Path<Uuid>,
+) -> Result<Json<ApprovalWorkflowResponse>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let workflow = ApprovalService::cancel(&mut conn, workflow_id, user_id, auth_user.is_admin())?;
+
+    reindex_document(&state, &mut conn, workflow.workflow.document_id).await?;
+
+    Ok(Json(workflow))
+}
+
+// Status changes are reflected in the search index
+async fn reindex_document(
+    state: &AppState,
+    conn: &mut crate::db::DbConnection,
+    document_id: Uuid,
+) -> Result<()> {
+    let document = documents::table
+        .find(document_id)
+        .select(Document::as_select())
+        .first::<Document>(conn)?;
+
+    let search_service = SearchService::new(&state.config.meilisearch)?;
+    search_service.update_document(document).await
+}
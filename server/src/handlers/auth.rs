@@ -1,24 +1,59 @@
-use axum::{extract::State, Json};
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Path, State},
+    http::HeaderMap,
+    Json,
+};
 use diesel::prelude::*;
+use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
     db::AppState,
+    handlers::registration::send_verification_email,
     error::{AppError, Result},
-    models::user::{CreateUserRequest, LoginRequest, LoginResponse, NewUser, User, UserResponse, UserRole},
+    middleware::AuthUser,
+    models::{
+        group::GROUP_MEMBER_SOURCE_LDAP,
+        session::{RefreshTokenRequest, SessionResponse},
+        user::{
+            ChangePasswordRequest, CreateUserRequest, ForgotPasswordRequest, LoginOutcome, LoginRequest,
+            LoginResponse, NewUser, ResetPasswordRequest, User, UserResponse, UserRole,
+        },
+    },
     schema::users,
-    utils::{hash_password, verify_password, Claims, encode_jwt},
+    services::{
+        mailer::{self, MailMessage},
+        session::ClientInfo,
+        GroupSyncService, LdapService, LoginThrottleService, PasswordPolicyService, PasswordResetService,
+        RateLimiter, RegistrationService, SessionService, TotpService,
+    },
+    utils::{hash_password, verify_password},
 };
 
 pub async fn register(
     State(state): State<AppState>,
     Json(payload): Json<CreateUserRequest>,
 ) -> Result<Json<UserResponse>> {
+    ensure_password_login(&state)?;
+
     payload.validate()
         .map_err(|e| AppError::ValidationError(e.to_string()))?;
 
     let mut conn = state.get_connection()?;
 
+    let invitation = payload
+        .invitation_token
+        .as_deref()
+        .map(|token| RegistrationService::find_invitation(&mut conn, token))
+        .transpose()?;
+
+    RegistrationService::ensure_allowed(&state.config.registration, &payload.email, invitation.as_ref())?;
+
+    // Sign-ups whose verification links all expired don't keep the name or address
+    RegistrationService::release_abandoned(&mut conn, &payload.username, &payload.email)?;
+
     // Check if username or email already exists
     let existing_user = users::table
         .filter(
@@ -32,6 +67,8 @@ pub async fn register(
         return Err(AppError::BadRequest("Username or email already exists".to_string()));
     }
 
+    PasswordPolicyService::validate(&state.config.password, &payload.password)?;
+
     // Hash password
     let password_hash = hash_password(&payload.password)?;
 
@@ -41,20 +78,35 @@ pub async fn register(
         email: payload.email,
         password_hash,
         full_name: payload.full_name,
-        role: UserRole::User.as_str().to_string(),
+        role: invitation
+            .as_ref()
+            .map_or_else(|| UserRole::User.as_str().to_string(), |invitation| invitation.role.clone()),
     };
 
-    let user: User = diesel::insert_into(users::table)
-        .values(&new_user)
-        .get_result(&mut conn)?;
+    // The invitation reached the address, so it needs no further proof
+    let verified = invitation.is_some() || !state.config.registration.email_verification;
+
+    let user = RegistrationService::register(&mut conn, new_user, invitation.as_ref(), verified)?;
+
+    if !verified {
+        let token = RegistrationService::create_verification_token(
+            &mut conn,
+            user.id,
+            state.config.registration.verification_expiration,
+        )?;
+
+        send_verification_email(&state, &user, &token).await?;
+    }
 
     Ok(Json(user.into()))
 }
 
 pub async fn login(
     State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
-) -> Result<Json<LoginResponse>> {
+) -> Result<Json<LoginOutcome>> {
     // 验证输入
     payload.validate()
         .map_err(|e| AppError::ValidationError(e.to_string()))?;
@@ -63,37 +115,182 @@ pub async fn login(
     
     let mut conn = state.get_connection()?;
 
+    let throttle = &state.config.login_throttle;
+    let throttle_keys = [
+        LoginThrottleService::account_key(&payload.username),
+        LoginThrottleService::ip_key(&RateLimiter::client_ip(&state.config.rate_limit, &headers, peer)),
+    ];
+
+    // Before any password is checked, so turned-away guesses cost no hashing
+    LoginThrottleService::check(&mut conn, throttle, &throttle_keys)?;
+
     // Find user by username
-    let user = users::table
+    let local_user = users::table
         .filter(users::username.eq(&payload.username))
         .first::<User>(&mut conn)
-        .map_err(|_| AppError::Unauthorized("Invalid credentials".to_string()))?;
+        .optional()?;
 
-    // Check if user is active
-    if !user.is_active {
-        return Err(AppError::Unauthorized("Account is not active".to_string()));
-    }
+    let user = match local_user {
+        Some(user) if user.ldap_dn.is_none() => {
+            ensure_password_login(&state)?;
+
+            // Verify password
+            let is_valid = verify_password(&payload.password, &user.password_hash)?;
+            if !is_valid {
+                LoginThrottleService::record_failure(&mut conn, throttle, &throttle_keys)?;
+                return Err(AppError::Unauthorized("Invalid credentials".to_string()));
+            }
+
+            // Only reported after the password, or the state would tell
+            // anyone which usernames exist
+            if !user.is_active {
+                if RegistrationService::awaiting_verification(&mut conn, &user)? {
+                    return Err(AppError::Unauthorized("Email address is not verified".to_string()));
+                }
+
+                return Err(AppError::Unauthorized("Account is not active".to_string()));
+            }
+
+            if user.is_service_account {
+                return Err(AppError::Unauthorized(
+                    "Service accounts can only use API tokens".to_string(),
+                ));
+            }
+
+            user
+        }
+        // Directory accounts, including ones that have never logged in here
+        _ if state.config.ldap.enabled => {
+            let ldap_user = LdapService::new(&state.config.ldap)?
+                .authenticate(&payload.username, &payload.password)
+                .await?;
 
-    // Verify password
-    let is_valid = verify_password(&payload.password, &user.password_hash)?;
-    if !is_valid {
-        return Err(AppError::Unauthorized("Invalid credentials".to_string()));
+            let Some(ldap_user) = ldap_user else {
+                LoginThrottleService::record_failure(&mut conn, throttle, &throttle_keys)?;
+                return Err(AppError::Unauthorized("Invalid credentials".to_string()));
+            };
+
+            let user = LdapService::find_or_create_user(&mut conn, &ldap_user)?;
+
+            if !user.is_active {
+                return Err(AppError::Unauthorized("Account is not active".to_string()));
+            }
+
+            GroupSyncService::sync_memberships(&mut conn, user.id, GROUP_MEMBER_SOURCE_LDAP, &ldap_user.groups, None)?;
+
+            user
+        }
+        _ => {
+            ensure_password_login(&state)?;
+            LoginThrottleService::record_failure(&mut conn, throttle, &throttle_keys)?;
+            return Err(AppError::Unauthorized("Invalid credentials".to_string()));
+        }
+    };
+
+    // The session is only started once the second factor checks out
+    if user.totp_enabled || user.totp_required {
+        let challenge = TotpService::challenge(&state.config.jwt, &state.config.totp, &user)?;
+        return Ok(Json(LoginOutcome::TotpRequired(challenge)));
     }
 
-    // Generate JWT token
-    let claims = Claims::new(
-        user.id,
-        user.username.clone(),
-        user.role.clone(),
-        state.config.jwt.expiration,
-    );
+    // Only a complete login clears the account, or a known password would
+    // make guessing the second factor free
+    LoginThrottleService::reset(&mut conn, &throttle_keys[0])?;
 
-    let token = encode_jwt(&claims, &state.config.jwt.secret)?;
+    // Start a session and issue access and refresh tokens
+    let response = SessionService::start(
+        &mut conn,
+        &state.config.jwt,
+        user,
+        ClientInfo::from_request(&state.config.rate_limit, &headers, peer),
+    )?;
 
-    Ok(Json(LoginResponse {
-        token,
-        user: user.into(),
-    }))
+    Ok(Json(LoginOutcome::Authenticated(response)))
+}
+
+pub async fn refresh_token(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshTokenRequest>,
+) -> Result<Json<LoginResponse>> {
+    let mut conn = state.get_connection()?;
+
+    let response = SessionService::refresh(&mut conn, &state.config.jwt, &payload.refresh_token)?;
+
+    Ok(Json(response))
+}
+
+pub async fn logout(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<serde_json::Value>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    SessionService::revoke(&mut conn, user_id, auth_user.claims.sid)?;
+
+    Ok(Json(serde_json::json!({
+        "message": "Logged out successfully"
+    })))
+}
+
+pub async fn logout_all(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.ensure_not_impersonating()?;
+
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let revoked = SessionService::revoke_all(&mut conn, user_id)?;
+
+    Ok(Json(serde_json::json!({
+        "message": "All sessions logged out",
+        "revoked_sessions": revoked,
+    })))
+}
+
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<SessionResponse>>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let sessions = SessionService::list_active(&mut conn, user_id)?
+        .into_iter()
+        .map(|session| SessionResponse {
+            id: session.id,
+            user_agent: session.user_agent,
+            ip_address: session.ip_address,
+            created_at: session.created_at,
+            last_used_at: session.last_used_at,
+            expires_at: session.expires_at,
+            impersonator_id: session.impersonator_id,
+            current: session.id == auth_user.claims.sid,
+        })
+        .collect();
+
+    Ok(Json(sessions))
+}
+
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.ensure_not_impersonating()?;
+
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    if !SessionService::revoke(&mut conn, user_id, session_id)? {
+        return Err(AppError::NotFound("Session not found".to_string()));
+    }
+
+    Ok(Json(serde_json::json!({
+        "message": "Session revoked successfully"
+    })))
 }
 
 pub async fn get_current_user(
@@ -110,3 +307,131 @@ pub async fn get_current_user(
     Ok(Json(user.into()))
 }
 
+
+pub async fn change_password(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.ensure_not_impersonating()?;
+
+    ensure_password_login(&state)?;
+
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let user = users::table
+        .find(user_id)
+        .first::<User>(&mut conn)?;
+
+    if user.ldap_dn.is_some() {
+        return Err(AppError::BadRequest(
+            "This account's password is managed by the directory".to_string(),
+        ));
+    }
+
+    if !verify_password(&payload.current_password, &user.password_hash)? {
+        return Err(AppError::Unauthorized("Current password is incorrect".to_string()));
+    }
+
+    if payload.new_password == payload.current_password {
+        return Err(AppError::ValidationError(
+            "New password must differ from the current password".to_string(),
+        ));
+    }
+
+    PasswordPolicyService::validate(&state.config.password, &payload.new_password)?;
+
+    let password_hash = hash_password(&payload.new_password)?;
+    PasswordResetService::set_password(&mut conn, user_id, &password_hash)?;
+
+    // Other devices have to log in again with the new password
+    SessionService::revoke_others(&mut conn, user_id, auth_user.claims.sid)?;
+
+    Ok(Json(serde_json::json!({
+        "message": "Password changed successfully"
+    })))
+}
+
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> Result<Json<serde_json::Value>> {
+    ensure_password_login(&state)?;
+
+    payload.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let mut conn = state.get_connection()?;
+
+    let user = users::table
+        .filter(users::email.eq(&payload.email))
+        .filter(users::is_active.eq(true))
+        .filter(users::ldap_dn.is_null())
+        .filter(users::is_service_account.eq(false))
+        .first::<User>(&mut conn)
+        .optional()?;
+
+    // Answer the same way whether or not the address is registered
+    if let Some(user) = user {
+        let token = PasswordResetService::create_token(
+            &mut conn,
+            user.id,
+            state.config.password.reset_expiration,
+        )?;
+
+        let separator = if state.config.password.reset_url.contains('?') { '&' } else { '?' };
+        let reset_link = format!("{}{}token={}", state.config.password.reset_url, separator, token);
+
+        let message = MailMessage {
+            to: user.email.clone(),
+            subject: "Reset your password".to_string(),
+            body: format!(
+                "Hello {},\n\nUse the link below to choose a new password. It expires in {} minutes and can be used once.\n\n{}\n\nIf you didn't ask to reset your password, you can ignore this email.\n",
+                user.full_name.as_deref().unwrap_or(&user.username),
+                state.config.password.reset_expiration / 60,
+                reset_link,
+            ),
+        };
+
+        if let Err(e) = mailer::from_config(&state.config.mail)?.send(message).await {
+            tracing::error!("Failed to send password reset email to user {}: {}", user.id, e);
+        }
+    }
+
+    Ok(Json(serde_json::json!({
+        "message": "If the address is registered, a reset link has been sent"
+    })))
+}
+
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<Json<serde_json::Value>> {
+    ensure_password_login(&state)?;
+
+    PasswordPolicyService::validate(&state.config.password, &payload.new_password)?;
+
+    let mut conn = state.get_connection()?;
+
+    let password_hash = hash_password(&payload.new_password)?;
+    let user_id = PasswordResetService::reset_password(&mut conn, &payload.token, &password_hash)?;
+
+    // Whoever knew the old password is logged out everywhere
+    SessionService::revoke_all(&mut conn, user_id)?;
+
+    Ok(Json(serde_json::json!({
+        "message": "Password reset successfully"
+    })))
+}
+
+/// Fail unless this deployment allows logging in with a local password
+pub(crate) fn ensure_password_login(state: &AppState) -> Result<()> {
+    if !state.config.password.login_enabled {
+        return Err(AppError::Forbidden(
+            "Password login is disabled; sign in with single sign-on".to_string(),
+        ));
+    }
+
+    Ok(())
+}
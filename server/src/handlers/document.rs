@@ -12,11 +12,14 @@ use crate::{
     error::{AppError, Result},
     middleware::AuthUser,
     models::document::{
-        CreateDocumentRequest, CreateFolderRequest, Document, MoveDocumentRequest, 
-        NewDocument, NewDocumentVersion, UpdateDocumentRequest,
+        CheckoutRequest, CreateDocumentRequest, CreateFolderRequest, Document, DocumentChangeset,
+        MoveDocumentRequest, NewDocument, NewDocumentVersion, UpdateDocumentRequest,
     },
     schema::{documents, document_versions},
-    services::{PermissionService, StorageService, SearchService},
+    services::{
+        LifecycleService, LockService, MetadataService, PermissionService, QuotaService, RetentionService,
+        StorageService, SearchService, TagService, VersionService,
+    },
     models::permission::PermissionType,
 };
 
@@ -52,19 +55,7 @@ pub async fn create_folder(
     let mut conn = state.get_connection()?;
     let user_id = auth_user.claims.user_id()?;
 
-    // If parent folder is specified, check permissions
-    if let Some(parent_id) = payload.parent_folder_id {
-        let can_write = PermissionService::check_permission(
-            &mut conn,
-            user_id,
-            parent_id,
-            PermissionType::Write,
-        )?;
-
-        if !can_write {
-            return Err(AppError::Forbidden("No permission to create folder here".to_string()));
-        }
-    }
+    auth_user.ensure_can_add_to(&mut conn, payload.parent_folder_id)?;
 
     let new_folder = NewDocument {
         name: payload.name,
@@ -103,6 +94,7 @@ pub async fn upload_document(
     let mut parent_folder_id: Option<Uuid> = None;
     let mut description: Option<String> = None;
     let mut tags: Option<Vec<String>> = None;
+    let mut metadata: Option<serde_json::Value> = None;
 
     // Parse multipart form data
     while let Some(field) = multipart.next_field().await.map_err(|e| {
@@ -133,7 +125,15 @@ pub async fn upload_document(
                 let value = field.text().await.map_err(|e| {
                     AppError::BadRequest(format!("Failed to read tags: {}", e))
                 })?;
-                tags = Some(value.split(',').map(|s| s.trim().to_string()).collect());
+                tags = Some(value.split(',').map(|s| s.to_string()).collect());
+            }
+            "metadata" => {
+                let value = field.text().await.map_err(|e| {
+                    AppError::BadRequest(format!("Failed to read metadata: {}", e))
+                })?;
+                metadata = Some(serde_json::from_str(&value).map_err(|e| {
+                    AppError::BadRequest(format!("Invalid metadata JSON: {}", e))
+                })?);
             }
             _ => {}
         }
@@ -145,19 +145,19 @@ pub async fn upload_document(
 
     let mut conn = state.get_connection()?;
 
-    // Check parent folder permissions
-    if let Some(parent_id) = parent_folder_id {
-        let can_write = PermissionService::check_permission(
-            &mut conn,
-            user_id,
-            parent_id,
-            PermissionType::Write,
-        )?;
+    auth_user.ensure_can_add_to(&mut conn, parent_folder_id)?;
 
-        if !can_write {
-            return Err(AppError::Forbidden("No permission to upload here".to_string()));
-        }
-    }
+    // Normalize tags and enforce the vocabulary
+    let tags = match tags {
+        Some(raw) => Some(TagService::prepare(&mut conn, &state.config.tags, raw)?),
+        None => None,
+    };
+
+    // Validate metadata against the applicable schema
+    let metadata = MetadataService::validate_for_document(&mut conn, parent_folder_id, &content_type, metadata)?;
+
+    // Check storage quota
+    QuotaService::check_quota(&mut conn, user_id, file_data.len() as i64)?;
 
     // Upload to MinIO
     let storage_service = StorageService::new(&state.config.minio)?;
@@ -174,7 +174,7 @@ pub async fn upload_document(
         parent_folder_id,
         is_folder: false,
         tags: tags.map(|t| t.into_iter().map(Some).collect()),
-        metadata: None,
+        metadata,
     };
 
     let document = diesel::insert_into(documents::table)
@@ -225,6 +225,8 @@ pub async fn list_documents(
     let mut conn = state.get_connection()?;
     let user_id = auth_user.claims.user_id()?;
 
+    auth_user.ensure_in_scope(&mut conn, params.folder_id)?;
+
     let mut query = documents::table
         .filter(documents::owner_id.eq(user_id))
         .filter(documents::deleted_at.is_null())
@@ -270,93 +272,58 @@ pub async fn update_document(
         return Err(AppError::Forbidden("No permission to update this document".to_string()));
     }
 
-    // Build update changeset
-    use crate::schema::documents::dsl::*;
-    
+    // Only the lock holder may modify a checked-out document
+    let existing = documents::table
+        .find(document_id)
+        .select(Document::as_select())
+        .first::<Document>(&mut conn)?;
+    LockService::ensure_not_locked_by_other(&existing, user_id)?;
+    LifecycleService::ensure_editable(&existing)?;
+    RetentionService::ensure_not_on_hold(&mut conn, &existing)?;
+
     // Check if there are any fields to update
-    if payload.name.is_none() && payload.description.is_none() && payload.tags.is_none() {
+    if payload.name.is_none()
+        && payload.description.is_none()
+        && payload.tags.is_none()
+        && payload.metadata.is_none()
+    {
         return Err(AppError::BadRequest("No fields to update".to_string()));
     }
 
-    let document = match (payload.name, payload.description, payload.tags) {
-        (Some(new_name), Some(new_description), Some(new_tags)) => {
-            let tags_opt: Vec<Option<String>> = new_tags.into_iter().map(Some).collect();
-            diesel::update(documents.find(document_id))
-                .set((
-                    name.eq(new_name),
-                    description.eq(Some(new_description)),
-                    tags.eq(Some(tags_opt)),
-                    updated_at.eq(diesel::dsl::now),
-                ))
-                .returning(Document::as_returning())
-                .get_result::<Document>(&mut conn)?
-        }
-        (Some(new_name), Some(new_description), None) => {
-            diesel::update(documents.find(document_id))
-                .set((
-                    name.eq(new_name),
-                    description.eq(Some(new_description)),
-                    updated_at.eq(diesel::dsl::now),
-                ))
-                .returning(Document::as_returning())
-                .get_result::<Document>(&mut conn)?
-        }
-        (Some(new_name), None, Some(new_tags)) => {
-            let tags_opt: Vec<Option<String>> = new_tags.into_iter().map(Some).collect();
-            diesel::update(documents.find(document_id))
-                .set((
-                    name.eq(new_name),
-                    tags.eq(Some(tags_opt)),
-                    updated_at.eq(diesel::dsl::now),
-                ))
-                .returning(Document::as_returning())
-                .get_result::<Document>(&mut conn)?
-        }
-        (None, Some(new_description), Some(new_tags)) => {
-            let tags_opt: Vec<Option<String>> = new_tags.into_iter().map(Some).collect();
-            diesel::update(documents.find(document_id))
-                .set((
-                    description.eq(Some(new_description)),
-                    tags.eq(Some(tags_opt)),
-                    updated_at.eq(diesel::dsl::now),
-                ))
-                .returning(Document::as_returning())
-                .get_result::<Document>(&mut conn)?
-        }
-        (Some(new_name), None, None) => {
-            diesel::update(documents.find(document_id))
-                .set((
-                    name.eq(new_name),
-                    updated_at.eq(diesel::dsl::now),
-                ))
-                .returning(Document::as_returning())
-                .get_result::<Document>(&mut conn)?
-        }
-        (None, Some(new_description), None) => {
-            diesel::update(documents.find(document_id))
-                .set((
-                    description.eq(Some(new_description)),
-                    updated_at.eq(diesel::dsl::now),
-                ))
-                .returning(Document::as_returning())
-                .get_result::<Document>(&mut conn)?
-        }
-        (None, None, Some(new_tags)) => {
-            let tags_opt: Vec<Option<String>> = new_tags.into_iter().map(Some).collect();
-            diesel::update(documents.find(document_id))
-                .set((
-                    tags.eq(Some(tags_opt)),
-                    updated_at.eq(diesel::dsl::now),
-                ))
-                .returning(Document::as_returning())
-                .get_result::<Document>(&mut conn)?
-        }
-        (None, None, None) => {
-            // Already checked above
-            unreachable!()
-        }
+    // Metadata is validated against the schema for the document's folder and type
+    let metadata = match payload.metadata {
+        Some(value) => MetadataService::validate_for_document(
+            &mut conn,
+            existing.parent_folder_id,
+            &existing.mime_type,
+            Some(value),
+        )?,
+        None => None,
     };
 
+    let new_tags = match payload.tags {
+        Some(raw) => Some(TagService::prepare(&mut conn, &state.config.tags, raw)?),
+        None => None,
+    };
+
+    let changeset = DocumentChangeset {
+        name: payload.name,
+        description: payload.description,
+        tags: new_tags.map(|t| t.into_iter().map(Some).collect()),
+        metadata,
+    };
+
+    // Retention policies can match on tags; dropping one mustn't free the document early
+    if let Some(tags) = &changeset.tags {
+        let updated = Document { tags: Some(tags.clone()), ..existing.clone() };
+        RetentionService::ensure_update_keeps_retention(&mut conn, &existing, &updated)?;
+    }
+
+    let document = diesel::update(documents::table.find(document_id))
+        .set((&changeset, documents::updated_at.eq(diesel::dsl::now)))
+        .returning(Document::as_returning())
+        .get_result::<Document>(&mut conn)?;
+
     // Update search index
     let search_service = SearchService::new(&state.config.meilisearch)?;
     search_service.update_document(document.clone()).await?;
@@ -389,6 +356,9 @@ pub async fn delete_document(
         .select(Document::as_select())
         .first(&mut conn)?;
 
+    // Legal hold and retention policies block deletion
+    RetentionService::ensure_deletable(&mut conn, &document)?;
+
     // Soft delete
     diesel::update(documents::table.find(document_id))
         .set(documents::deleted_at.eq(diesel::dsl::now))
@@ -466,24 +436,222 @@ pub async fn move_document(
         return Err(AppError::Forbidden("No permission to move this document".to_string()));
     }
 
-    // Check write permission on target folder
-    if let Some(target_id) = payload.target_folder_id {
-        let can_write_target = PermissionService::check_permission(
+    let existing = documents::table
+        .find(document_id)
+        .select(Document::as_select())
+        .first::<Document>(&mut conn)?;
+    RetentionService::ensure_not_on_hold(&mut conn, &existing)?;
+    RetentionService::ensure_move_keeps_retention(&mut conn, &existing, payload.target_folder_id)?;
+
+    auth_user.ensure_can_add_to(&mut conn, payload.target_folder_id)?;
+
+    let document = diesel::update(documents::table.find(document_id))
+        .set(documents::parent_folder_id.eq(payload.target_folder_id))
+        .returning(Document::as_returning())
+        .get_result::<Document>(&mut conn)?;
+
+    Ok(Json(document))
+}
+
+pub async fn checkout_document(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+    payload: Option<Json<CheckoutRequest>>,
+) -> Result<Json<Document>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    // Check write permission
+    let can_write = PermissionService::check_permission(
+        &mut conn,
+        user_id,
+        document_id,
+        PermissionType::Write,
+    )?;
+
+    if !can_write {
+        return Err(AppError::Forbidden("No permission to check out this document".to_string()));
+    }
+
+    let document = documents::table
+        .find(document_id)
+        .select(Document::as_select())
+        .first::<Document>(&mut conn)?;
+
+    if document.is_folder {
+        return Err(AppError::BadRequest("Cannot check out a folder".to_string()));
+    }
+
+    LifecycleService::ensure_editable(&document)?;
+    RetentionService::ensure_not_on_hold(&mut conn, &document)?;
+
+    let duration_minutes = payload.and_then(|Json(p)| p.duration_minutes);
+    let document = LockService::checkout(&mut conn, document_id, user_id, duration_minutes)?;
+
+    Ok(Json(document))
+}
+
+pub async fn checkin_document(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<Document>> {
+    let user_id = auth_user.claims.user_id()?;
+    let mut file_data: Option<Vec<u8>> = None;
+    let mut comment: Option<String> = None;
+
+    // Parse multipart form data
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        AppError::BadRequest(format!("Failed to parse multipart: {}", e))
+    })? {
+        let field_name = field.name().unwrap_or("").to_string();
+
+        match field_name.as_str() {
+            "file" => {
+                file_data = Some(field.bytes().await.map_err(|e| {
+                    AppError::BadRequest(format!("Failed to read file: {}", e))
+                })?.to_vec());
+            }
+            "comment" => {
+                comment = Some(field.text().await.map_err(|e| {
+                    AppError::BadRequest(format!("Failed to read comment: {}", e))
+                })?);
+            }
+            _ => {}
+        }
+    }
+
+    let file_data = file_data.ok_or_else(|| AppError::BadRequest("No file provided".to_string()))?;
+
+    let mut conn = state.get_connection()?;
+
+    let document = documents::table
+        .find(document_id)
+        .select(Document::as_select())
+        .first::<Document>(&mut conn)?;
+
+    if document.active_lock_holder() != Some(user_id) {
+        return Err(AppError::Locked("Document must be checked out by you before check-in".to_string()));
+    }
+
+    LifecycleService::ensure_editable(&document)?;
+    RetentionService::ensure_not_on_hold(&mut conn, &document)?;
+
+    // Versions count against the owner's storage
+    QuotaService::check_quota(&mut conn, document.owner_id, file_data.len() as i64)?;
+
+    let storage_service = StorageService::new(&state.config.minio)?;
+    VersionService::create_version(
+        &mut conn,
+        &storage_service,
+        &document,
+        &file_data,
+        comment,
+        user_id,
+    )
+    .await?;
+
+    let document = LockService::release(&mut conn, document_id)?;
+
+    // Update search index
+    let search_service = SearchService::new(&state.config.meilisearch)?;
+    search_service.update_document(document.clone()).await?;
+
+    Ok(Json(document))
+}
+
+pub async fn cancel_checkout(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+) -> Result<Json<Document>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    // Check read permission
+    let can_read = PermissionService::check_permission(
+        &mut conn,
+        user_id,
+        document_id,
+        PermissionType::Read,
+    )?;
+
+    if !can_read {
+        return Err(AppError::Forbidden("No permission to view this document".to_string()));
+    }
+
+    let document = documents::table
+        .find(document_id)
+        .select(Document::as_select())
+        .first::<Document>(&mut conn)?;
+
+    // Check write permission; admins may unlock any document
+    if !auth_user.is_admin() {
+        let can_write = PermissionService::check_permission(
             &mut conn,
             user_id,
-            target_id,
+            document_id,
             PermissionType::Write,
         )?;
 
-        if !can_write_target {
-            return Err(AppError::Forbidden("No permission to move to target folder".to_string()));
+        if !can_write {
+            return Err(AppError::Forbidden("No permission to unlock this document".to_string()));
         }
     }
 
-    let document = diesel::update(documents::table.find(document_id))
-        .set(documents::parent_folder_id.eq(payload.target_folder_id))
-        .returning(Document::as_returning())
-        .get_result::<Document>(&mut conn)?;
+    // The lock holder can release their own lock; admins can force-unlock
+    if document.is_locked_by_other(user_id) && !auth_user.is_admin() {
+        return Err(AppError::Forbidden("Only the lock holder or an admin can unlock this document".to_string()));
+    }
+
+    if document.locked_by.is_some() && document.locked_by != Some(user_id) {
+        tracing::info!(
+            "Document {} force-unlocked by {} (was locked by {:?})",
+            document_id,
+            user_id,
+            document.locked_by
+        );
+    }
+
+    let document = LockService::release(&mut conn, document_id)?;
 
     Ok(Json(document))
 }
+
+pub async fn purge_document_version(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path((document_id, version)): Path<(Uuid, i32)>,
+) -> Result<Json<serde_json::Value>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    // Check delete permission
+    let can_delete = PermissionService::check_permission(
+        &mut conn,
+        user_id,
+        document_id,
+        PermissionType::Delete,
+    )?;
+
+    if !can_delete {
+        return Err(AppError::Forbidden("No permission to delete versions of this document".to_string()));
+    }
+
+    let document = documents::table
+        .find(document_id)
+        .select(Document::as_select())
+        .first::<Document>(&mut conn)?;
+
+    // Legal hold and retention policies block version purges
+    RetentionService::ensure_deletable(&mut conn, &document)?;
+
+    let storage_service = StorageService::new(&state.config.minio)?;
+    VersionService::purge_version(&mut conn, &storage_service, &document, version).await?;
+
+    Ok(Json(serde_json::json!({
+        "message": "Version deleted successfully"
+    })))
+}
@@ -0,0 +1,20 @@
+use axum::{extract::State, Json};
+
+use crate::{
+    db::AppState,
+    error::Result,
+    middleware::AuthUser,
+    services::{ldap::LdapSyncSummary, LdapService},
+};
+
+/// Run the directory sync now instead of waiting for the schedule
+pub async fn sync_ldap_directory(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<LdapSyncSummary>> {
+    auth_user.require_admin()?;
+
+    let summary = LdapService::run_sync(&state).await?;
+
+    Ok(Json(summary))
+}
@@ -0,0 +1,155 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    error::{AppError, Result},
+    middleware::AuthUser,
+    models::{
+        document::Document,
+        lifecycle::{
+            ChangeStatusRequest, CreateTransitionRequest, NewStatusTransition, StatusHistoryEntry,
+            StatusTransition,
+        },
+        permission::PermissionType,
+    },
+    schema::{document_status_transitions, documents},
+    services::{ApprovalService, LifecycleService, PermissionService, SearchService},
+};
+
+pub async fn change_document_status(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+    Json(payload): Json<ChangeStatusRequest>,
+) -> Result<Json<Document>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let document = documents::table
+        .find(document_id)
+        .select(Document::as_select())
+        .first::<Document>(&mut conn)?;
+
+    // Documents under an approval workflow change status through reviewer decisions
+    if ApprovalService::has_pending_workflow(&mut conn, document_id)? {
+        return Err(AppError::BadRequest(
+            "Document has a pending approval workflow".to_string(),
+        ));
+    }
+
+    let document = LifecycleService::change_status(
+        &mut conn,
+        &document,
+        payload.status,
+        user_id,
+        auth_user.is_admin(),
+        payload.comment,
+    )?;
+
+    // Update search index
+    let search_service = SearchService::new(&state.config.meilisearch)?;
+    search_service.update_document(document.clone()).await?;
+
+    Ok(Json(document))
+}
+
+pub async fn get_status_history(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+) -> Result<Json<Vec<StatusHistoryEntry>>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    // Check read permission
+    let can_read = PermissionService::check_permission(
+        &mut conn,
+        user_id,
+        document_id,
+        PermissionType::Read,
+    )?;
+
+    if !can_read {
+        return Err(AppError::Forbidden("No permission to view this document".to_string()));
+    }
+
+    let history = LifecycleService::get_history(&mut conn, document_id)?;
+
+    Ok(Json(history))
+}
+
+pub async fn list_status_transitions(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+) -> Result<Json<Vec<StatusTransition>>> {
+    let mut conn = state.get_connection()?;
+
+    let transitions = document_status_transitions::table
+        .order((
+            document_status_transitions::from_status.asc(),
+            document_status_transitions::to_status.asc(),
+        ))
+        .select(StatusTransition::as_select())
+        .load(&mut conn)?;
+
+    Ok(Json(transitions))
+}
+
+pub async fn create_status_transition(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateTransitionRequest>,
+) -> Result<Json<StatusTransition>> {
+    auth_user.require_admin()?;
+
+    if payload.from_status == payload.to_status {
+        return Err(AppError::BadRequest("A transition must change the status".to_string()));
+    }
+
+    let mut conn = state.get_connection()?;
+
+    let new_transition = NewStatusTransition {
+        from_status: payload.from_status.as_str().to_string(),
+        to_status: payload.to_status.as_str().to_string(),
+        required_permission: payload.required_permission.as_str().to_string(),
+    };
+
+    let transition = diesel::insert_into(document_status_transitions::table)
+        .values(&new_transition)
+        .on_conflict((
+            document_status_transitions::from_status,
+            document_status_transitions::to_status,
+        ))
+        .do_update()
+        .set(document_status_transitions::required_permission.eq(&new_transition.required_permission))
+        .returning(StatusTransition::as_returning())
+        .get_result(&mut conn)?;
+
+    Ok(Json(transition))
+}
+
+pub async fn delete_status_transition(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(transition_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let deleted = diesel::delete(document_status_transitions::table.find(transition_id))
+        .execute(&mut conn)?;
+
+    if deleted == 0 {
+        return Err(AppError::NotFound("Status transition not found".to_string()));
+    }
+
+    Ok(Json(serde_json::json!({
+        "message": "Status transition deleted successfully"
+    })))
+}
@@ -0,0 +1,260 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use diesel::prelude::*;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    db::AppState,
+    error::{AppError, Result},
+    middleware::AuthUser,
+    models::{
+        document::Document,
+        metadata::{
+            BindMetadataSchemaRequest, CreateMetadataSchemaRequest, MetadataSchema,
+            MetadataSchemaBinding, NewMetadataSchema, NewMetadataSchemaBinding,
+            UpdateMetadataSchemaRequest,
+        },
+        permission::PermissionType,
+    },
+    schema::{documents, metadata_schema_bindings, metadata_schemas},
+    services::{MetadataService, PermissionService, SearchService},
+};
+
+pub async fn list_metadata_schemas(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+) -> Result<Json<Vec<MetadataSchema>>> {
+    let mut conn = state.get_connection()?;
+
+    let schemas = metadata_schemas::table
+        .order(metadata_schemas::name.asc())
+        .select(MetadataSchema::as_select())
+        .load(&mut conn)?;
+
+    Ok(Json(schemas))
+}
+
+pub async fn create_metadata_schema(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateMetadataSchemaRequest>,
+) -> Result<Json<MetadataSchema>> {
+    auth_user.require_admin()?;
+
+    payload.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    MetadataService::validate_fields(&payload.fields)?;
+
+    let mut conn = state.get_connection()?;
+    let admin_id = auth_user.claims.user_id()?;
+
+    let new_schema = NewMetadataSchema {
+        name: payload.name,
+        description: payload.description,
+        fields: serde_json::to_value(&payload.fields)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?,
+        created_by: admin_id,
+    };
+
+    let schema = diesel::insert_into(metadata_schemas::table)
+        .values(&new_schema)
+        .returning(MetadataSchema::as_returning())
+        .get_result(&mut conn)?;
+
+    sync_search_attributes(&state, &mut conn).await?;
+
+    Ok(Json(schema))
+}
+
+pub async fn update_metadata_schema(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(schema_id): Path<Uuid>,
+    Json(payload): Json<UpdateMetadataSchemaRequest>,
+) -> Result<Json<MetadataSchema>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let schema = metadata_schemas::table
+        .find(schema_id)
+        .select(MetadataSchema::as_select())
+        .first::<MetadataSchema>(&mut conn)?;
+
+    let fields = match payload.fields {
+        Some(fields) => {
+            MetadataService::validate_fields(&fields)?;
+            serde_json::to_value(&fields)
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?
+        }
+        None => schema.fields,
+    };
+
+    let schema = diesel::update(metadata_schemas::table.find(schema_id))
+        .set((
+            metadata_schemas::description.eq(payload.description.or(schema.description)),
+            metadata_schemas::fields.eq(fields),
+            metadata_schemas::updated_at.eq(diesel::dsl::now),
+        ))
+        .returning(MetadataSchema::as_returning())
+        .get_result(&mut conn)?;
+
+    sync_search_attributes(&state, &mut conn).await?;
+
+    Ok(Json(schema))
+}
+
+pub async fn delete_metadata_schema(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(schema_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let deleted = diesel::delete(metadata_schemas::table.find(schema_id))
+        .execute(&mut conn)?;
+
+    if deleted == 0 {
+        return Err(AppError::NotFound("Metadata schema not found".to_string()));
+    }
+
+    sync_search_attributes(&state, &mut conn).await?;
+
+    Ok(Json(serde_json::json!({
+        "message": "Metadata schema deleted successfully"
+    })))
+}
+
+pub async fn list_metadata_schema_bindings(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(schema_id): Path<Uuid>,
+) -> Result<Json<Vec<MetadataSchemaBinding>>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let bindings = metadata_schema_bindings::table
+        .filter(metadata_schema_bindings::schema_id.eq(schema_id))
+        .order(metadata_schema_bindings::created_at.asc())
+        .select(MetadataSchemaBinding::as_select())
+        .load(&mut conn)?;
+
+    Ok(Json(bindings))
+}
+
+pub async fn bind_metadata_schema(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(schema_id): Path<Uuid>,
+    Json(payload): Json<BindMetadataSchemaRequest>,
+) -> Result<Json<MetadataSchemaBinding>> {
+    auth_user.require_admin()?;
+
+    if payload.folder_id.is_some() == payload.mime_type.is_some() {
+        return Err(AppError::BadRequest(
+            "Provide exactly one of folder_id or mime_type".to_string(),
+        ));
+    }
+
+    let mut conn = state.get_connection()?;
+
+    // Make sure the schema exists
+    metadata_schemas::table
+        .find(schema_id)
+        .select(metadata_schemas::id)
+        .first::<Uuid>(&mut conn)?;
+
+    if let Some(folder_id) = payload.folder_id {
+        let is_folder = documents::table
+            .find(folder_id)
+            .select(documents::is_folder)
+            .first::<bool>(&mut conn)?;
+
+        if !is_folder {
+            return Err(AppError::BadRequest("folder_id must refer to a folder".to_string()));
+        }
+    }
+
+    let new_binding = NewMetadataSchemaBinding {
+        schema_id,
+        folder_id: payload.folder_id,
+        mime_type: payload.mime_type,
+    };
+
+    let binding = diesel::insert_into(metadata_schema_bindings::table)
+        .values(&new_binding)
+        .returning(MetadataSchemaBinding::as_returning())
+        .get_result(&mut conn)?;
+
+    Ok(Json(binding))
+}
+
+pub async fn delete_metadata_schema_binding(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(binding_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let deleted = diesel::delete(metadata_schema_bindings::table.find(binding_id))
+        .execute(&mut conn)?;
+
+    if deleted == 0 {
+        return Err(AppError::NotFound("Metadata schema binding not found".to_string()));
+    }
+
+    Ok(Json(serde_json::json!({
+        "message": "Metadata schema binding deleted successfully"
+    })))
+}
+
+pub async fn get_document_metadata_schema(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+) -> Result<Json<Option<MetadataSchema>>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    // Check read permission
+    let can_read = PermissionService::check_permission(
+        &mut conn,
+        user_id,
+        document_id,
+        PermissionType::Read,
+    )?;
+
+    if !can_read {
+        return Err(AppError::Forbidden("No permission to view this document".to_string()));
+    }
+
+    let document = documents::table
+        .find(document_id)
+        .select(Document::as_select())
+        .first::<Document>(&mut conn)?;
+
+    let schema = MetadataService::resolve_schema(
+        &mut conn,
+        document.parent_folder_id,
+        &document.mime_type,
+    )?;
+
+    Ok(Json(schema))
+}
+
+// Schema fields changed, so the search index settings must follow
+async fn sync_search_attributes(
+    state: &AppState,
+    conn: &mut crate::db::DbConnection,
+) -> Result<()> {
+    let search_service = SearchService::new(&state.config.meilisearch)?;
+    MetadataService::sync_search_attributes(conn, &search_service).await
+}
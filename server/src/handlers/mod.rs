@@ -3,10 +3,40 @@ pub mod document;
 pub mod permission;
 pub mod search;
 pub mod onlyoffice;
+pub mod quota;
+pub mod lifecycle;
+pub mod approval;
+pub mod retention;
+pub mod metadata;
+pub mod tag;
+pub mod thumbnail;
+pub mod rendition;
+pub mod template;
+pub mod totp;
+pub mod oidc;
+pub mod ldap;
+pub mod api_token;
+pub mod user_admin;
+pub mod registration;
 
 pub use auth::*;
 pub use document::*;
 pub use permission::*;
 pub use search::*;
 pub use onlyoffice::*;
+pub use quota::*;
+pub use lifecycle::*;
+pub use approval::*;
+pub use retention::*;
+pub use metadata::*;
+pub use tag::*;
+pub use thumbnail::*;
+pub use rendition::*;
+pub use template::*;
+pub use totp::*;
+pub use oidc::*;
+pub use ldap::*;
+pub use api_token::*;
+pub use user_admin::*;
+pub use registration::*;
 
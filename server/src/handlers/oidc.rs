@@ -0,0 +1,82 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::HeaderMap,
+    Json,
+};
+
+use crate::{
+    db::AppState,
+    error::{AppError, Result},
+    models::{
+        group::GROUP_MEMBER_SOURCE_OIDC,
+        oidc::{AuthMethodsResponse, OidcAuthorizeResponse, OidcCallbackRequest},
+        registration::RegistrationMode,
+        user::LoginOutcome,
+    },
+    services::{session::ClientInfo, GroupSyncService, OidcService, RegistrationService, SessionService, TotpService},
+};
+
+/// Login methods this deployment offers, for the login page
+pub async fn get_auth_methods(State(state): State<AppState>) -> Json<AuthMethodsResponse> {
+    Json(AuthMethodsResponse {
+        password_login: state.config.password.login_enabled,
+        oidc: state.config.oidc.enabled,
+        // A misconfigured mode turns every registration away
+        registration: RegistrationService::mode(&state.config.registration)
+            .unwrap_or(RegistrationMode::Disabled)
+            .as_str()
+            .to_string(),
+    })
+}
+
+pub async fn oidc_authorize(State(state): State<AppState>) -> Result<Json<OidcAuthorizeResponse>> {
+    let oidc_service = OidcService::new(&state.config.oidc)?;
+    let mut conn = state.get_connection()?;
+
+    let (authorization_url, login_request_id) = oidc_service.authorization_url(&mut conn).await?;
+
+    Ok(Json(OidcAuthorizeResponse { authorization_url, login_request_id }))
+}
+
+/// Complete a single sign-on login with the code the identity provider sent back.
+///
+/// Accounts with two-factor authentication get the same challenge as a
+/// password login, unless the deployment trusts the identity provider's MFA.
+pub async fn oidc_callback(
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<OidcCallbackRequest>,
+) -> Result<Json<LoginOutcome>> {
+    let oidc_service = OidcService::new(&state.config.oidc)?;
+    let mut conn = state.get_connection()?;
+
+    let identity = oidc_service
+        .complete(&mut conn, &payload.code, &payload.state, &payload.login_request_id)
+        .await?;
+    let user = OidcService::find_or_create_user(&mut conn, &state.config.oidc, &identity)?;
+
+    if !user.is_active {
+        return Err(AppError::Unauthorized("Account is not active".to_string()));
+    }
+
+    if let Some(groups) = &identity.groups {
+        GroupSyncService::sync_memberships(&mut conn, user.id, GROUP_MEMBER_SOURCE_OIDC, groups, Some(user.id))?;
+    }
+
+    if (user.totp_enabled || user.totp_required) && !state.config.oidc.trust_idp_mfa {
+        let challenge = TotpService::challenge(&state.config.jwt, &state.config.totp, &user)?;
+        return Ok(Json(LoginOutcome::TotpRequired(challenge)));
+    }
+
+    let response = SessionService::start(
+        &mut conn,
+        &state.config.jwt,
+        user,
+        ClientInfo::from_request(&state.config.rate_limit, &headers, peer),
+    )?;
+
+    Ok(Json(LoginOutcome::Authenticated(response)))
+}
@@ -1,18 +1,20 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap},
     Json,
 };
 use diesel::prelude::*;
 use jsonwebtoken::encode;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::{
-    db::AppState,
+    db::{AppState, DbConnection},
     error::{AppError, Result},
     middleware::AuthUser,
-    models::{document::Document, permission::PermissionType},
-    schema::documents,
-    services::{OnlyOfficeService, PermissionService, StorageService, onlyoffice::{OnlyOfficeCallbackData, CallbackResponse}},
+    models::{document::{Document, DocumentVersion}, editor_session::ActiveEditor, permission::{PermissionType, ShareEditorQuery, ShareLink}},
+    schema::{documents, share_links, users},
+    services::{EditorSessionService, LifecycleService, LockService, OnlyOfficeService, PermissionService, QuotaService, RetentionService, StorageService, VersionService, onlyoffice::{EditorAccess, OnlyOfficeCallbackData, CallbackResponse}, version::EditorSave},
 };
 
 pub async fn get_editor_config(
@@ -33,49 +35,123 @@ pub async fn get_editor_config(
         return Err(AppError::BadRequest("Cannot edit a folder".to_string()));
     }
 
-    // Check read permission
-    let can_read = PermissionService::check_permission(
-        &mut conn,
-        user_id,
-        document_id,
-        PermissionType::Read,
-    )?;
+    // Comment, review and form filling permissions open the editor as well
+    let granted = PermissionService::effective_permissions(&mut conn, user_id, document_id)?;
 
-    if !can_read {
+    if !EditorAccess::can_read(&granted) {
         return Err(AppError::Forbidden("No permission to view this document".to_string()));
     }
 
-    // Check write permission
-    let can_edit = PermissionService::check_permission(
+    // A document checked out by someone else, in a read-only lifecycle
+    // status or under legal hold opens read-only
+    let modifiable = !document.is_locked_by_other(user_id)
+        && !LifecycleService::is_read_only(&document)
+        && RetentionService::active_holds(&mut conn, &document)?.is_empty();
+
+    let file_type = OnlyOfficeService::get_file_type_from_extension(file_extension(&document));
+    let access = EditorAccess::from_permissions(&granted, modifiable, file_type);
+
+    build_editor_config(
+        &state,
         &mut conn,
-        user_id,
-        document_id,
-        PermissionType::Write,
-    )?;
+        &document,
+        &user_id.to_string(),
+        &auth_user.claims.username,
+        access,
+    )
+    .await
+}
+
+pub async fn get_share_editor_config(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Query(params): Query<ShareEditorQuery>,
+) -> Result<Json<serde_json::Value>> {
+    let mut conn = state.get_connection()?;
 
+    let link = share_links::table
+        .filter(share_links::token.eq(&token))
+        .first::<ShareLink>(&mut conn)?;
+
+    link.ensure_usable()?;
+    link.check_password(params.password.as_deref())?;
+
+    let permission = link
+        .permission_type()
+        .ok_or_else(|| AppError::InternalServerError("Invalid share link permission".to_string()))?;
+
+    let document = documents::table
+        .find(link.document_id)
+        .filter(documents::deleted_at.is_null())
+        .select(Document::as_select())
+        .first::<Document>(&mut conn)
+        .map_err(|_| AppError::NotFound("Document not found".to_string()))?;
+
+    if document.is_folder {
+        return Err(AppError::BadRequest("Cannot edit a folder".to_string()));
+    }
+
+    // Nobody may edit through a link while someone holds the check-out
+    let modifiable = document.active_lock_holder().is_none()
+        && !LifecycleService::is_read_only(&document)
+        && RetentionService::active_holds(&mut conn, &document)?.is_empty();
+
+    let file_type = OnlyOfficeService::get_file_type_from_extension(file_extension(&document));
+    let access = EditorAccess::for_share_link(permission, modifiable, file_type);
+
+    // Opening the editor counts as an access
+    diesel::update(share_links::table.find(link.id))
+        .set(share_links::access_count.eq(share_links::access_count + 1))
+        .execute(&mut conn)?;
+
+    let user_name = params
+        .name
+        .map(|name| name.trim().chars().take(64).collect::<String>())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "Guest".to_string());
+
+    build_editor_config(
+        &state,
+        &mut conn,
+        &document,
+        &OnlyOfficeService::share_user_id(link.id),
+        &user_name,
+        access,
+    )
+    .await
+}
+
+// Signed editor config for DocEditor
+pub(crate) async fn build_editor_config(
+    state: &AppState,
+    conn: &mut DbConnection,
+    document: &Document,
+    user_id: &str,
+    user_name: &str,
+    access: EditorAccess,
+) -> Result<Json<serde_json::Value>> {
     // Get presigned URL for the document (使用 OnlyOffice 可访问的 URL)
     let storage_service = StorageService::new(&state.config.minio)?;
     let file_url = storage_service.get_file_url_for_onlyoffice(&document.file_path, 3600).await?;
 
-    // Get file extension
-    let file_extension = std::path::Path::new(&document.name)
-        .extension()
-        .and_then(|s| s.to_str())
-        .unwrap_or("docx");
-
+    let file_extension = file_extension(document);
     let file_type = OnlyOfficeService::get_file_type_from_extension(file_extension);
     let document_type = OnlyOfficeService::get_document_type_from_extension(file_extension);
 
+    // Join a session that is still open after a force save
+    let document_key = EditorSessionService::editing_key(conn, document)?;
+
     // Generate OnlyOffice config
     let onlyoffice_service = OnlyOfficeService::new(&state.config.onlyoffice);
     let editor_config = onlyoffice_service.generate_editor_config(
-        document_id,
+        document.id,
+        &document_key,
         &document.name,
         file_type,
         &file_url,
         user_id,
-        &auth_user.claims.username,
-        can_edit,
+        user_name,
+        access,
         &state.config.app.url,
     )?;
 
@@ -108,8 +184,21 @@ pub async fn get_editor_config(
 pub async fn onlyoffice_callback(
     State(state): State<AppState>,
     Path(document_id): Path<Uuid>,
+    headers: HeaderMap,
     Json(callback_data): Json<OnlyOfficeCallbackData>,
 ) -> Result<Json<CallbackResponse>> {
+    let onlyoffice_service = OnlyOfficeService::new(&state.config.onlyoffice);
+
+    // The route is unauthenticated, so trust only what Document Server signed
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    let callback_data = onlyoffice_service.verify_callback(&callback_data, authorization)?;
+
+    let (key_document_id, key_version) = OnlyOfficeService::parse_document_key(&callback_data.key)
+        .filter(|(id, _)| *id == document_id)
+        .ok_or_else(|| AppError::BadRequest("Callback key does not match the document".to_string()))?;
+
     let mut conn = state.get_connection()?;
 
     // Verify document exists
@@ -119,61 +208,348 @@ pub async fn onlyoffice_callback(
         .first::<Document>(&mut conn)
         .map_err(|_| AppError::NotFound("Document not found".to_string()))?;
 
-    let onlyoffice_service = OnlyOfficeService::new(&state.config.onlyoffice);
+    let editor_ids: Vec<String> = callback_data.users.iter().flatten().cloned().collect();
+
+    // Track who is editing: status 1 reports the current editors, 2 and 4
+    // mean everyone has left
+    match callback_data.status {
+        1 => EditorSessionService::sync_editors(&mut conn, key_document_id, &callback_data.key, &editor_ids)?,
+        2 | 4 => EditorSessionService::end_all(&mut conn, key_document_id)?,
+        _ => {}
+    }
+
     let response = onlyoffice_service.handle_callback(callback_data.clone()).await?;
 
     // If document is ready for saving (status 2 or 6)
     if matches!(callback_data.status, 2 | 6) {
         if let Some(download_url) = &response.download_url {
+            // A session that was force-saved keeps its key while the version moves on
+            let continues_session = VersionService::current_version(&mut conn, &document)?
+                .is_some_and(|version| {
+                    version.is_forcesave && version.document_key.as_deref() == Some(callback_data.key.as_str())
+                });
+
+            // Saving an older version would overwrite changes made since
+            if key_version != document.version && !continues_session {
+                return Err(AppError::BadRequest(format!(
+                    "Callback is for version {} but the document is at version {}",
+                    key_version, document.version
+                )));
+            }
+
+            let (saved_by, share_link_id) = resolve_saved_by(&mut conn, &callback_data, &document)?;
+
+            // The document may have been checked out since the editor opened;
+            // link editors may not save under anyone's check-out
+            if share_link_id.is_some() && document.active_lock_holder().is_some() {
+                return Err(AppError::Locked("Document is checked out".to_string()));
+            }
+            LockService::ensure_not_locked_by_other(&document, saved_by)?;
+
             // Download the updated file from OnlyOffice
-            let client = reqwest::Client::new();
-            let file_data = client
-                .get(download_url)
-                .send()
-                .await
-                .map_err(|e| AppError::InternalServerError(format!("Failed to download updated file: {}", e)))?
-                .bytes()
-                .await
-                .map_err(|e| AppError::InternalServerError(format!("Failed to read file data: {}", e)))?;
-
-            // Upload new version to MinIO
+            let file_data = onlyoffice_service.download_server_file(download_url).await?;
+
+            LifecycleService::ensure_editable(&document)?;
+            RetentionService::ensure_not_on_hold(&mut conn, &document)?;
+
+            // Any error response makes Document Server keep the edits and
+            // retry, so the save goes through once space is freed
+            QuotaService::check_quota(&mut conn, document.owner_id, file_data.len() as i64)?;
+
+            // Store as a new version
             let storage_service = StorageService::new(&state.config.minio)?;
-            let new_file_path = storage_service
-                .upload_file(&file_data, &document.name, &document.mime_type)
-                .await?;
-
-            // Create new version record
-            use crate::models::document::NewDocumentVersion;
-            use crate::schema::document_versions;
-
-            let new_version = NewDocumentVersion {
-                document_id,
-                version: document.version + 1,
-                file_path: new_file_path.clone(),
-                file_size: file_data.len() as i64,
-                comment: Some("Updated via OnlyOffice".to_string()),
-                created_by: document.owner_id, // TODO: Get actual editor user_id
-            };
-
-            diesel::insert_into(document_versions::table)
-                .values(&new_version)
-                .execute(&mut conn)?;
-
-            // Update document with new version
-            diesel::update(documents::table.find(document_id))
-                .set((
-                    documents::file_path.eq(&new_file_path),
-                    documents::file_size.eq(file_data.len() as i64),
-                    documents::version.eq(document.version + 1),
-                    documents::updated_at.eq(diesel::dsl::now),
-                ))
-                .execute(&mut conn)?;
-
-            // Delete old file from MinIO
-            let _ = storage_service.delete_file(&document.file_path).await;
+            let updated = VersionService::create_editor_version(
+                &mut conn,
+                &storage_service,
+                &document,
+                &file_data,
+                Some(callback_data.save_comment().to_string()),
+                saved_by,
+                EditorSave {
+                    document_key: Some(callback_data.key.clone()),
+                    is_forcesave: callback_data.status == 6,
+                    history: callback_data.history.clone(),
+                    share_link_id,
+                },
+            )
+            .await?;
+
+            // The version is saved; failing now would only make Document Server retry it
+            if let Some(changes_url) = &callback_data.changesurl {
+                let stored = match onlyoffice_service.download_server_file(changes_url).await {
+                    Ok(changes) => {
+                        VersionService::attach_changes(&mut conn, &storage_service, &updated, changes).await
+                    }
+                    Err(e) => Err(e),
+                };
+
+                if let Err(e) = stored {
+                    tracing::warn!("Failed to store OnlyOffice changes for {}: {}", document_id, e);
+                }
+            }
         }
     }
 
     Ok(Json(response))
 }
 
+pub async fn get_document_editors(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+) -> Result<Json<Vec<ActiveEditor>>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    // Check read permission
+    let can_read = PermissionService::check_permission(
+        &mut conn,
+        user_id,
+        document_id,
+        PermissionType::Read,
+    )?;
+
+    if !can_read {
+        return Err(AppError::Forbidden("No permission to view this document".to_string()));
+    }
+
+    let editors = EditorSessionService::active_editors(&mut conn, document_id)?;
+
+    Ok(Json(editors))
+}
+
+pub async fn forcesave_document(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    // Check write permission
+    let can_write = PermissionService::check_permission(
+        &mut conn,
+        user_id,
+        document_id,
+        PermissionType::Write,
+    )?;
+
+    if !can_write {
+        return Err(AppError::Forbidden("No permission to save this document".to_string()));
+    }
+
+    let document = documents::table
+        .find(document_id)
+        .select(Document::as_select())
+        .first::<Document>(&mut conn)?;
+
+    if EditorSessionService::active_editors(&mut conn, document_id)?.is_empty() {
+        return Err(AppError::BadRequest("Document is not being edited".to_string()));
+    }
+
+    let key = EditorSessionService::editing_key(&mut conn, &document)?;
+
+    let onlyoffice_service = OnlyOfficeService::new(&state.config.onlyoffice);
+    let saved = onlyoffice_service.forcesave(&key).await?;
+
+    let message = if saved {
+        "Force save requested"
+    } else {
+        "No changes to save"
+    };
+
+    Ok(Json(serde_json::json!({
+        "message": message,
+        "saved": saved,
+    })))
+}
+
+/// Version list for the editor's `refreshHistory` call
+pub async fn get_editor_history(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let document = PermissionService::load_readable(&mut conn, user_id, document_id)?;
+    let current_key = EditorSessionService::editing_key(&mut conn, &document)?;
+    let versions = history_versions(&mut conn, &document)?;
+
+    let author_ids: Vec<Uuid> = versions.iter().map(|v| v.created_by).collect();
+    let authors: HashMap<Uuid, String> = users::table
+        .filter(users::id.eq_any(&author_ids))
+        .select((users::id, users::username))
+        .load::<(Uuid, String)>(&mut conn)?
+        .into_iter()
+        .collect();
+
+    let history: Vec<serde_json::Value> = versions
+        .iter()
+        .map(|v| {
+            let mut entry = serde_json::json!({
+                "version": v.version,
+                "key": history_key(&document, v.version, &current_key),
+                "created": v.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+                "user": {
+                    "id": v.created_by.to_string(),
+                    "name": authors.get(&v.created_by).cloned().unwrap_or_default(),
+                },
+            });
+
+            // Changes and server version come straight from the save callback
+            if let Some(history) = &v.history {
+                if let Some(changes) = history.get("changes") {
+                    entry["changes"] = changes.clone();
+                }
+                if let Some(server_version) = history.get("serverVersion") {
+                    entry["serverVersion"] = server_version.clone();
+                }
+            }
+
+            entry
+        })
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "currentVersion": document.version,
+        "history": history,
+    })))
+}
+
+/// File URLs of one version for the editor's `setHistoryData` call
+pub async fn get_editor_history_data(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path((document_id, version)): Path<(Uuid, i32)>,
+) -> Result<Json<serde_json::Value>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let document = PermissionService::load_readable(&mut conn, user_id, document_id)?;
+    let current_key = EditorSessionService::editing_key(&mut conn, &document)?;
+    let versions = history_versions(&mut conn, &document)?;
+
+    let entry = versions
+        .iter()
+        .find(|v| v.version == version)
+        .ok_or_else(|| AppError::NotFound("Version not found".to_string()))?;
+
+    let file_type = OnlyOfficeService::get_file_type_from_extension(file_extension(&document));
+
+    // Document Server fetches these itself
+    let storage_service = StorageService::new(&state.config.minio)?;
+    let url = storage_service.get_file_url_for_onlyoffice(&entry.file_path, 3600).await?;
+
+    let mut data = serde_json::json!({
+        "version": version,
+        "key": history_key(&document, version, &current_key),
+        "url": url,
+        "fileType": file_type,
+    });
+
+    if let Some(changes_path) = &entry.changes_path {
+        data["changesUrl"] = storage_service
+            .get_file_url_for_onlyoffice(changes_path, 3600)
+            .await?
+            .into();
+    }
+
+    if let Some(previous) = versions.iter().find(|v| v.version == version - 1) {
+        data["previous"] = serde_json::json!({
+            "key": history_key(&document, previous.version, &current_key),
+            "url": storage_service.get_file_url_for_onlyoffice(&previous.file_path, 3600).await?,
+            "fileType": file_type,
+        });
+    }
+
+    let onlyoffice_service = OnlyOfficeService::new(&state.config.onlyoffice);
+    data["token"] = onlyoffice_service.sign(&data)?.into();
+
+    Ok(Json(data))
+}
+
+fn file_extension(document: &Document) -> &str {
+    std::path::Path::new(&document.name)
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("docx")
+}
+
+// Attribute a save to the first reported editor that still exists: a user,
+// or the creator of the share link an anonymous editor came through
+fn resolve_saved_by(
+    conn: &mut DbConnection,
+    callback_data: &OnlyOfficeCallbackData,
+    document: &Document,
+) -> Result<(Uuid, Option<Uuid>)> {
+    for editor in callback_data.users.iter().flatten() {
+        if let Ok(user_id) = Uuid::parse_str(editor) {
+            let exists = diesel::select(diesel::dsl::exists(users::table.find(user_id)))
+                .get_result::<bool>(conn)?;
+
+            if exists {
+                return Ok((user_id, None));
+            }
+        } else if let Some(link_id) = OnlyOfficeService::parse_share_user_id(editor) {
+            let link = share_links::table
+                .find(link_id)
+                .filter(share_links::document_id.eq(document.id))
+                .first::<ShareLink>(conn)
+                .optional()?;
+
+            if let Some(link) = link {
+                // Opening the editor already counted as an access, so only
+                // expiry can end a session the link let in
+                link.ensure_not_expired()?;
+
+                let can_edit = link.permission_type().is_some_and(|permission| {
+                    EditorAccess::for_share_link(permission, true, file_extension(document)).mode() == "edit"
+                });
+                if !can_edit {
+                    return Err(AppError::Forbidden("Share link does not allow editing".to_string()));
+                }
+
+                return Ok((link.created_by, Some(link.id)));
+            }
+        }
+    }
+
+    tracing::warn!("No known editor in OnlyOffice callback for {}", document.id);
+    Ok((document.owner_id, None))
+}
+
+// Recorded versions, plus the current file when it was never versioned
+fn history_versions(conn: &mut DbConnection, document: &Document) -> Result<Vec<DocumentVersion>> {
+    let mut versions = VersionService::list_versions(conn, document.id)?;
+
+    if versions.last().map(|v| v.version) != Some(document.version) {
+        versions.push(DocumentVersion {
+            id: document.id,
+            document_id: document.id,
+            version: document.version,
+            file_path: document.file_path.clone(),
+            file_size: document.file_size,
+            comment: None,
+            created_by: document.owner_id,
+            created_at: document.updated_at,
+            document_key: None,
+            is_forcesave: false,
+            changes_path: None,
+            history: None,
+            share_link_id: None,
+        });
+    }
+
+    Ok(versions)
+}
+
+// Key of a version's file; the current one is whatever the open session uses
+fn history_key(document: &Document, version: i32, current_key: &str) -> String {
+    if version == document.version {
+        current_key.to_string()
+    } else {
+        OnlyOfficeService::document_key(document.id, version)
+    }
+}
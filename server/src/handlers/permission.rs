@@ -114,6 +114,8 @@ pub async fn create_share_link(
     Path(document_id): Path<Uuid>,
     Json(payload): Json<CreateShareLinkRequest>,
 ) -> Result<Json<ShareLink>> {
+    auth_user.ensure_can_create_share_links()?;
+
     let mut conn = state.get_connection()?;
     let user_id = auth_user.claims.user_id()?;
 
@@ -163,19 +165,8 @@ pub async fn get_share_link(
         .filter(share_links::token.eq(&token))
         .first::<ShareLink>(&mut conn)?;
 
-    // Check if link is expired
-    if let Some(expires_at) = link.expires_at {
-        if expires_at < chrono::Local::now().naive_local() {
-            return Err(AppError::BadRequest("Share link has expired".to_string()));
-        }
-    }
-
-    // Check max access count
-    if let Some(max_count) = link.max_access_count {
-        if link.access_count >= max_count {
-            return Err(AppError::BadRequest("Share link access limit reached".to_string()));
-        }
-    }
+    // Check expiry and max access count
+    link.ensure_usable()?;
 
     // Increment access count
     diesel::update(share_links::table.filter(share_links::token.eq(&token)))
@@ -0,0 +1,141 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    error::{AppError, Result},
+    middleware::AuthUser,
+    models::quota::{NewStorageQuota, SetQuotaRequest, StorageQuota, UsageResponse},
+    schema::storage_quotas,
+    services::QuotaService,
+};
+
+pub async fn get_my_usage(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<UsageResponse>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let report = QuotaService::get_usage_report(&mut conn, user_id)?;
+
+    Ok(Json(report))
+}
+
+pub async fn get_usage_report(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<UsageResponse>>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let rows = QuotaService::get_all_usage(&mut conn)?;
+    let mut report = Vec::with_capacity(rows.len());
+    for row in rows {
+        let quota = QuotaService::get_effective_quota(&mut conn, row.user_id)?;
+        report.push(UsageResponse::new(row, quota));
+    }
+
+    Ok(Json(report))
+}
+
+pub async fn list_quotas(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<StorageQuota>>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let quotas = storage_quotas::table
+        .order(storage_quotas::created_at.desc())
+        .select(StorageQuota::as_select())
+        .load(&mut conn)?;
+
+    Ok(Json(quotas))
+}
+
+pub async fn set_quota(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<SetQuotaRequest>,
+) -> Result<Json<StorageQuota>> {
+    auth_user.require_admin()?;
+
+    if payload.user_id.is_some() == payload.group_id.is_some() {
+        return Err(AppError::BadRequest(
+            "Exactly one of user_id or group_id must be provided".to_string(),
+        ));
+    }
+
+    if payload.max_bytes < 0 {
+        return Err(AppError::BadRequest("max_bytes must not be negative".to_string()));
+    }
+
+    let mut conn = state.get_connection()?;
+    let admin_id = auth_user.claims.user_id()?;
+
+    let existing = match (payload.user_id, payload.group_id) {
+        (Some(target_user_id), _) => storage_quotas::table
+            .filter(storage_quotas::user_id.eq(target_user_id))
+            .select(StorageQuota::as_select())
+            .first(&mut conn)
+            .optional()?,
+        (_, Some(target_group_id)) => storage_quotas::table
+            .filter(storage_quotas::group_id.eq(target_group_id))
+            .select(StorageQuota::as_select())
+            .first(&mut conn)
+            .optional()?,
+        (None, None) => unreachable!(),
+    };
+
+    let quota = if let Some(existing) = existing {
+        diesel::update(storage_quotas::table.find(existing.id))
+            .set((
+                storage_quotas::max_bytes.eq(payload.max_bytes),
+                storage_quotas::updated_at.eq(diesel::dsl::now),
+            ))
+            .returning(StorageQuota::as_returning())
+            .get_result(&mut conn)?
+    } else {
+        let new_quota = NewStorageQuota {
+            user_id: payload.user_id,
+            group_id: payload.group_id,
+            max_bytes: payload.max_bytes,
+            created_by: admin_id,
+        };
+
+        diesel::insert_into(storage_quotas::table)
+            .values(&new_quota)
+            .returning(StorageQuota::as_returning())
+            .get_result(&mut conn)?
+    };
+
+    Ok(Json(quota))
+}
+
+pub async fn delete_quota(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(quota_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let deleted = diesel::delete(storage_quotas::table.find(quota_id))
+        .execute(&mut conn)?;
+
+    if deleted == 0 {
+        return Err(AppError::NotFound("Quota not found".to_string()));
+    }
+
+    Ok(Json(serde_json::json!({
+        "message": "Quota deleted successfully"
+    })))
+}
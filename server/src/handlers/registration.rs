@@ -0,0 +1,172 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    db::AppState,
+    error::{AppError, Result},
+    middleware::AuthUser,
+    models::{
+        registration::{
+            CreateInvitationRequest, CreateInvitationResponse, InvitationInfo, InvitationResponse,
+            ResendVerificationRequest, VerifyEmailRequest,
+        },
+        user::{User, UserResponse},
+    },
+    services::{
+        mailer::{self, MailMessage},
+        RegistrationService,
+    },
+};
+
+pub async fn verify_email(
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> Result<Json<UserResponse>> {
+    let mut conn = state.get_connection()?;
+
+    let user = RegistrationService::verify_email(&mut conn, &payload.token)?;
+
+    Ok(Json(user.into()))
+}
+
+pub async fn resend_verification_email(
+    State(state): State<AppState>,
+    Json(payload): Json<ResendVerificationRequest>,
+) -> Result<Json<serde_json::Value>> {
+    payload.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let mut conn = state.get_connection()?;
+
+    // Answer the same way whether or not the address is waiting for verification
+    if let Some(user) = RegistrationService::find_unverified(&mut conn, &payload.email)? {
+        let token = RegistrationService::create_verification_token(
+            &mut conn,
+            user.id,
+            state.config.registration.verification_expiration,
+        )?;
+
+        send_verification_email(&state, &user, &token).await?;
+    }
+
+    Ok(Json(serde_json::json!({
+        "message": "If the address is waiting for verification, a new link has been sent"
+    })))
+}
+
+/// Who an invitation link is for, so the sign-up page can fill in the address
+pub async fn get_invitation(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Json<InvitationInfo>> {
+    let mut conn = state.get_connection()?;
+
+    let invitation = RegistrationService::find_invitation(&mut conn, &token)?;
+
+    Ok(Json(InvitationInfo {
+        email: invitation.email,
+        role: invitation.role,
+        expires_at: invitation.expires_at,
+    }))
+}
+
+pub async fn list_invitations(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<InvitationResponse>>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let invitations = RegistrationService::list_invitations(&mut conn)?;
+
+    Ok(Json(invitations))
+}
+
+pub async fn create_invitation(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateInvitationRequest>,
+) -> Result<Json<CreateInvitationResponse>> {
+    auth_user.require_admin()?;
+
+    payload.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let mut conn = state.get_connection()?;
+
+    let (invitation, token) = RegistrationService::create_invitation(
+        &mut conn,
+        auth_user.claims.user_id()?,
+        payload,
+        state.config.registration.invitation_expiration,
+    )?;
+
+    let invitation_url = with_token(&state.config.registration.invitation_url, &token);
+
+    let message = MailMessage {
+        to: invitation.invitation.email.clone(),
+        subject: "You're invited".to_string(),
+        body: format!(
+            "Hello,\n\n{} has invited you to create an account. Use the link below to sign up. It expires in {} days and can be used once.\n\n{}\n",
+            auth_user.claims.username,
+            state.config.registration.invitation_expiration / 86400,
+            invitation_url,
+        ),
+    };
+
+    // The admin can still pass the link on themselves
+    if let Err(e) = mailer::from_config(&state.config.mail)?.send(message).await {
+        tracing::error!("Failed to send invitation {}: {}", invitation.invitation.id, e);
+    }
+
+    Ok(Json(CreateInvitationResponse { invitation_url, invitation }))
+}
+
+pub async fn revoke_invitation(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(invitation_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    if !RegistrationService::revoke_invitation(&mut conn, invitation_id)? {
+        return Err(AppError::NotFound("Invitation not found".to_string()));
+    }
+
+    Ok(Json(serde_json::json!({
+        "message": "Invitation revoked"
+    })))
+}
+
+pub(crate) async fn send_verification_email(state: &AppState, user: &User, token: &str) -> Result<()> {
+    let verify_link = with_token(&state.config.registration.verification_url, token);
+
+    let message = MailMessage {
+        to: user.email.clone(),
+        subject: "Confirm your email address".to_string(),
+        body: format!(
+            "Hello {},\n\nUse the link below to confirm your email address and activate your account. It expires in {} hours and can be used once.\n\n{}\n\nIf you didn't sign up, you can ignore this email.\n",
+            user.full_name.as_deref().unwrap_or(&user.username),
+            state.config.registration.verification_expiration / 3600,
+            verify_link,
+        ),
+    };
+
+    if let Err(e) = mailer::from_config(&state.config.mail)?.send(message).await {
+        tracing::error!("Failed to send verification email to user {}: {}", user.id, e);
+    }
+
+    Ok(())
+}
+
+fn with_token(url: &str, token: &str) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{}{}token={}", url, separator, token)
+}
@@ -0,0 +1,147 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    error::{AppError, Result},
+    middleware::AuthUser,
+    models::{
+        document::{Document, NewDocument},
+        rendition::{ConvertDocumentRequest, CreateRenditionRequest, DocumentRendition},
+    },
+    schema::documents,
+    services::{
+        MetadataService, OnlyOfficeService, PermissionService, QuotaService, RenditionService,
+        SearchService, StorageService,
+    },
+};
+
+pub async fn create_rendition(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+    Json(payload): Json<CreateRenditionRequest>,
+) -> Result<Json<DocumentRendition>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let document = PermissionService::load_readable(&mut conn, user_id, document_id)?;
+    let format = payload.format.to_lowercase();
+    RenditionService::ensure_convertible(&document, &format)?;
+
+    let storage_service = StorageService::new(&state.config.minio)?;
+    let onlyoffice_service = OnlyOfficeService::new(&state.config.onlyoffice);
+
+    let rendition = RenditionService::get_or_create(
+        &mut conn,
+        &storage_service,
+        &onlyoffice_service,
+        &document,
+        &format,
+        user_id,
+    )
+    .await?;
+
+    Ok(Json(rendition))
+}
+
+pub async fn list_renditions(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+) -> Result<Json<Vec<DocumentRendition>>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    PermissionService::load_readable(&mut conn, user_id, document_id)?;
+
+    let renditions = RenditionService::list(&mut conn, document_id)?;
+
+    Ok(Json(renditions))
+}
+
+pub async fn download_rendition(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path((document_id, format)): Path<(Uuid, String)>,
+) -> Result<String> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let document = PermissionService::load_readable(&mut conn, user_id, document_id)?;
+
+    let rendition = RenditionService::find(&mut conn, &document, &format.to_lowercase())?
+        .ok_or_else(|| AppError::NotFound("Rendition not found for the current version".to_string()))?;
+
+    // Generate presigned URL
+    let storage_service = StorageService::new(&state.config.minio)?;
+    let url = storage_service.get_file_url(&rendition.file_path, 3600).await?;
+
+    Ok(url)
+}
+
+pub async fn convert_document(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+    Json(payload): Json<ConvertDocumentRequest>,
+) -> Result<Json<Document>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let source = PermissionService::load_readable(&mut conn, user_id, document_id)?;
+    let output_type = payload.output_type.to_lowercase();
+    RenditionService::ensure_convertible(&source, &output_type)?;
+
+    let parent_folder_id = payload.parent_folder_id.or(source.parent_folder_id);
+
+    auth_user.ensure_can_add_to(&mut conn, parent_folder_id)?;
+
+    let name = payload.name.unwrap_or_else(|| {
+        let stem = std::path::Path::new(&source.name)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(&source.name);
+        format!("{}.{}", stem, output_type)
+    });
+    let mime_type = OnlyOfficeService::mime_type_for_file_type(&output_type).to_string();
+    let metadata = MetadataService::validate_for_document(&mut conn, parent_folder_id, &mime_type, None)?;
+
+    let storage_service = StorageService::new(&state.config.minio)?;
+    let onlyoffice_service = OnlyOfficeService::new(&state.config.onlyoffice);
+
+    let file_data = RenditionService::convert(&storage_service, &onlyoffice_service, &source, &output_type).await?;
+
+    // Check storage quota
+    QuotaService::check_quota(&mut conn, user_id, file_data.len() as i64)?;
+
+    let file_path = storage_service.upload_file(&file_data, &name, &mime_type).await?;
+
+    let new_document = NewDocument {
+        name,
+        description: source.description.clone(),
+        file_path,
+        file_size: file_data.len() as i64,
+        mime_type,
+        owner_id: user_id,
+        parent_folder_id,
+        is_folder: false,
+        tags: source.tags.clone(),
+        metadata,
+    };
+
+    let document = diesel::insert_into(documents::table)
+        .values(&new_document)
+        .returning(Document::as_returning())
+        .get_result(&mut conn)?;
+
+    // Index in search
+    let search_service = SearchService::new(&state.config.meilisearch)?;
+    search_service.index_document(document.clone()).await?;
+
+    Ok(Json(document))
+}
@@ -0,0 +1,272 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use diesel::prelude::*;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    db::AppState,
+    error::{AppError, Result},
+    handlers::document::PaginationParams,
+    middleware::AuthUser,
+    models::{
+        document::Document,
+        permission::PermissionType,
+        retention::{
+            CreateLegalHoldRequest, CreateRetentionPolicyRequest, LegalHold, NewLegalHold,
+            NewRetentionPolicy, RetentionAuditLog, RetentionPolicy, RetentionStatusResponse,
+            UpdateRetentionPolicyRequest,
+        },
+    },
+    schema::{documents, legal_holds, retention_audit_logs, retention_policies},
+    services::{PermissionService, RetentionService},
+};
+
+pub async fn list_retention_policies(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<RetentionPolicy>>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let policies = retention_policies::table
+        .order(retention_policies::created_at.desc())
+        .select(RetentionPolicy::as_select())
+        .load(&mut conn)?;
+
+    Ok(Json(policies))
+}
+
+pub async fn create_retention_policy(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateRetentionPolicyRequest>,
+) -> Result<Json<RetentionPolicy>> {
+    auth_user.require_admin()?;
+
+    payload.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    if payload.folder_id.is_none() && payload.tag.is_none() && payload.mime_type.is_none() {
+        return Err(AppError::BadRequest(
+            "A policy must match on at least one of folder_id, tag or mime_type".to_string(),
+        ));
+    }
+
+    let mut conn = state.get_connection()?;
+    let admin_id = auth_user.claims.user_id()?;
+
+    if let Some(folder_id) = payload.folder_id {
+        let is_folder = documents::table
+            .find(folder_id)
+            .select(documents::is_folder)
+            .first::<bool>(&mut conn)?;
+
+        if !is_folder {
+            return Err(AppError::BadRequest("folder_id must refer to a folder".to_string()));
+        }
+    }
+
+    let new_policy = NewRetentionPolicy {
+        name: payload.name,
+        folder_id: payload.folder_id,
+        tag: payload.tag,
+        mime_type: payload.mime_type,
+        retention_days: payload.retention_days,
+        created_by: admin_id,
+    };
+
+    let policy = diesel::insert_into(retention_policies::table)
+        .values(&new_policy)
+        .returning(RetentionPolicy::as_returning())
+        .get_result(&mut conn)?;
+
+    Ok(Json(policy))
+}
+
+pub async fn update_retention_policy(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(policy_id): Path<Uuid>,
+    Json(payload): Json<UpdateRetentionPolicyRequest>,
+) -> Result<Json<RetentionPolicy>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let policy = retention_policies::table
+        .find(policy_id)
+        .select(RetentionPolicy::as_select())
+        .first::<RetentionPolicy>(&mut conn)?;
+
+    let retention_days = payload.retention_days.unwrap_or(policy.retention_days);
+    if !(1..=36500).contains(&retention_days) {
+        return Err(AppError::BadRequest("retention_days must be between 1 and 36500".to_string()));
+    }
+
+    let policy = diesel::update(retention_policies::table.find(policy_id))
+        .set((
+            retention_policies::retention_days.eq(retention_days),
+            retention_policies::is_active.eq(payload.is_active.unwrap_or(policy.is_active)),
+            retention_policies::updated_at.eq(diesel::dsl::now),
+        ))
+        .returning(RetentionPolicy::as_returning())
+        .get_result(&mut conn)?;
+
+    Ok(Json(policy))
+}
+
+pub async fn delete_retention_policy(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(policy_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let deleted = diesel::delete(retention_policies::table.find(policy_id))
+        .execute(&mut conn)?;
+
+    if deleted == 0 {
+        return Err(AppError::NotFound("Retention policy not found".to_string()));
+    }
+
+    Ok(Json(serde_json::json!({
+        "message": "Retention policy deleted successfully"
+    })))
+}
+
+pub async fn list_retention_audit_logs(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(params): Query<PaginationParams>,
+) -> Result<Json<Vec<RetentionAuditLog>>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let logs = retention_audit_logs::table
+        .order(retention_audit_logs::executed_at.desc())
+        .limit(params.limit)
+        .offset(params.offset)
+        .select(RetentionAuditLog::as_select())
+        .load(&mut conn)?;
+
+    Ok(Json(logs))
+}
+
+pub async fn get_retention_status(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+) -> Result<Json<RetentionStatusResponse>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    // Check read permission
+    let can_read = PermissionService::check_permission(
+        &mut conn,
+        user_id,
+        document_id,
+        PermissionType::Read,
+    )?;
+
+    if !can_read {
+        return Err(AppError::Forbidden("No permission to view this document".to_string()));
+    }
+
+    let document = documents::table
+        .find(document_id)
+        .select(Document::as_select())
+        .first::<Document>(&mut conn)?;
+
+    let status = RetentionService::retention_status(&mut conn, &document)?;
+
+    Ok(Json(status))
+}
+
+pub async fn place_legal_hold(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+    Json(payload): Json<CreateLegalHoldRequest>,
+) -> Result<Json<LegalHold>> {
+    auth_user.require_admin()?;
+
+    payload.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let mut conn = state.get_connection()?;
+    let admin_id = auth_user.claims.user_id()?;
+
+    // Make sure the document exists
+    documents::table
+        .find(document_id)
+        .select(documents::id)
+        .first::<Uuid>(&mut conn)?;
+
+    let new_hold = NewLegalHold {
+        document_id,
+        reason: payload.reason,
+        created_by: admin_id,
+    };
+
+    let hold = diesel::insert_into(legal_holds::table)
+        .values(&new_hold)
+        .returning(LegalHold::as_returning())
+        .get_result(&mut conn)?;
+
+    tracing::info!("Legal hold {} placed on {} by {}", hold.id, document_id, admin_id);
+
+    Ok(Json(hold))
+}
+
+pub async fn release_legal_hold(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(hold_id): Path<Uuid>,
+) -> Result<Json<LegalHold>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+    let admin_id = auth_user.claims.user_id()?;
+
+    let hold = diesel::update(
+        legal_holds::table
+            .filter(legal_holds::id.eq(hold_id))
+            .filter(legal_holds::released_at.is_null()),
+    )
+    .set((
+        legal_holds::released_by.eq(Some(admin_id)),
+        legal_holds::released_at.eq(diesel::dsl::now.nullable()),
+    ))
+    .returning(LegalHold::as_returning())
+    .get_result::<LegalHold>(&mut conn)
+    .optional()?
+    .ok_or_else(|| AppError::NotFound("Active legal hold not found".to_string()))?;
+
+    tracing::info!("Legal hold {} released by {}", hold.id, admin_id);
+
+    Ok(Json(hold))
+}
+
+pub async fn list_legal_holds(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<LegalHold>>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let holds = legal_holds::table
+        .filter(legal_holds::released_at.is_null())
+        .order(legal_holds::created_at.desc())
+        .select(LegalHold::as_select())
+        .load(&mut conn)?;
+
+    Ok(Json(holds))
+}
@@ -3,12 +3,14 @@ use axum::{
     Json,
 };
 use serde::Deserialize;
+use uuid::Uuid;
 
 use crate::{
     db::AppState,
-    error::Result,
+    error::{AppError, Result},
     middleware::AuthUser,
-    services::{SearchService, search::DocumentSearchIndex},
+    models::lifecycle::DocumentStatus,
+    services::{PermissionService, SearchService, search::DocumentSearchIndex},
 };
 
 #[derive(Deserialize)]
@@ -21,6 +23,11 @@ pub struct SearchParams {
     pub owner_id: Option<String>,
     pub mime_type: Option<String>,
     pub is_folder: Option<bool>,
+    pub status: Option<String>,
+    /// Metadata filters as `field:value` pairs separated by commas
+    pub metadata: Option<String>,
+    /// Sort rules such as `metadata.value:desc,name:asc`
+    pub sort: Option<String>,
 }
 
 fn default_limit() -> usize {
@@ -29,36 +36,113 @@ fn default_limit() -> usize {
 
 pub async fn search_documents(
     State(state): State<AppState>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
     Query(params): Query<SearchParams>,
 ) -> Result<Json<Vec<DocumentSearchIndex>>> {
     let search_service = SearchService::new(&state.config.meilisearch)?;
 
     // Build filters
     let mut filters = Vec::new();
+
+    // Guests only find what they could open anyway
+    if !auth_user.role().can_search_all() {
+        let mut conn = state.get_connection()?;
+        let readable = PermissionService::readable_document_ids(&mut conn, auth_user.claims.user_id()?)?;
+
+        if readable.is_empty() {
+            return Ok(Json(Vec::new()));
+        }
+
+        let ids: Vec<String> = readable.iter().map(|id| quote(&id.to_string())).collect();
+        filters.push(format!("id IN [{}]", ids.join(", ")));
+    }
     
     if let Some(owner_id) = params.owner_id {
-        filters.push(format!("owner_id = \"{}\"", owner_id));
+        let owner_id = Uuid::parse_str(&owner_id)
+            .map_err(|_| AppError::BadRequest(format!("Invalid owner_id '{}'", owner_id)))?;
+        filters.push(format!("owner_id = {}", quote(&owner_id.to_string())));
     }
     
     if let Some(mime_type) = params.mime_type {
-        filters.push(format!("mime_type = \"{}\"", mime_type));
+        filters.push(format!("mime_type = {}", quote(&mime_type)));
     }
     
     if let Some(is_folder) = params.is_folder {
         filters.push(format!("is_folder = {}", is_folder));
     }
 
+    if let Some(status) = params.status {
+        let status = DocumentStatus::from_str(&status)
+            .ok_or_else(|| AppError::BadRequest(format!("Unknown status '{}'", status)))?;
+        filters.push(format!("status = {}", quote(status.as_str())));
+    }
+
+    if let Some(metadata) = params.metadata {
+        for pair in metadata.split(',').filter(|p| !p.is_empty()) {
+            let (field, value) = pair
+                .split_once(':')
+                .ok_or_else(|| AppError::BadRequest(format!("Invalid metadata filter '{}'", pair)))?;
+
+            if !is_attribute_name(field) {
+                return Err(AppError::BadRequest(format!("Invalid metadata field '{}'", field)));
+            }
+
+            filters.push(format!("metadata.{} = {}", field, quote(value)));
+        }
+    }
+
+    // Each clause is grouped on its own, so an OR inside one can't widen the guest restriction
     let filter_str = if filters.is_empty() {
         None
     } else {
-        Some(filters.join(" AND "))
+        Some(
+            filters
+                .iter()
+                .map(|filter| format!("({})", filter))
+                .collect::<Vec<_>>()
+                .join(" AND "),
+        )
+    };
+
+    let sort_rules: Vec<String> = match params.sort {
+        Some(sort) => sort
+            .split(',')
+            .filter(|rule| !rule.is_empty())
+            .map(|rule| {
+                let valid = rule
+                    .rsplit_once(':')
+                    .map(|(attr, dir)| {
+                        (dir == "asc" || dir == "desc")
+                            && attr.split('.').all(is_attribute_name)
+                    })
+                    .unwrap_or(false);
+
+                if valid {
+                    Ok(rule.to_string())
+                } else {
+                    Err(AppError::BadRequest(format!("Invalid sort rule '{}'", rule)))
+                }
+            })
+            .collect::<Result<_>>()?,
+        None => Vec::new(),
     };
+    let sort: Vec<&str> = sort_rules.iter().map(String::as_str).collect();
 
     let results = search_service
-        .search(&params.q, filter_str, Some(params.limit), Some(params.offset))
+        .search(&params.q, filter_str, &sort, Some(params.limit), Some(params.offset))
         .await?;
 
     Ok(Json(results.hits.into_iter().map(|hit| hit.result).collect()))
 }
 
+/// A string literal for a Meilisearch filter
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn is_attribute_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
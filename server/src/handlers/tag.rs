@@ -0,0 +1,156 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use diesel::prelude::*;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    db::AppState,
+    error::{AppError, Result},
+    middleware::AuthUser,
+    models::tag::{
+        CreateTagRequest, MergeTagsRequest, NewTag, RenameTagRequest, Tag, TagRewriteResponse,
+        TagSummary,
+    },
+    schema::tags,
+    services::{SearchService, TagService},
+};
+
+pub async fn list_tags(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+) -> Result<Json<Vec<TagSummary>>> {
+    let mut conn = state.get_connection()?;
+
+    let tags = TagService::list_usage(&mut conn)?;
+
+    Ok(Json(tags))
+}
+
+pub async fn create_tag(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateTagRequest>,
+) -> Result<Json<Tag>> {
+    auth_user.require_admin()?;
+
+    payload.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let name = TagService::normalize(&payload.name)
+        .ok_or_else(|| AppError::ValidationError("Tag name cannot be empty".to_string()))?;
+
+    let mut conn = state.get_connection()?;
+    let admin_id = auth_user.claims.user_id()?;
+
+    let new_tag = NewTag {
+        name,
+        description: payload.description,
+        created_by: admin_id,
+    };
+
+    let tag = diesel::insert_into(tags::table)
+        .values(&new_tag)
+        .returning(Tag::as_returning())
+        .get_result(&mut conn)?;
+
+    Ok(Json(tag))
+}
+
+pub async fn delete_tag(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(tag_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    // Documents keep the tag; it just stops being part of the vocabulary
+    let deleted = diesel::delete(tags::table.find(tag_id))
+        .execute(&mut conn)?;
+
+    if deleted == 0 {
+        return Err(AppError::NotFound("Tag not found".to_string()));
+    }
+
+    Ok(Json(serde_json::json!({
+        "message": "Tag deleted successfully"
+    })))
+}
+
+pub async fn rename_tag(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<RenameTagRequest>,
+) -> Result<Json<TagRewriteResponse>> {
+    auth_user.require_admin()?;
+
+    let from = TagService::normalize(&payload.from)
+        .ok_or_else(|| AppError::ValidationError("Tag name cannot be empty".to_string()))?;
+    let to = TagService::normalize(&payload.to)
+        .ok_or_else(|| AppError::ValidationError("Tag name cannot be empty".to_string()))?;
+
+    let mut conn = state.get_connection()?;
+    let admin_id = auth_user.claims.user_id()?;
+
+    let target_exists = TagService::list_usage(&mut conn)?
+        .iter()
+        .any(|tag| tag.name == to && to != from);
+
+    if target_exists {
+        return Err(AppError::BadRequest(format!(
+            "Tag '{}' already exists; merge the tags instead",
+            to
+        )));
+    }
+
+    let search_service = SearchService::new(&state.config.meilisearch)?;
+    let sources = vec![from];
+
+    let response = TagService::replace_tags(&mut conn, &search_service, &sources, &to).await?;
+    TagService::replace_vocabulary(&mut conn, &sources, &to, admin_id)?;
+
+    Ok(Json(response))
+}
+
+pub async fn merge_tags(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<MergeTagsRequest>,
+) -> Result<Json<TagRewriteResponse>> {
+    auth_user.require_admin()?;
+
+    let target = TagService::normalize(&payload.target)
+        .ok_or_else(|| AppError::ValidationError("Tag name cannot be empty".to_string()))?;
+    let sources = TagService::normalize_all(&payload.sources);
+
+    if sources.is_empty() {
+        return Err(AppError::BadRequest("No source tags provided".to_string()));
+    }
+
+    let mut conn = state.get_connection()?;
+    let admin_id = auth_user.claims.user_id()?;
+    let search_service = SearchService::new(&state.config.meilisearch)?;
+
+    let response = TagService::replace_tags(&mut conn, &search_service, &sources, &target).await?;
+    TagService::replace_vocabulary(&mut conn, &sources, &target, admin_id)?;
+
+    Ok(Json(response))
+}
+
+pub async fn normalize_tags(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<TagRewriteResponse>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+    let search_service = SearchService::new(&state.config.meilisearch)?;
+
+    let response = TagService::normalize_documents(&mut conn, &search_service).await?;
+
+    Ok(Json(response))
+}
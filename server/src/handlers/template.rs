@@ -0,0 +1,267 @@
+use axum::{
+    extract::{Multipart, Path, Query, State},
+    Json,
+};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    error::{AppError, Result},
+    middleware::AuthUser,
+    models::{
+        document::{Document, NewDocument},
+        permission::PermissionType,
+        template::{
+            CreateFromTemplateRequest, DocumentTemplate, NewDocumentTemplate, TemplateListParams,
+            TEMPLATE_KINDS,
+        },
+    },
+    schema::{document_templates, documents},
+    services::{
+        onlyoffice::EditorAccess, MetadataService, OnlyOfficeService, QuotaService,
+        SearchService, StorageService, TemplateService,
+    },
+};
+
+use super::onlyoffice::build_editor_config;
+
+pub async fn list_templates(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(params): Query<TemplateListParams>,
+) -> Result<Json<Vec<DocumentTemplate>>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let templates = TemplateService::visible_templates(&mut conn, user_id, params.kind.as_deref())?;
+
+    Ok(Json(templates))
+}
+
+pub async fn create_document_from_template(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(template_id): Path<Uuid>,
+    Json(payload): Json<CreateFromTemplateRequest>,
+) -> Result<Json<serde_json::Value>> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    let template = TemplateService::find_visible(&mut conn, user_id, template_id)?;
+
+    auth_user.ensure_can_add_to(&mut conn, payload.parent_folder_id)?;
+
+    let name = TemplateService::document_name(&template, payload.name.as_deref())?;
+    let metadata = MetadataService::validate_for_document(
+        &mut conn,
+        payload.parent_folder_id,
+        &template.mime_type,
+        None,
+    )?;
+
+    // Check storage quota
+    QuotaService::check_quota(&mut conn, user_id, template.file_size)?;
+
+    // Copy the template; same key layout as uploaded files
+    let storage_service = StorageService::new(&state.config.minio)?;
+    let file_path = format!("{}/{}", Uuid::new_v4(), name);
+    storage_service.copy_file(&template.file_path, &file_path).await?;
+
+    let new_document = NewDocument {
+        name,
+        description: None,
+        file_path: file_path.clone(),
+        file_size: template.file_size,
+        mime_type: template.mime_type.clone(),
+        owner_id: user_id,
+        parent_folder_id: payload.parent_folder_id,
+        is_folder: false,
+        tags: None,
+        metadata,
+    };
+
+    let document = match diesel::insert_into(documents::table)
+        .values(&new_document)
+        .returning(Document::as_returning())
+        .get_result::<Document>(&mut conn)
+    {
+        Ok(document) => document,
+        Err(e) => {
+            let _ = storage_service.delete_file(&file_path).await;
+            return Err(e.into());
+        }
+    };
+
+    // Index in search
+    let search_service = SearchService::new(&state.config.meilisearch)?;
+    search_service.index_document(document.clone()).await?;
+
+    // The creator owns the new document, so it opens for editing
+    let file_type = OnlyOfficeService::get_file_type_from_extension(
+        std::path::Path::new(&document.name)
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or("docx"),
+    );
+    let access = EditorAccess::from_permissions(&PermissionType::ALL, true, file_type);
+
+    let Json(editor) = build_editor_config(
+        &state,
+        &mut conn,
+        &document,
+        &user_id.to_string(),
+        &auth_user.claims.username,
+        access,
+    )
+    .await?;
+
+    Ok(Json(serde_json::json!({
+        "document": document,
+        "editor": editor,
+    })))
+}
+
+pub async fn list_all_templates(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<DocumentTemplate>>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let templates = document_templates::table
+        .order((document_templates::kind.asc(), document_templates::name.asc()))
+        .select(DocumentTemplate::as_select())
+        .load(&mut conn)?;
+
+    Ok(Json(templates))
+}
+
+pub async fn create_template(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    mut multipart: Multipart,
+) -> Result<Json<DocumentTemplate>> {
+    auth_user.require_admin()?;
+
+    let admin_id = auth_user.claims.user_id()?;
+    let mut file_data: Option<Vec<u8>> = None;
+    let mut file_name: Option<String> = None;
+    let mut name: Option<String> = None;
+    let mut description: Option<String> = None;
+    let mut group_id: Option<Uuid> = None;
+
+    // Parse multipart form data
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        AppError::BadRequest(format!("Failed to parse multipart: {}", e))
+    })? {
+        let field_name = field.name().unwrap_or("").to_string();
+
+        match field_name.as_str() {
+            "file" => {
+                file_name = field.file_name().map(|s| s.to_string());
+                file_data = Some(field.bytes().await.map_err(|e| {
+                    AppError::BadRequest(format!("Failed to read file: {}", e))
+                })?.to_vec());
+            }
+            "name" => {
+                name = Some(field.text().await.map_err(|e| {
+                    AppError::BadRequest(format!("Failed to read name: {}", e))
+                })?);
+            }
+            "description" => {
+                description = Some(field.text().await.map_err(|e| {
+                    AppError::BadRequest(format!("Failed to read description: {}", e))
+                })?);
+            }
+            "group_id" => {
+                let value = field.text().await.map_err(|e| {
+                    AppError::BadRequest(format!("Failed to read group_id: {}", e))
+                })?;
+                group_id = Some(Uuid::parse_str(value.trim()).map_err(|_| {
+                    AppError::BadRequest("Invalid group_id".to_string())
+                })?);
+            }
+            _ => {}
+        }
+    }
+
+    let file_data = file_data.ok_or_else(|| AppError::BadRequest("No file provided".to_string()))?;
+    let file_name = file_name.ok_or_else(|| AppError::BadRequest("No filename provided".to_string()))?;
+
+    let kind = TemplateService::kind_for_file_name(&file_name).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "Templates must be one of: {} (docx, xlsx, pptx or OpenDocument)",
+            TEMPLATE_KINDS.join(", ")
+        ))
+    })?;
+
+    let name = name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .or_else(|| {
+            std::path::Path::new(&file_name)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .map(|s| s.to_string())
+        })
+        .unwrap_or_else(|| file_name.clone());
+
+    let storage_service = StorageService::new(&state.config.minio)?;
+    let file_path = TemplateService::template_key(&file_name);
+    let file_size = file_data.len() as i64;
+    storage_service.write_file(&file_path, file_data).await?;
+
+    let new_template = NewDocumentTemplate {
+        name,
+        description,
+        kind: kind.to_string(),
+        mime_type: TemplateService::mime_type_for_file_name(&file_name).to_string(),
+        file_name,
+        file_path: file_path.clone(),
+        file_size,
+        group_id,
+        created_by: admin_id,
+    };
+
+    let mut conn = state.get_connection()?;
+
+    let template = match diesel::insert_into(document_templates::table)
+        .values(&new_template)
+        .returning(DocumentTemplate::as_returning())
+        .get_result(&mut conn)
+    {
+        Ok(template) => template,
+        Err(e) => {
+            let _ = storage_service.delete_file(&file_path).await;
+            return Err(e.into());
+        }
+    };
+
+    Ok(Json(template))
+}
+
+pub async fn delete_template(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(template_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    // Documents created from the template have their own copy of the file
+    let file_path = diesel::delete(document_templates::table.find(template_id))
+        .returning(document_templates::file_path)
+        .get_result::<String>(&mut conn)
+        .optional()?
+        .ok_or_else(|| AppError::NotFound("Template not found".to_string()))?;
+
+    let storage_service = StorageService::new(&state.config.minio)?;
+    storage_service.delete_file(&file_path).await?;
+
+    Ok(Json(serde_json::json!({
+        "message": "Template deleted successfully"
+    })))
+}
@@ -0,0 +1,78 @@
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    error::{AppError, Result},
+    middleware::AuthUser,
+    models::{
+        document::Document,
+        permission::PermissionType,
+        thumbnail::{THUMBNAIL_FAILED, THUMBNAIL_READY, THUMBNAIL_UNSUPPORTED},
+    },
+    schema::documents,
+    services::{PermissionService, StorageService, ThumbnailService},
+};
+
+pub async fn get_document_thumbnail(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document_id): Path<Uuid>,
+) -> Result<Response> {
+    let mut conn = state.get_connection()?;
+    let user_id = auth_user.claims.user_id()?;
+
+    // Check read permission
+    let can_read = PermissionService::check_permission(
+        &mut conn,
+        user_id,
+        document_id,
+        PermissionType::Read,
+    )?;
+
+    if !can_read {
+        return Err(AppError::Forbidden("No permission to view this document".to_string()));
+    }
+
+    let document = documents::table
+        .find(document_id)
+        .select(Document::as_select())
+        .first::<Document>(&mut conn)?;
+
+    if document.is_folder {
+        return Err(AppError::BadRequest("Folders have no thumbnail".to_string()));
+    }
+
+    // Only serve a thumbnail of the current version
+    let thumbnail = ThumbnailService::get_thumbnail(&mut conn, document_id)?
+        .filter(|t| t.version == document.version)
+        .ok_or_else(|| AppError::NotFound("Thumbnail is not ready yet".to_string()))?;
+
+    let file_path = match (thumbnail.status.as_str(), thumbnail.file_path) {
+        (THUMBNAIL_READY, Some(path)) => path,
+        (THUMBNAIL_UNSUPPORTED, _) => {
+            return Err(AppError::NotFound("No thumbnail for this file type".to_string()));
+        }
+        (THUMBNAIL_FAILED, _) => {
+            return Err(AppError::NotFound("Thumbnail generation failed".to_string()));
+        }
+        _ => return Err(AppError::NotFound("Thumbnail is not ready yet".to_string())),
+    };
+
+    let storage_service = StorageService::new(&state.config.minio)?;
+    let data = storage_service.download_file(&file_path).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/png"),
+            (header::CACHE_CONTROL, "private, max-age=300"),
+        ],
+        data,
+    )
+        .into_response())
+}
@@ -0,0 +1,291 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Path, State},
+    http::HeaderMap,
+    Json,
+};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    db::{AppState, DbConnection},
+    error::{AppError, Result},
+    middleware::AuthUser,
+    models::{
+        totp::{
+            DisableTotpRequest, EnableTotpRequest, RecoveryCodesResponse, SetTotpRequiredRequest,
+            TotpChallengeRequest, TotpCodeRequest, TotpLoginRequest, TotpSetupResponse,
+            TotpStatusResponse,
+        },
+        user::{LoginResponse, User, UserResponse},
+    },
+    schema::users,
+    services::{session::ClientInfo, LoginThrottleService, RateLimiter, SessionService, TotpService},
+    utils::{decode_challenge, verify_password, ChallengeClaims},
+};
+
+use super::auth::ensure_password_login;
+
+/// Second step of a login: exchange the challenge token and a code for a session
+pub async fn verify_totp_login(
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<TotpLoginRequest>,
+) -> Result<Json<LoginResponse>> {
+    let mut conn = state.get_connection()?;
+    let user = load_challenged_user(&state, &mut conn, &payload.challenge_token)?;
+
+    // Directory accounts keep logging in when local passwords are turned off
+    if user.ldap_dn.is_none() {
+        ensure_password_login(&state)?;
+    }
+
+    // Wrong codes count against the account like wrong passwords
+    let throttle = &state.config.login_throttle;
+    let throttle_keys = [
+        LoginThrottleService::account_key(&user.username),
+        LoginThrottleService::ip_key(&RateLimiter::client_ip(&state.config.rate_limit, &headers, peer)),
+    ];
+    LoginThrottleService::check(&mut conn, throttle, &throttle_keys)?;
+
+    let second_factor = if user.totp_enabled {
+        TotpService::check_second_factor(
+            &mut conn,
+            &user,
+            payload.code.as_deref(),
+            payload.recovery_code.as_deref(),
+        )
+        .map(|_| None)
+    } else if user.totp_required {
+        // Setting up the authenticator is part of this login
+        let code = payload
+            .code
+            .as_deref()
+            .ok_or_else(|| AppError::BadRequest("A verification code is required".to_string()))?;
+        TotpService::confirm_enrollment(&mut conn, &user, code).map(Some)
+    } else {
+        // Two-factor authentication was switched off after the password step
+        Ok(None)
+    };
+
+    let recovery_codes = match second_factor {
+        Ok(recovery_codes) => recovery_codes,
+        Err(e @ AppError::Unauthorized(_)) => {
+            LoginThrottleService::record_failure(&mut conn, throttle, &throttle_keys)?;
+            return Err(e);
+        }
+        Err(e) => return Err(e),
+    };
+
+    LoginThrottleService::reset(&mut conn, &throttle_keys[0])?;
+
+    let mut response = SessionService::start(
+        &mut conn,
+        &state.config.jwt,
+        user,
+        ClientInfo::from_request(&state.config.rate_limit, &headers, peer),
+    )?;
+    response.recovery_codes = recovery_codes;
+
+    Ok(Json(response))
+}
+
+/// Set up an authenticator during login, for accounts an admin requires it on
+pub async fn setup_totp_login(
+    State(state): State<AppState>,
+    Json(payload): Json<TotpChallengeRequest>,
+) -> Result<Json<TotpSetupResponse>> {
+    let mut conn = state.get_connection()?;
+    let user = load_challenged_user(&state, &mut conn, &payload.challenge_token)?;
+
+    if user.ldap_dn.is_none() {
+        ensure_password_login(&state)?;
+    }
+
+    if !user.totp_required {
+        return Err(AppError::BadRequest(
+            "Log in first to set up two-factor authentication".to_string(),
+        ));
+    }
+
+    let setup = TotpService::begin_enrollment(&mut conn, &state.config.totp, &user)?;
+
+    Ok(Json(setup))
+}
+
+pub async fn get_totp_status(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<TotpStatusResponse>> {
+    let mut conn = state.get_connection()?;
+    let user = load_user(&mut conn, auth_user.claims.user_id()?)?;
+
+    let recovery_codes_remaining = TotpService::remaining_recovery_codes(&mut conn, user.id)?;
+
+    Ok(Json(TotpStatusResponse {
+        enabled: user.totp_enabled,
+        required: user.totp_required,
+        recovery_codes_remaining,
+    }))
+}
+
+pub async fn setup_totp(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<TotpSetupResponse>> {
+    auth_user.ensure_not_impersonating()?;
+
+    let mut conn = state.get_connection()?;
+    let user = load_user(&mut conn, auth_user.claims.user_id()?)?;
+
+    let setup = TotpService::begin_enrollment(&mut conn, &state.config.totp, &user)?;
+
+    Ok(Json(setup))
+}
+
+pub async fn enable_totp(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<EnableTotpRequest>,
+) -> Result<Json<RecoveryCodesResponse>> {
+    auth_user.ensure_not_impersonating()?;
+
+    let mut conn = state.get_connection()?;
+    let user = load_user(&mut conn, auth_user.claims.user_id()?)?;
+
+    let recovery_codes = TotpService::confirm_enrollment(&mut conn, &user, &payload.code)?;
+
+    // Sessions on other devices were opened with the password alone
+    SessionService::revoke_others(&mut conn, user.id, auth_user.claims.sid)?;
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+pub async fn disable_totp(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<DisableTotpRequest>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.ensure_not_impersonating()?;
+
+    let mut conn = state.get_connection()?;
+    let user = load_user(&mut conn, auth_user.claims.user_id()?)?;
+
+    if !user.totp_enabled {
+        return Err(AppError::BadRequest("Two-factor authentication is not enabled".to_string()));
+    }
+
+    if user.totp_required {
+        return Err(AppError::Forbidden(
+            "Two-factor authentication is required for this account".to_string(),
+        ));
+    }
+
+    if !verify_password(&payload.password, &user.password_hash)? {
+        return Err(AppError::Unauthorized("Password is incorrect".to_string()));
+    }
+
+    TotpService::check_second_factor(
+        &mut conn,
+        &user,
+        payload.code.as_deref(),
+        payload.recovery_code.as_deref(),
+    )?;
+
+    TotpService::disable(&mut conn, user.id)?;
+
+    Ok(Json(serde_json::json!({
+        "message": "Two-factor authentication disabled"
+    })))
+}
+
+pub async fn regenerate_recovery_codes(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<TotpCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>> {
+    auth_user.ensure_not_impersonating()?;
+
+    let mut conn = state.get_connection()?;
+    let user = load_user(&mut conn, auth_user.claims.user_id()?)?;
+
+    if !user.totp_enabled {
+        return Err(AppError::BadRequest("Two-factor authentication is not enabled".to_string()));
+    }
+
+    TotpService::check_second_factor(
+        &mut conn,
+        &user,
+        payload.code.as_deref(),
+        payload.recovery_code.as_deref(),
+    )?;
+
+    let recovery_codes = TotpService::regenerate_recovery_codes(&mut conn, user.id)?;
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+pub async fn set_user_totp_required(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<SetTotpRequiredRequest>,
+) -> Result<Json<UserResponse>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let user = TotpService::set_required(&mut conn, user_id, payload.required)?;
+
+    // Existing sessions were opened without a second factor
+    if user.totp_required && !user.totp_enabled {
+        SessionService::revoke_all(&mut conn, user.id)?;
+    }
+
+    Ok(Json(user.into()))
+}
+
+/// Clear a user's authenticator, e.g. after a lost phone
+pub async fn reset_user_totp(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+    let user = load_user(&mut conn, user_id)?;
+
+    TotpService::disable(&mut conn, user.id)?;
+
+    Ok(Json(serde_json::json!({
+        "message": "Two-factor authentication reset"
+    })))
+}
+
+fn load_user(conn: &mut DbConnection, user_id: Uuid) -> Result<User> {
+    users::table
+        .find(user_id)
+        .first::<User>(conn)
+        .optional()?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+}
+
+// User a login challenge was issued to, if they may still log in
+fn load_challenged_user(state: &AppState, conn: &mut DbConnection, challenge_token: &str) -> Result<User> {
+    let claims = decode_challenge(challenge_token, &state.config.jwt.secret, ChallengeClaims::TOTP)?;
+
+    let user = users::table
+        .find(claims.user_id()?)
+        .first::<User>(conn)
+        .optional()?
+        .ok_or_else(|| AppError::Unauthorized("Invalid or expired challenge token".to_string()))?;
+
+    if !user.is_active {
+        return Err(AppError::Unauthorized("Account is not active".to_string()));
+    }
+
+    Ok(user)
+}
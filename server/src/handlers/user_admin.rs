@@ -0,0 +1,308 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    db::AppState,
+    error::{AppError, Result},
+    middleware::AuthUser,
+    models::{
+        admin::{
+            AdminAuditLog, AdminCreateUserRequest, AdminSetPasswordRequest, AdminUpdateUserRequest,
+            DeleteUserParams, DeleteUserResponse, ImpersonateRequest,
+        },
+        user::{LoginResponse, NewUser, UserResponse, UserRole},
+    },
+    services::{
+        session::ClientInfo,
+        user_admin::{AdminActor, UserFilter},
+        PasswordPolicyService, PasswordResetService, RateLimiter, SearchService, SessionService,
+        UserAdminService,
+    },
+    utils::hash_password,
+};
+
+#[derive(Deserialize)]
+pub struct ListUsersParams {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+    pub q: Option<String>,
+    pub role: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct AuditLogParams {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+    pub user_id: Option<Uuid>,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+pub async fn list_users(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(params): Query<ListUsersParams>,
+) -> Result<Json<Vec<UserResponse>>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let filter = UserFilter {
+        query: params.q,
+        role: params.role,
+        is_active: params.is_active,
+        limit: params.limit,
+        offset: params.offset,
+    };
+
+    let users = UserAdminService::list(&mut conn, &filter)?
+        .into_iter()
+        .map(UserResponse::from)
+        .collect();
+
+    Ok(Json(users))
+}
+
+pub async fn get_user(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<UserResponse>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let user = UserAdminService::find(&mut conn, user_id)?;
+
+    Ok(Json(user.into()))
+}
+
+pub async fn create_user(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<AdminCreateUserRequest>,
+) -> Result<Json<UserResponse>> {
+    auth_user.require_admin()?;
+
+    payload.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    PasswordPolicyService::validate(&state.config.password, &payload.password)?;
+
+    let mut conn = state.get_connection()?;
+
+    let new_user = NewUser {
+        username: payload.username,
+        email: payload.email,
+        password_hash: hash_password(&payload.password)?,
+        full_name: payload.full_name,
+        role: payload.role.unwrap_or_else(|| UserRole::User.as_str().to_string()).to_lowercase(),
+    };
+
+    let user = UserAdminService::create(&mut conn, new_user)?;
+
+    let details = format!("role={}", user.role);
+    UserAdminService::audit(&mut conn, &actor(&state, &auth_user, &headers, peer)?, AdminAuditLog::CREATE_USER, &user, Some(details))?;
+
+    Ok(Json(user.into()))
+}
+
+pub async fn update_user(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<AdminUpdateUserRequest>,
+) -> Result<Json<UserResponse>> {
+    auth_user.require_admin()?;
+
+    payload.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let mut conn = state.get_connection()?;
+    let actor = actor(&state, &auth_user, &headers, peer)?;
+
+    let existing = UserAdminService::find(&mut conn, user_id)?;
+    let user = UserAdminService::update(&mut conn, actor.id, &existing, &payload)?;
+
+    // A deactivated user is logged out everywhere
+    if existing.is_active && !user.is_active {
+        SessionService::revoke_all(&mut conn, user.id)?;
+    }
+
+    let changes: Vec<String> = [
+        (existing.email != user.email).then(|| format!("email={}", user.email)),
+        (existing.full_name != user.full_name).then(|| "full_name".to_string()),
+        (existing.role != user.role).then(|| format!("role={}", user.role)),
+        (existing.is_active != user.is_active).then(|| format!("is_active={}", user.is_active)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if !changes.is_empty() {
+        UserAdminService::audit(&mut conn, &actor, AdminAuditLog::UPDATE_USER, &user, Some(changes.join(", ")))?;
+    }
+
+    Ok(Json(user.into()))
+}
+
+/// Set a new password for a user, e.g. when they're locked out
+pub async fn set_user_password(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<AdminSetPasswordRequest>,
+) -> Result<Json<serde_json::Value>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+    let user = UserAdminService::find(&mut conn, user_id)?;
+
+    if user.ldap_dn.is_some() || user.is_service_account {
+        return Err(AppError::BadRequest("This account doesn't log in with a password".to_string()));
+    }
+
+    PasswordPolicyService::validate(&state.config.password, &payload.new_password)?;
+
+    let password_hash = hash_password(&payload.new_password)?;
+    PasswordResetService::set_password(&mut conn, user.id, &password_hash)?;
+
+    // Whoever was using the old password is logged out
+    SessionService::revoke_all(&mut conn, user.id)?;
+
+    UserAdminService::audit(&mut conn, &actor(&state, &auth_user, &headers, peer)?, AdminAuditLog::SET_PASSWORD, &user, None)?;
+
+    Ok(Json(serde_json::json!({
+        "message": "Password updated"
+    })))
+}
+
+pub async fn delete_user(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+    Query(params): Query<DeleteUserParams>,
+) -> Result<Json<DeleteUserResponse>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+    let actor = actor(&state, &auth_user, &headers, peer)?;
+
+    if user_id == actor.id {
+        return Err(AppError::BadRequest("You can't delete yourself".to_string()));
+    }
+
+    let user = UserAdminService::find(&mut conn, user_id)?;
+    let transferred = UserAdminService::delete_with_transfer(&mut conn, user.id, params.transfer_to)?;
+
+    let details = format!("transfer_to={}, documents={}", params.transfer_to, transferred.len());
+    UserAdminService::audit(&mut conn, &actor, AdminAuditLog::DELETE_USER, &user, Some(details))?;
+
+    // The search index still has the old owner
+    let documents = UserAdminService::load_documents(&mut conn, &transferred)?;
+    if !documents.is_empty() {
+        let search_service = SearchService::new(&state.config.meilisearch)?;
+        if let Err(e) = search_service.batch_index_documents(documents).await {
+            tracing::warn!("Failed to re-index documents transferred from user {}: {}", user.id, e);
+        }
+    }
+
+    Ok(Json(DeleteUserResponse {
+        transferred_documents: transferred.len(),
+    }))
+}
+
+/// Log in as a user to see what they see; the session is short, and starting it is audited
+pub async fn impersonate_user(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<ImpersonateRequest>,
+) -> Result<Json<LoginResponse>> {
+    auth_user.require_admin()?;
+
+    payload.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let mut conn = state.get_connection()?;
+    let actor = actor(&state, &auth_user, &headers, peer)?;
+
+    let user = UserAdminService::find(&mut conn, user_id)?;
+
+    if user.id == actor.id {
+        return Err(AppError::BadRequest("You can't impersonate yourself".to_string()));
+    }
+
+    // Admins answer for their own actions
+    if UserRole::from_str(&user.role) == Some(UserRole::Admin) {
+        return Err(AppError::Forbidden("Admins can't be impersonated".to_string()));
+    }
+
+    if !user.is_active {
+        return Err(AppError::BadRequest("Account is not active".to_string()));
+    }
+
+    UserAdminService::audit(&mut conn, &actor, AdminAuditLog::IMPERSONATE, &user, Some(payload.reason))?;
+
+    let response = SessionService::impersonate(
+        &mut conn,
+        &state.config.jwt,
+        actor.id,
+        user,
+        ClientInfo::from_request(&state.config.rate_limit, &headers, peer),
+    )?;
+
+    Ok(Json(response))
+}
+
+pub async fn list_admin_audit_logs(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(params): Query<AuditLogParams>,
+) -> Result<Json<Vec<AdminAuditLog>>> {
+    auth_user.require_admin()?;
+
+    let mut conn = state.get_connection()?;
+
+    let logs = UserAdminService::list_audit_logs(&mut conn, params.user_id, params.limit, params.offset)?;
+
+    Ok(Json(logs))
+}
+
+fn actor<'a>(
+    state: &AppState,
+    auth_user: &'a AuthUser,
+    headers: &HeaderMap,
+    peer: SocketAddr,
+) -> Result<AdminActor<'a>> {
+    Ok(AdminActor {
+        id: auth_user.claims.user_id()?,
+        username: &auth_user.claims.username,
+        ip_address: Some(RateLimiter::client_ip(&state.config.rate_limit, headers, peer)),
+    })
+}
@@ -22,7 +22,7 @@ use crate::{
     config::Config,
     db::{create_pool, AppState},
     routes::create_routes,
-    services::SearchService,
+    services::{LdapService, MetadataService, RetentionService, SearchService, ThumbnailService},
 };
 
 #[tokio::main]
@@ -59,11 +59,38 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Initializing search index...");
     let search_service = SearchService::new(&config.meilisearch)?;
     search_service.initialize_index().await?;
+    {
+        let mut conn = pool.get()?;
+        MetadataService::sync_search_attributes(&mut conn, &search_service).await?;
+    }
     tracing::info!("Search index initialized");
 
     // Create application state
     let state = AppState::new(pool, config.clone());
 
+    // Start the retention sweep in the background
+    tokio::spawn(RetentionService::run_scheduler(state.clone()));
+    tracing::info!(
+        "Retention sweep scheduled every {} seconds",
+        config.retention.sweep_interval_secs
+    );
+
+    // Generate thumbnails and previews in the background
+    tokio::spawn(ThumbnailService::run_scheduler(state.clone()));
+    tracing::info!(
+        "Thumbnail generation scheduled every {} seconds",
+        config.thumbnail.interval_secs
+    );
+
+    // Mirror LDAP users and groups in the background
+    if config.ldap.enabled && config.ldap.sync_interval_secs > 0 {
+        tokio::spawn(LdapService::run_scheduler(state.clone()));
+        tracing::info!(
+            "LDAP sync scheduled every {} seconds",
+            config.ldap.sync_interval_secs
+        );
+    }
+
     // Configure CORS
     let cors = CorsLayer::new()
         .allow_origin(Any)
@@ -71,7 +98,7 @@ async fn main() -> anyhow::Result<()> {
         .allow_headers(Any);
 
     // Create router
-    let app = create_routes()
+    let app = create_routes(state.clone())
         .layer(cors)
         .layer(TraceLayer::new_for_http())
         .with_state(state);
@@ -81,7 +108,8 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Server listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    // The peer address identifies clients for rate limiting
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
 
     Ok(())
 }
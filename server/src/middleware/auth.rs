@@ -1,20 +1,172 @@
+use std::collections::HashMap;
+
 use axum::{
     async_trait,
-    extract::{FromRequestParts, State},
-    http::{header, request::Parts, StatusCode},
+    extract::{FromRequestParts, MatchedPath, Path, State},
+    http::{header, request::Parts, Method, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde_json::json;
+use uuid::Uuid;
 
 use crate::{
-    db::AppState,
+    db::{AppState, DbConnection},
     error::AppError,
+    models::{api_token::ApiToken, permission::PermissionType, user::UserRole},
+    services::{ApiTokenService, PermissionService, SessionService},
     utils::jwt::{decode_jwt, Claims},
 };
 
+// 限定文件夹的令牌可以访问的、不含文档 ID 的路由，目标文件夹由处理函数检查
+const FOLDER_SCOPED_ROUTES: [(&str, Method); 3] = [
+    ("/api/documents", Method::GET),
+    ("/api/documents/upload", Method::POST),
+    ("/api/folders", Method::POST),
+];
+
 pub struct AuthUser {
+    /// For API tokens, `sid` is the token's ID
     pub claims: Claims,
+    /// Set when the request authenticated with an API token
+    pub api_token: Option<ApiToken>,
+    /// The admin acting as the user, for impersonated sessions
+    pub impersonator_id: Option<Uuid>,
+}
+
+impl AuthUser {
+    pub fn is_admin(&self) -> bool {
+        UserRole::from_str(&self.claims.role) == Some(UserRole::Admin)
+    }
+
+    pub fn require_admin(&self) -> Result<(), AppError> {
+        if self.is_admin() {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden("Admin privileges required".to_string()))
+        }
+    }
+
+    /// The caller's role; an unknown role gets the fewest privileges
+    pub fn role(&self) -> UserRole {
+        UserRole::from_str(&self.claims.role).unwrap_or(UserRole::Guest)
+    }
+
+    /// Check the caller may put a document or folder into a folder; `None` is
+    /// the top level. Every path that creates or moves documents goes through
+    /// here, so the role, token scope and folder permission are checked alike.
+    pub fn ensure_can_add_to(&self, conn: &mut DbConnection, folder_id: Option<Uuid>) -> Result<(), AppError> {
+        self.ensure_in_scope(conn, folder_id)?;
+
+        let Some(folder_id) = folder_id else {
+            if !self.role().can_create_at_root() {
+                return Err(AppError::Forbidden(
+                    "Guests can only add documents to folders shared with them".to_string(),
+                ));
+            }
+
+            return Ok(());
+        };
+
+        let user_id = self.claims.user_id()?;
+        if !PermissionService::check_permission(conn, user_id, folder_id, PermissionType::Write)? {
+            return Err(AppError::Forbidden("No permission to add documents to this folder".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Fail for impersonated sessions; an admin acting as a user mustn't
+    /// take over the account through its credentials or other sessions
+    pub fn ensure_not_impersonating(&self) -> Result<(), AppError> {
+        if self.impersonator_id.is_some() {
+            return Err(AppError::Forbidden(
+                "Not allowed while impersonating a user".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn ensure_can_create_share_links(&self) -> Result<(), AppError> {
+        if !self.role().can_create_share_links() {
+            return Err(AppError::Forbidden("Guests can't create share links".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Check that the API token of this request, if any, may touch a
+    /// document or folder; `None` is the top level
+    pub fn ensure_in_scope(&self, conn: &mut DbConnection, document_id: Option<Uuid>) -> Result<(), AppError> {
+        let Some(api_token) = &self.api_token else {
+            return Ok(());
+        };
+
+        if ApiTokenService::in_scope(conn, api_token, document_id)? {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden("This API token is limited to another folder".to_string()))
+        }
+    }
+
+    async fn from_api_token(parts: &mut Parts, state: &AppState, token: &str) -> Result<Self, AppError> {
+        let mut conn = state.get_connection()?;
+        let (api_token, user) = ApiTokenService::authenticate(&mut conn, token)?;
+
+        let path = parts
+            .extensions
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_default();
+
+        // Tokens are for automation, not for managing the account they belong to
+        let is_account_route = path.starts_with("/api/auth/") || path.starts_with("/api/tokens");
+        if is_account_route && !(path == "/api/auth/me" && parts.method == Method::GET) {
+            return Err(AppError::Forbidden("API tokens can't be used to manage the account".to_string()));
+        }
+
+        // The editor saves changes through OnlyOffice, whatever the request method
+        let writes = !matches!(parts.method, Method::GET | Method::HEAD) || path.starts_with("/api/onlyoffice/");
+        if api_token.is_read_only() && writes {
+            return Err(AppError::Forbidden("This API token is read-only".to_string()));
+        }
+
+        let auth_user = AuthUser {
+            claims: Claims {
+                sub: user.id.to_string(),
+                username: user.username,
+                role: user.role,
+                sid: api_token.id,
+                exp: api_token.expires_at.map_or(i64::MAX, |at| at.and_utc().timestamp()),
+                iat: api_token.created_at.and_utc().timestamp(),
+            },
+            api_token: Some(api_token),
+            impersonator_id: None,
+        };
+
+        if auth_user.api_token.as_ref().is_some_and(|t| t.folder_id.is_some()) {
+            let is_document_route = path.starts_with("/api/documents/:") || path.starts_with("/api/onlyoffice/:id");
+
+            if is_document_route {
+                let params = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+                    .await
+                    .map(|Path(params)| params)
+                    .unwrap_or_default();
+
+                let document_id = params
+                    .get("document_id")
+                    .or_else(|| params.get("id"))
+                    .and_then(|id| Uuid::parse_str(id).ok());
+
+                auth_user.ensure_in_scope(&mut conn, document_id)?;
+            } else if !FOLDER_SCOPED_ROUTES.iter().any(|(route, method)| path == *route && parts.method == method) {
+                return Err(AppError::Forbidden("This API token is limited to a folder".to_string()));
+            }
+        }
+
+        Ok(auth_user)
+    }
 }
 
 #[async_trait]
@@ -49,8 +201,15 @@ impl FromRequestParts<AppState> for AuthUser {
                     .into_response()
             })?;
 
+        if ApiTokenService::is_api_token(token) {
+            let token = token.to_string();
+            return Self::from_api_token(parts, state, &token)
+                .await
+                .map_err(IntoResponse::into_response);
+        }
+
         // Decode the JWT token
-        let claims = decode_jwt(token, &state.config.jwt.secret).map_err(|e| {
+        let mut claims = decode_jwt(token, &state.config.jwt.secret).map_err(|e| {
             let error_msg = format!("{}", e);
             (
                 StatusCode::UNAUTHORIZED,
@@ -59,7 +218,17 @@ impl FromRequestParts<AppState> for AuthUser {
                 .into_response()
         })?;
 
-        Ok(AuthUser { claims })
+        // Reject tokens of revoked sessions and deactivated users, and pick up
+        // role changes made since the token was issued
+        let user_id = claims.user_id().map_err(IntoResponse::into_response)?;
+        let mut conn = state
+            .get_connection()
+            .map_err(|e| AppError::from(e).into_response())?;
+        let (role, impersonator_id) = SessionService::validate(&mut conn, claims.sid, user_id)
+            .map_err(IntoResponse::into_response)?;
+        claims.role = role;
+
+        Ok(AuthUser { claims, api_token: None, impersonator_id })
     }
 }
 
@@ -0,0 +1,35 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{
+    db::AppState,
+    error::AppError,
+    services::{rate_limit::RouteGroup, RateLimiter},
+};
+
+/// Turn clients away with `429` once they go over their route group's limit
+pub async fn rate_limit(
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if let Some(group) = RouteGroup::for_path(request.uri().path()) {
+        let limit = group.limit(&state.config.rate_limit);
+
+        if limit > 0 {
+            let client = RateLimiter::client_ip(&state.config.rate_limit, request.headers(), peer);
+
+            state.rate_limiter.hit(group, &client, limit).map_err(|retry_after| {
+                AppError::TooManyRequests("Too many requests; slow down".to_string(), retry_after)
+            })?;
+        }
+    }
+
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,86 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// An admin action on a user account
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = crate::schema::admin_audit_logs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AdminAuditLog {
+    pub id: Uuid,
+    pub admin_id: Uuid,
+    pub admin_username: String,
+    pub action: String,
+    pub target_user_id: Uuid,
+    pub target_username: String,
+    pub details: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl AdminAuditLog {
+    pub const CREATE_USER: &'static str = "create_user";
+    pub const UPDATE_USER: &'static str = "update_user";
+    pub const SET_PASSWORD: &'static str = "set_password";
+    pub const DELETE_USER: &'static str = "delete_user";
+    pub const IMPERSONATE: &'static str = "impersonate";
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::admin_audit_logs)]
+pub struct NewAdminAuditLog {
+    pub admin_id: Uuid,
+    pub admin_username: String,
+    pub action: String,
+    pub target_user_id: Uuid,
+    pub target_username: String,
+    pub details: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct AdminCreateUserRequest {
+    #[validate(length(min = 3, max = 100))]
+    pub username: String,
+    #[validate(email)]
+    pub email: String,
+    /// Checked against the configured password policy
+    pub password: String,
+    pub full_name: Option<String>,
+    /// Defaults to user
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct AdminUpdateUserRequest {
+    #[validate(email)]
+    pub email: Option<String>,
+    pub full_name: Option<String>,
+    pub role: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AdminSetPasswordRequest {
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteUserParams {
+    /// Receives the user's documents and everything else they created
+    pub transfer_to: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ImpersonateRequest {
+    /// Why support needs to act as the user; kept in the audit log
+    #[validate(length(min = 1, max = 500))]
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteUserResponse {
+    pub transferred_documents: usize,
+}
@@ -0,0 +1,95 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::api_tokens)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ApiToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    /// First characters of the token, to tell tokens apart
+    pub token_prefix: String,
+    pub scope: String,
+    /// Folder the token is limited to, including everything below it
+    pub folder_id: Option<Uuid>,
+    pub expires_at: Option<NaiveDateTime>,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+impl ApiToken {
+    pub fn is_read_only(&self) -> bool {
+        ApiTokenScope::from_str(&self.scope) == Some(ApiTokenScope::Read)
+    }
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::api_tokens)]
+pub struct NewApiToken {
+    pub user_id: Uuid,
+    pub name: String,
+    pub token_hash: String,
+    pub token_prefix: String,
+    pub scope: String,
+    pub folder_id: Option<Uuid>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiTokenScope {
+    Read,
+    Write,
+}
+
+impl ApiTokenScope {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ApiTokenScope::Read => "read",
+            ApiTokenScope::Write => "write",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "read" => Some(ApiTokenScope::Read),
+            "write" => Some(ApiTokenScope::Write),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateApiTokenRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    /// Defaults to write
+    pub scope: Option<ApiTokenScope>,
+    pub folder_id: Option<Uuid>,
+    /// None for a token that doesn't expire
+    #[validate(range(min = 1, max = 3650))]
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateApiTokenResponse {
+    /// Only ever shown here
+    pub token: String,
+    #[serde(flatten)]
+    pub api_token: ApiToken,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateServiceAccountRequest {
+    #[validate(length(min = 3, max = 100))]
+    pub username: String,
+    #[validate(email)]
+    pub email: String,
+    pub full_name: Option<String>,
+}
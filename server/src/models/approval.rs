@@ -0,0 +1,121 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalMode {
+    Sequential,
+    Parallel,
+}
+
+impl ApprovalMode {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ApprovalMode::Sequential => "sequential",
+            ApprovalMode::Parallel => "parallel",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalDecision {
+    Approve,
+    Reject,
+}
+
+// 流程状态
+pub const WORKFLOW_PENDING: &str = "pending";
+pub const WORKFLOW_APPROVED: &str = "approved";
+pub const WORKFLOW_REJECTED: &str = "rejected";
+pub const WORKFLOW_CANCELLED: &str = "cancelled";
+
+// 步骤状态
+pub const STEP_PENDING: &str = "pending";
+pub const STEP_APPROVED: &str = "approved";
+pub const STEP_REJECTED: &str = "rejected";
+pub const STEP_SKIPPED: &str = "skipped";
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::approval_workflows)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ApprovalWorkflow {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    pub document_version: i32,
+    pub submitted_by: Uuid,
+    pub mode: String,
+    pub status: String,
+    pub comment: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub completed_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::approval_workflows)]
+pub struct NewApprovalWorkflow {
+    pub document_id: Uuid,
+    pub document_version: i32,
+    pub submitted_by: Uuid,
+    pub mode: String,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::approval_steps)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ApprovalStep {
+    pub id: Uuid,
+    pub workflow_id: Uuid,
+    pub step_order: i32,
+    pub reviewer_user_id: Option<Uuid>,
+    pub reviewer_group_id: Option<Uuid>,
+    pub status: String,
+    pub decided_by: Option<Uuid>,
+    pub decision_comment: Option<String>,
+    pub decided_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::approval_steps)]
+pub struct NewApprovalStep {
+    pub workflow_id: Uuid,
+    pub step_order: i32,
+    pub reviewer_user_id: Option<Uuid>,
+    pub reviewer_group_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReviewerRequest {
+    pub user_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubmitApprovalRequest {
+    pub mode: ApprovalMode,
+    pub reviewers: Vec<ReviewerRequest>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DecideApprovalRequest {
+    pub decision: ApprovalDecision,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApprovalWorkflowResponse {
+    #[serde(flatten)]
+    pub workflow: ApprovalWorkflow,
+    pub steps: Vec<ApprovalStep>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingReviewResponse {
+    pub step: ApprovalStep,
+    pub workflow: ApprovalWorkflow,
+    pub document_name: String,
+}
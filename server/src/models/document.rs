@@ -25,6 +25,27 @@ pub struct Document {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub deleted_at: Option<NaiveDateTime>,
+    pub locked_by: Option<Uuid>,
+    pub locked_at: Option<NaiveDateTime>,
+    pub lock_expires_at: Option<NaiveDateTime>,
+}
+
+impl Document {
+    /// The user currently holding an unexpired check-out lock, if any
+    pub fn active_lock_holder(&self) -> Option<Uuid> {
+        match (self.locked_by, self.lock_expires_at) {
+            (Some(holder), Some(expires_at)) if expires_at > chrono::Local::now().naive_local() => {
+                Some(holder)
+            }
+            (Some(holder), None) => Some(holder),
+            _ => None,
+        }
+    }
+
+    /// Whether the document is checked out by someone other than `user_id`
+    pub fn is_locked_by_other(&self, user_id: Uuid) -> bool {
+        matches!(self.active_lock_holder(), Some(holder) if holder != user_id)
+    }
 }
 
 #[derive(Debug, Deserialize, Insertable)]
@@ -57,6 +78,17 @@ pub struct UpdateDocumentRequest {
     pub name: Option<String>,
     pub description: Option<String>,
     pub tags: Option<Vec<String>>,
+    pub metadata: Option<JsonValue>,
+}
+
+/// Fields changed by `update_document`; `None` leaves a column untouched
+#[derive(Debug, AsChangeset)]
+#[diesel(table_name = crate::schema::documents)]
+pub struct DocumentChangeset {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<Option<String>>>,
+    pub metadata: Option<JsonValue>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -78,6 +110,12 @@ pub struct DocumentVersion {
     pub comment: Option<String>,
     pub created_by: Uuid,
     pub created_at: NaiveDateTime,
+    pub document_key: Option<String>,
+    pub is_forcesave: bool,
+    pub changes_path: Option<String>,
+    pub history: Option<JsonValue>,
+    /// Share link the version was saved through, for anonymous editors
+    pub share_link_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize, Insertable)]
@@ -89,6 +127,10 @@ pub struct NewDocumentVersion {
     pub file_size: i64,
     pub comment: Option<String>,
     pub created_by: Uuid,
+    pub document_key: Option<String>,
+    pub is_forcesave: bool,
+    pub history: Option<JsonValue>,
+    pub share_link_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -125,3 +167,9 @@ pub struct MoveDocumentRequest {
     pub target_folder_id: Option<Uuid>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckoutRequest {
+    /// 锁定时长（分钟），默认 8 小时
+    pub duration_minutes: Option<i64>,
+}
+
@@ -0,0 +1,41 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::editor_sessions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct EditorSession {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    /// None for an anonymous editor on a share link
+    pub user_id: Option<Uuid>,
+    pub document_key: String,
+    pub started_at: NaiveDateTime,
+    pub last_seen_at: NaiveDateTime,
+    pub ended_at: Option<NaiveDateTime>,
+    pub share_link_id: Option<Uuid>,
+    /// The editor's user ID in Document Server
+    pub editor_id: String,
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::editor_sessions)]
+pub struct NewEditorSession {
+    pub document_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub document_key: String,
+    pub share_link_id: Option<Uuid>,
+    pub editor_id: String,
+}
+
+/// A user, or an anonymous editor who came through a share link
+#[derive(Debug, Serialize, Deserialize, Queryable)]
+pub struct ActiveEditor {
+    pub user_id: Option<Uuid>,
+    pub username: Option<String>,
+    pub share_link_id: Option<Uuid>,
+    pub started_at: NaiveDateTime,
+    pub last_seen_at: NaiveDateTime,
+}
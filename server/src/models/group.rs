@@ -0,0 +1,4 @@
+/// Where a group membership came from; synced memberships are only ever
+/// changed by the sync that created them
+pub const GROUP_MEMBER_SOURCE_OIDC: &str = "oidc";
+pub const GROUP_MEMBER_SOURCE_LDAP: &str = "ldap";
@@ -0,0 +1,108 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::permission::PermissionType;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentStatus {
+    Draft,
+    InReview,
+    Approved,
+    Archived,
+    Obsolete,
+}
+
+impl DocumentStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            DocumentStatus::Draft => "draft",
+            DocumentStatus::InReview => "in_review",
+            DocumentStatus::Approved => "approved",
+            DocumentStatus::Archived => "archived",
+            DocumentStatus::Obsolete => "obsolete",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "draft" => Some(DocumentStatus::Draft),
+            "in_review" => Some(DocumentStatus::InReview),
+            "approved" => Some(DocumentStatus::Approved),
+            "archived" => Some(DocumentStatus::Archived),
+            "obsolete" => Some(DocumentStatus::Obsolete),
+            _ => None,
+        }
+    }
+
+    /// Content can only change while a document is a draft or under review
+    pub fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            DocumentStatus::Approved | DocumentStatus::Archived | DocumentStatus::Obsolete
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::document_status_transitions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct StatusTransition {
+    pub id: Uuid,
+    pub from_status: String,
+    pub to_status: String,
+    pub required_permission: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl StatusTransition {
+    pub fn permission_type(&self) -> Option<PermissionType> {
+        PermissionType::from_str(&self.required_permission)
+    }
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::document_status_transitions)]
+pub struct NewStatusTransition {
+    pub from_status: String,
+    pub to_status: String,
+    pub required_permission: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = crate::schema::document_status_history)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct StatusHistoryEntry {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    pub from_status: String,
+    pub to_status: String,
+    pub comment: Option<String>,
+    pub changed_by: Uuid,
+    pub changed_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::document_status_history)]
+pub struct NewStatusHistoryEntry {
+    pub document_id: Uuid,
+    pub from_status: String,
+    pub to_status: String,
+    pub comment: Option<String>,
+    pub changed_by: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeStatusRequest {
+    pub status: DocumentStatus,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTransitionRequest {
+    pub from_status: DocumentStatus,
+    pub to_status: DocumentStatus,
+    pub required_permission: PermissionType,
+}
@@ -0,0 +1,15 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+
+/// Consecutive failed logins for an account or a client address
+#[derive(Debug, Serialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = crate::schema::login_failures)]
+#[diesel(primary_key(key))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct LoginFailure {
+    pub key: String,
+    pub failure_count: i32,
+    pub last_failure_at: NaiveDateTime,
+    pub locked_until: Option<NaiveDateTime>,
+}
@@ -0,0 +1,105 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataFieldType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Date,
+    Enum,
+}
+
+/// 元数据字段定义，存储在 metadata_schemas.fields 中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataField {
+    pub name: String,
+    pub label: Option<String>,
+    #[serde(rename = "type")]
+    pub field_type: MetadataFieldType,
+    #[serde(default)]
+    pub required: bool,
+    /// Allowed values for `enum` fields
+    pub options: Option<Vec<String>>,
+    #[serde(default = "default_true")]
+    pub filterable: bool,
+    #[serde(default)]
+    pub sortable: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::metadata_schemas)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MetadataSchema {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub fields: JsonValue,
+    pub created_by: Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl MetadataSchema {
+    pub fn field_definitions(&self) -> Vec<MetadataField> {
+        serde_json::from_value(self.fields.clone()).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::metadata_schemas)]
+pub struct NewMetadataSchema {
+    pub name: String,
+    pub description: Option<String>,
+    pub fields: JsonValue,
+    pub created_by: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateMetadataSchemaRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    pub description: Option<String>,
+    pub fields: Vec<MetadataField>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateMetadataSchemaRequest {
+    pub description: Option<String>,
+    pub fields: Option<Vec<MetadataField>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::metadata_schema_bindings)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MetadataSchemaBinding {
+    pub id: Uuid,
+    pub schema_id: Uuid,
+    pub folder_id: Option<Uuid>,
+    pub mime_type: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::metadata_schema_bindings)]
+pub struct NewMetadataSchemaBinding {
+    pub schema_id: Uuid,
+    pub folder_id: Option<Uuid>,
+    pub mime_type: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BindMetadataSchemaRequest {
+    pub folder_id: Option<Uuid>,
+    pub mime_type: Option<String>,
+}
@@ -1,6 +1,24 @@
 pub mod user;
 pub mod document;
 pub mod permission;
+pub mod quota;
+pub mod lifecycle;
+pub mod approval;
+pub mod retention;
+pub mod metadata;
+pub mod tag;
+pub mod thumbnail;
+pub mod rendition;
+pub mod editor_session;
+pub mod template;
+pub mod session;
+pub mod totp;
+pub mod oidc;
+pub mod group;
+pub mod api_token;
+pub mod admin;
+pub mod registration;
+pub mod login_failure;
 
 pub use user::{User, NewUser, UserRole};
 pub use document::{Document, NewDocument, DocumentVersion};
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OidcAuthorizeResponse {
+    /// Identity provider page to send the browser to
+    pub authorization_url: String,
+    /// Kept by the browser that started the login and sent back with the callback
+    pub login_request_id: String,
+}
+
+/// Query parameters the identity provider appended to the redirect URL, and
+/// the login request they belong to
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OidcCallbackRequest {
+    pub code: String,
+    pub state: String,
+    /// From the authorize response of this browser
+    pub login_request_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuthMethodsResponse {
+    pub password_login: bool,
+    pub oidc: bool,
+    /// Registration mode: open, invite, domain or disabled
+    pub registration: String,
+}
@@ -3,11 +3,20 @@ use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::error::{AppError, Result};
+use crate::utils::verify_password;
+
 // PermissionType 枚举仅用于应用层类型安全，不直接映射到数据库
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
 pub enum PermissionType {
     Read,
+    /// Comment in the editor without changing the content
+    Comment,
+    /// Edit in the editor with track changes forced on
+    Review,
+    /// Fill in the fields of a PDF form
+    FillForms,
     Write,
     Delete,
     Share,
@@ -15,9 +24,23 @@ pub enum PermissionType {
 }
 
 impl PermissionType {
+    pub const ALL: [PermissionType; 8] = [
+        PermissionType::Read,
+        PermissionType::Comment,
+        PermissionType::Review,
+        PermissionType::FillForms,
+        PermissionType::Write,
+        PermissionType::Delete,
+        PermissionType::Share,
+        PermissionType::Admin,
+    ];
+
     pub fn as_str(&self) -> &str {
         match self {
             PermissionType::Read => "read",
+            PermissionType::Comment => "comment",
+            PermissionType::Review => "review",
+            PermissionType::FillForms => "fill_forms",
             PermissionType::Write => "write",
             PermissionType::Delete => "delete",
             PermissionType::Share => "share",
@@ -28,6 +51,9 @@ impl PermissionType {
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "read" => Some(PermissionType::Read),
+            "comment" => Some(PermissionType::Comment),
+            "review" => Some(PermissionType::Review),
+            "fill_forms" => Some(PermissionType::FillForms),
             "write" => Some(PermissionType::Write),
             "delete" => Some(PermissionType::Delete),
             "share" => Some(PermissionType::Share),
@@ -115,6 +141,46 @@ impl ShareLink {
     pub fn permission_type(&self) -> Option<PermissionType> {
         PermissionType::from_str(&self.permission)
     }
+
+    /// Fail if the link has expired or used up its accesses
+    pub fn ensure_usable(&self) -> Result<()> {
+        self.ensure_not_expired()?;
+
+        if let Some(max_count) = self.max_access_count {
+            if self.access_count >= max_count {
+                return Err(AppError::BadRequest("Share link access limit reached".to_string()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fail if the link has expired
+    pub fn ensure_not_expired(&self) -> Result<()> {
+        if let Some(expires_at) = self.expires_at {
+            if expires_at < chrono::Local::now().naive_local() {
+                return Err(AppError::BadRequest("Share link has expired".to_string()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fail unless `password` matches the link's password, if it has one
+    pub fn check_password(&self, password: Option<&str>) -> Result<()> {
+        let Some(hash) = &self.password_hash else {
+            return Ok(());
+        };
+
+        let password = password
+            .ok_or_else(|| AppError::Unauthorized("Share link requires a password".to_string()))?;
+
+        if !verify_password(password, hash)? {
+            return Err(AppError::Unauthorized("Invalid share link password".to_string()));
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Deserialize, Insertable)]
@@ -136,6 +202,13 @@ pub struct GrantPermissionRequest {
     pub expires_at: Option<NaiveDateTime>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ShareEditorQuery {
+    pub password: Option<String>,
+    /// Name shown to other editors; defaults to "Guest"
+    pub name: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateShareLinkRequest {
     pub permission: PermissionType,
@@ -0,0 +1,73 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Text, Uuid as SqlUuid};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::storage_quotas)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct StorageQuota {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub max_bytes: i64,
+    pub created_by: Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::storage_quotas)]
+pub struct NewStorageQuota {
+    pub user_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub max_bytes: i64,
+    pub created_by: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetQuotaRequest {
+    pub user_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub max_bytes: i64,
+}
+
+/// 单个用户的存储用量（字节）
+#[derive(Debug, Serialize, Deserialize, QueryableByName)]
+pub struct UserUsageRow {
+    #[diesel(sql_type = SqlUuid)]
+    pub user_id: Uuid,
+    #[diesel(sql_type = Text)]
+    pub username: String,
+    #[diesel(sql_type = BigInt)]
+    pub document_bytes: i64,
+    #[diesel(sql_type = BigInt)]
+    pub version_bytes: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsageResponse {
+    pub user_id: Uuid,
+    pub username: String,
+    pub document_bytes: i64,
+    pub version_bytes: i64,
+    pub used_bytes: i64,
+    pub quota_bytes: Option<i64>,
+    pub remaining_bytes: Option<i64>,
+}
+
+impl UsageResponse {
+    pub fn new(row: UserUsageRow, quota_bytes: Option<i64>) -> Self {
+        let used_bytes = row.document_bytes + row.version_bytes;
+        UsageResponse {
+            user_id: row.user_id,
+            username: row.username,
+            document_bytes: row.document_bytes,
+            version_bytes: row.version_bytes,
+            used_bytes,
+            quota_bytes,
+            remaining_bytes: quota_bytes.map(|q| (q - used_bytes).max(0)),
+        }
+    }
+}
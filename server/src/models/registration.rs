@@ -0,0 +1,110 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Who may sign up through `POST /api/auth/register`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationMode {
+    Open,
+    /// Only with an invitation from an admin
+    Invite,
+    /// Only with an email address in an allowed domain, or an invitation
+    Domain,
+    Disabled,
+}
+
+impl RegistrationMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RegistrationMode::Open => "open",
+            RegistrationMode::Invite => "invite",
+            RegistrationMode::Domain => "domain",
+            RegistrationMode::Disabled => "disabled",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "open" => Some(RegistrationMode::Open),
+            "invite" => Some(RegistrationMode::Invite),
+            "domain" => Some(RegistrationMode::Domain),
+            "disabled" => Some(RegistrationMode::Disabled),
+            _ => None,
+        }
+    }
+}
+
+/// An admin's invitation to sign up; the token is only ever in the link
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = crate::schema::invitations)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Invitation {
+    pub id: Uuid,
+    pub email: String,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub role: String,
+    pub invited_by: Uuid,
+    pub expires_at: NaiveDateTime,
+    pub accepted_at: Option<NaiveDateTime>,
+    pub accepted_user_id: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::schema::invitations)]
+pub struct NewInvitation {
+    pub email: String,
+    pub token_hash: String,
+    pub role: String,
+    pub invited_by: Uuid,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateInvitationRequest {
+    #[validate(email)]
+    pub email: String,
+    /// user or guest; defaults to user
+    pub role: Option<String>,
+    /// Groups the new user joins on sign-up
+    #[serde(default)]
+    pub group_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InvitationResponse {
+    #[serde(flatten)]
+    pub invitation: Invitation,
+    pub group_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateInvitationResponse {
+    /// Sign-up link with the token; shown only once
+    pub invitation_url: String,
+    #[serde(flatten)]
+    pub invitation: InvitationResponse,
+}
+
+/// What the sign-up page shows for an invitation link
+#[derive(Debug, Serialize)]
+pub struct InvitationInfo {
+    pub email: String,
+    pub role: String,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ResendVerificationRequest {
+    #[validate(email)]
+    pub email: String,
+}
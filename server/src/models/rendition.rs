@@ -0,0 +1,43 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::document_renditions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DocumentRendition {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    pub version: i32,
+    pub format: String,
+    pub file_path: String,
+    pub file_size: i64,
+    pub created_by: Uuid,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::document_renditions)]
+pub struct NewDocumentRendition {
+    pub document_id: Uuid,
+    pub version: i32,
+    pub format: String,
+    pub file_path: String,
+    pub file_size: i64,
+    pub created_by: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateRenditionRequest {
+    pub format: String,
+}
+
+/// Convert a document and save the result as a new document
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConvertDocumentRequest {
+    pub output_type: String,
+    pub name: Option<String>,
+    /// Defaults to the source document's folder
+    pub parent_folder_id: Option<Uuid>,
+}
@@ -0,0 +1,134 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::retention_policies)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RetentionPolicy {
+    pub id: Uuid,
+    pub name: String,
+    pub folder_id: Option<Uuid>,
+    pub tag: Option<String>,
+    pub mime_type: Option<String>,
+    pub retention_days: i32,
+    pub is_active: bool,
+    pub created_by: Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl RetentionPolicy {
+    /// Whether `mime_type` matches the policy's pattern (`image/*` style wildcards allowed)
+    pub fn matches_mime_type(&self, mime_type: &str) -> bool {
+        match &self.mime_type {
+            Some(pattern) => match pattern.strip_suffix("/*") {
+                Some(prefix) => mime_type
+                    .split('/')
+                    .next()
+                    .map(|t| t.eq_ignore_ascii_case(prefix))
+                    .unwrap_or(false),
+                None => pattern.eq_ignore_ascii_case(mime_type),
+            },
+            None => true,
+        }
+    }
+
+    /// Whether the document carries the policy's tag (case-insensitive)
+    pub fn matches_tags(&self, tags: &[String]) -> bool {
+        match &self.tag {
+            Some(tag) => tags.iter().any(|t| t.trim().eq_ignore_ascii_case(tag.trim())),
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::retention_policies)]
+pub struct NewRetentionPolicy {
+    pub name: String,
+    pub folder_id: Option<Uuid>,
+    pub tag: Option<String>,
+    pub mime_type: Option<String>,
+    pub retention_days: i32,
+    pub created_by: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateRetentionPolicyRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    pub folder_id: Option<Uuid>,
+    #[validate(length(min = 1, max = 100))]
+    pub tag: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    pub mime_type: Option<String>,
+    #[validate(range(min = 1, max = 36500))]
+    pub retention_days: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateRetentionPolicyRequest {
+    pub retention_days: Option<i32>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::legal_holds)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct LegalHold {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    pub reason: String,
+    pub created_by: Uuid,
+    pub created_at: NaiveDateTime,
+    pub released_by: Option<Uuid>,
+    pub released_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::legal_holds)]
+pub struct NewLegalHold {
+    pub document_id: Uuid,
+    pub reason: String,
+    pub created_by: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateLegalHoldRequest {
+    #[validate(length(min = 1))]
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = crate::schema::retention_audit_logs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RetentionAuditLog {
+    pub id: Uuid,
+    pub policy_id: Option<Uuid>,
+    pub document_id: Uuid,
+    pub document_name: String,
+    pub action: String,
+    pub details: Option<String>,
+    pub executed_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::retention_audit_logs)]
+pub struct NewRetentionAuditLog {
+    pub policy_id: Option<Uuid>,
+    pub document_id: Uuid,
+    pub document_name: String,
+    pub action: String,
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RetentionStatusResponse {
+    pub document_id: Uuid,
+    pub retained_until: Option<NaiveDateTime>,
+    pub policy_ids: Vec<Uuid>,
+    pub legal_holds: Vec<LegalHold>,
+}
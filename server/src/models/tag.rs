@@ -0,0 +1,69 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Text};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::tags)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Tag {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::tags)]
+pub struct NewTag {
+    pub name: String,
+    pub description: Option<String>,
+    pub created_by: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateTagRequest {
+    #[validate(length(min = 1, max = 50))]
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenameTagRequest {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeTagsRequest {
+    pub sources: Vec<String>,
+    pub target: String,
+}
+
+/// 标签使用次数（按规范化后的名称统计）
+#[derive(Debug, QueryableByName)]
+pub struct TagUsageRow {
+    #[diesel(sql_type = Text)]
+    pub name: String,
+    #[diesel(sql_type = BigInt)]
+    pub document_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagSummary {
+    pub name: String,
+    pub document_count: i64,
+    /// Whether the tag is part of the admin-defined vocabulary
+    pub defined: bool,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagRewriteResponse {
+    pub updated_documents: usize,
+    /// Documents left unchanged because they are on legal hold, read-only or retained
+    pub skipped_documents: Vec<Uuid>,
+}
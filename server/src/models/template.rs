@@ -0,0 +1,50 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub const TEMPLATE_KINDS: [&str; 3] = ["document", "spreadsheet", "presentation"];
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::document_templates)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DocumentTemplate {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: String,
+    pub file_name: String,
+    pub file_path: String,
+    pub file_size: i64,
+    pub mime_type: String,
+    /// Department (group) the template is offered to; None for everyone
+    pub group_id: Option<Uuid>,
+    pub created_by: Uuid,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::document_templates)]
+pub struct NewDocumentTemplate {
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: String,
+    pub file_name: String,
+    pub file_path: String,
+    pub file_size: i64,
+    pub mime_type: String,
+    pub group_id: Option<Uuid>,
+    pub created_by: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TemplateListParams {
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateFromTemplateRequest {
+    /// Defaults to the template name; the template's extension is added if missing
+    pub name: Option<String>,
+    pub parent_folder_id: Option<Uuid>,
+}
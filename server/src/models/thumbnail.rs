@@ -0,0 +1,33 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub const THUMBNAIL_READY: &str = "ready";
+pub const THUMBNAIL_FAILED: &str = "failed";
+pub const THUMBNAIL_UNSUPPORTED: &str = "unsupported";
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::document_thumbnails)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DocumentThumbnail {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    pub version: i32,
+    pub status: String,
+    pub file_path: Option<String>,
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::document_thumbnails)]
+#[diesel(treat_none_as_null = true)]
+pub struct NewDocumentThumbnail {
+    pub document_id: Uuid,
+    pub version: i32,
+    pub status: String,
+    pub file_path: Option<String>,
+    pub error: Option<String>,
+}
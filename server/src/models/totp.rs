@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TotpChallengeResponse {
+    pub totp_required: bool,
+    pub challenge_token: String,
+    /// Seconds until `challenge_token` expires
+    pub expires_in: i64,
+    /// The account must set up an authenticator before it can log in
+    pub enrollment_required: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TotpChallengeRequest {
+    pub challenge_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TotpLoginRequest {
+    pub challenge_token: String,
+    pub code: Option<String>,
+    pub recovery_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TotpSetupResponse {
+    /// Base32 secret, for authenticators that can't scan the URI
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EnableTotpRequest {
+    pub code: String,
+}
+
+/// Second factor: a code from the authenticator or an unused recovery code
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TotpCodeRequest {
+    pub code: Option<String>,
+    pub recovery_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DisableTotpRequest {
+    pub password: String,
+    pub code: Option<String>,
+    pub recovery_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TotpStatusResponse {
+    pub enabled: bool,
+    pub required: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetTotpRequiredRequest {
+    pub required: bool,
+}
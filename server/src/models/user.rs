@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::models::permission::PermissionType;
+use crate::models::totp::TotpChallengeResponse;
+
 #[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
 #[diesel(table_name = crate::schema::users)]
 pub struct User {
@@ -17,6 +20,22 @@ pub struct User {
     pub is_active: bool,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    #[serde(skip_serializing)]
+    pub totp_secret: Option<String>,
+    pub totp_enabled: bool,
+    pub totp_required: bool,
+    #[serde(skip_serializing)]
+    pub totp_last_step: Option<i64>,
+    /// Subject of the linked OpenID Connect identity
+    #[serde(skip_serializing)]
+    pub oidc_subject: Option<String>,
+    /// DN of the directory entry; set for accounts managed by LDAP
+    #[serde(skip_serializing)]
+    pub ldap_dn: Option<String>,
+    /// Only authenticates with API tokens
+    pub is_service_account: bool,
+    /// When the user confirmed their email address
+    pub email_verified_at: Option<NaiveDateTime>,
 }
 
 #[derive(Debug, Deserialize, Insertable, Validate)]
@@ -37,9 +56,11 @@ pub struct CreateUserRequest {
     pub username: String,
     #[validate(email)]
     pub email: String,
-    #[validate(length(min = 6))]
+    /// Checked against the configured password policy
     pub password: String,
     pub full_name: Option<String>,
+    /// Token from an invitation link
+    pub invitation_token: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -53,7 +74,22 @@ pub struct LoginRequest {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LoginResponse {
     pub token: String,
+    pub refresh_token: String,
+    /// Seconds until `token` expires
+    pub expires_in: i64,
     pub user: UserResponse,
+    /// Only set when two-factor setup was completed as part of this login
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery_codes: Option<Vec<String>>,
+}
+
+/// Result of the password step of a login
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum LoginOutcome {
+    Authenticated(LoginResponse),
+    /// The user still has to pass two-factor authentication
+    TotpRequired(TotpChallengeResponse),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -64,6 +100,10 @@ pub struct UserResponse {
     pub full_name: Option<String>,
     pub role: String,
     pub is_active: bool,
+    pub totp_enabled: bool,
+    pub totp_required: bool,
+    pub is_service_account: bool,
+    pub email_verified_at: Option<NaiveDateTime>,
     pub created_at: NaiveDateTime,
 }
 
@@ -76,6 +116,10 @@ impl From<User> for UserResponse {
             full_name: user.full_name,
             role: user.role,
             is_active: user.is_active,
+            totp_enabled: user.totp_enabled,
+            totp_required: user.totp_required,
+            is_service_account: user.is_service_account,
+            email_verified_at: user.email_verified_at,
             created_at: user.created_at,
         }
     }
@@ -106,5 +150,45 @@ impl UserRole {
             _ => None,
         }
     }
+
+    /// Permissions the role holds on every document, without a grant
+    pub fn global_permissions(&self) -> &'static [PermissionType] {
+        match self {
+            UserRole::Admin => &[PermissionType::Read, PermissionType::Admin],
+            UserRole::User | UserRole::Guest => &[],
+        }
+    }
+
+    /// Guests only add documents to folders shared with them
+    pub fn can_create_at_root(&self) -> bool {
+        *self != UserRole::Guest
+    }
+
+    pub fn can_create_share_links(&self) -> bool {
+        *self != UserRole::Guest
+    }
+
+    /// Whether search covers every document rather than only readable ones
+    pub fn can_search_all(&self) -> bool {
+        *self != UserRole::Guest
+    }
+}
+
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ForgotPasswordRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
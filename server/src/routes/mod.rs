@@ -1,18 +1,45 @@
 use axum::{
+    middleware::from_fn_with_state,
     routing::{delete, get, post, put},
     Router,
 };
 
-use crate::{db::AppState, handlers};
+use crate::{db::AppState, handlers, middleware::rate_limit};
 
-pub fn create_routes() -> Router<AppState> {
+pub fn create_routes(state: AppState) -> Router<AppState> {
     Router::new()
         // Health check
         .route("/health", get(health_check))
         // Auth routes
         .route("/api/auth/register", post(handlers::register))
         .route("/api/auth/login", post(handlers::login))
+        .route("/api/auth/login/totp", post(handlers::verify_totp_login))
+        .route("/api/auth/login/totp/setup", post(handlers::setup_totp_login))
+        .route("/api/auth/methods", get(handlers::get_auth_methods))
+        .route("/api/auth/oidc/authorize", get(handlers::oidc_authorize))
+        .route("/api/auth/oidc/callback", post(handlers::oidc_callback))
         .route("/api/auth/me", get(handlers::get_current_user))
+        .route("/api/auth/refresh", post(handlers::refresh_token))
+        .route("/api/auth/logout", post(handlers::logout))
+        .route("/api/auth/logout-all", post(handlers::logout_all))
+        .route("/api/auth/sessions", get(handlers::list_sessions))
+        .route("/api/auth/sessions/:id", delete(handlers::revoke_session))
+        .route("/api/auth/password/change", post(handlers::change_password))
+        .route("/api/auth/password/forgot", post(handlers::forgot_password))
+        .route("/api/auth/password/reset", post(handlers::reset_password))
+        .route("/api/auth/verify-email", post(handlers::verify_email))
+        .route("/api/auth/verify-email/resend", post(handlers::resend_verification_email))
+        .route("/api/auth/invitations/:token", get(handlers::get_invitation))
+        .route("/api/auth/totp", get(handlers::get_totp_status))
+        .route("/api/auth/totp/setup", post(handlers::setup_totp))
+        .route("/api/auth/totp/enable", post(handlers::enable_totp))
+        .route("/api/auth/totp/disable", post(handlers::disable_totp))
+        .route("/api/auth/totp/recovery-codes", post(handlers::regenerate_recovery_codes))
+        .route("/api/me/usage", get(handlers::get_my_usage))
+        // API token routes
+        .route("/api/tokens", get(handlers::list_api_tokens))
+        .route("/api/tokens", post(handlers::create_api_token))
+        .route("/api/tokens/:id", delete(handlers::revoke_api_token))
         // Document routes
         .route("/api/documents", get(handlers::list_documents))
         .route("/api/documents/upload", post(handlers::upload_document))
@@ -21,6 +48,29 @@ pub fn create_routes() -> Router<AppState> {
         .route("/api/documents/:id", delete(handlers::delete_document))
         .route("/api/documents/:id/download", get(handlers::download_document))
         .route("/api/documents/:id/move", post(handlers::move_document))
+        .route("/api/documents/:id/checkout", post(handlers::checkout_document))
+        .route("/api/documents/:id/checkout", delete(handlers::cancel_checkout))
+        .route("/api/documents/:id/checkin", post(handlers::checkin_document))
+        .route("/api/documents/:id/status", post(handlers::change_document_status))
+        .route("/api/documents/:id/status/history", get(handlers::get_status_history))
+        .route("/api/documents/:id/approvals", post(handlers::submit_for_approval))
+        .route("/api/documents/:id/approvals", get(handlers::list_document_approvals))
+        .route("/api/documents/:id/versions/:version", delete(handlers::purge_document_version))
+        .route("/api/documents/:id/retention", get(handlers::get_retention_status))
+        .route("/api/documents/:id/legal-holds", post(handlers::place_legal_hold))
+        .route("/api/documents/:id/metadata-schema", get(handlers::get_document_metadata_schema))
+        .route("/api/documents/:id/thumbnail", get(handlers::get_document_thumbnail))
+        .route("/api/documents/:id/renditions", post(handlers::create_rendition))
+        .route("/api/documents/:id/renditions", get(handlers::list_renditions))
+        .route("/api/documents/:id/renditions/:format", get(handlers::download_rendition))
+        .route("/api/documents/:id/convert", post(handlers::convert_document))
+        .route("/api/documents/:id/editors", get(handlers::get_document_editors))
+        .route("/api/metadata/schemas", get(handlers::list_metadata_schemas))
+        .route("/api/tags", get(handlers::list_tags))
+        // Approval routes
+        .route("/api/approvals/pending", get(handlers::list_pending_reviews))
+        .route("/api/approvals/steps/:step_id/decision", post(handlers::decide_approval_step))
+        .route("/api/approvals/:id", delete(handlers::cancel_approval))
         // Folder routes
         .route("/api/folders", post(handlers::create_folder))
         // Permission routes
@@ -35,11 +85,71 @@ pub fn create_routes() -> Router<AppState> {
         .route("/api/documents/:id/shares", get(handlers::list_share_links))
         .route("/api/documents/:document_id/shares/:share_id", delete(handlers::delete_share_link))
         .route("/api/shares/access/:token", get(handlers::get_share_link))
+        .route("/api/shares/access/:token/editor", get(handlers::get_share_editor_config))
         // Search routes
         .route("/api/search", get(handlers::search_documents))
         // OnlyOffice routes
         .route("/api/onlyoffice/:id/config", get(handlers::get_editor_config))
         .route("/api/onlyoffice/callback/:id", post(handlers::onlyoffice_callback))
+        .route("/api/onlyoffice/:id/forcesave", post(handlers::forcesave_document))
+        .route("/api/onlyoffice/:id/history", get(handlers::get_editor_history))
+        .route("/api/onlyoffice/:id/history/:version", get(handlers::get_editor_history_data))
+        // Template routes
+        .route("/api/templates", get(handlers::list_templates))
+        .route("/api/templates/:id/documents", post(handlers::create_document_from_template))
+        .route("/api/admin/templates", get(handlers::list_all_templates))
+        .route("/api/admin/templates", post(handlers::create_template))
+        .route("/api/admin/templates/:id", delete(handlers::delete_template))
+        // Admin routes
+        .route("/api/admin/usage", get(handlers::get_usage_report))
+        .route("/api/admin/users", get(handlers::list_users))
+        .route("/api/admin/users", post(handlers::create_user))
+        .route("/api/admin/users/:id", get(handlers::get_user))
+        .route("/api/admin/users/:id", put(handlers::update_user))
+        .route("/api/admin/users/:id", delete(handlers::delete_user))
+        .route("/api/admin/users/:id/password", post(handlers::set_user_password))
+        .route("/api/admin/users/:id/impersonate", post(handlers::impersonate_user))
+        .route("/api/admin/audit-logs", get(handlers::list_admin_audit_logs))
+        .route("/api/admin/users/:id/totp", put(handlers::set_user_totp_required))
+        .route("/api/admin/users/:id/totp", delete(handlers::reset_user_totp))
+        .route("/api/admin/ldap/sync", post(handlers::sync_ldap_directory))
+        .route("/api/admin/invitations", get(handlers::list_invitations))
+        .route("/api/admin/invitations", post(handlers::create_invitation))
+        .route("/api/admin/invitations/:id", delete(handlers::revoke_invitation))
+        .route("/api/admin/service-accounts", get(handlers::list_service_accounts))
+        .route("/api/admin/service-accounts", post(handlers::create_service_account))
+        .route("/api/admin/service-accounts/:id/tokens", get(handlers::list_service_account_tokens))
+        .route("/api/admin/service-accounts/:id/tokens", post(handlers::create_service_account_token))
+        .route(
+            "/api/admin/service-accounts/:id/tokens/:token_id",
+            delete(handlers::revoke_service_account_token),
+        )
+        .route("/api/admin/quotas", get(handlers::list_quotas))
+        .route("/api/admin/quotas", put(handlers::set_quota))
+        .route("/api/admin/quotas/:id", delete(handlers::delete_quota))
+        .route("/api/lifecycle/transitions", get(handlers::list_status_transitions))
+        .route("/api/admin/lifecycle/transitions", post(handlers::create_status_transition))
+        .route("/api/admin/lifecycle/transitions/:id", delete(handlers::delete_status_transition))
+        .route("/api/admin/retention/policies", get(handlers::list_retention_policies))
+        .route("/api/admin/retention/policies", post(handlers::create_retention_policy))
+        .route("/api/admin/retention/policies/:id", put(handlers::update_retention_policy))
+        .route("/api/admin/retention/policies/:id", delete(handlers::delete_retention_policy))
+        .route("/api/admin/retention/audit", get(handlers::list_retention_audit_logs))
+        .route("/api/admin/legal-holds", get(handlers::list_legal_holds))
+        .route("/api/admin/legal-holds/:id", delete(handlers::release_legal_hold))
+        .route("/api/admin/metadata/schemas", post(handlers::create_metadata_schema))
+        .route("/api/admin/metadata/schemas/:id", put(handlers::update_metadata_schema))
+        .route("/api/admin/metadata/schemas/:id", delete(handlers::delete_metadata_schema))
+        .route("/api/admin/metadata/schemas/:id/bindings", get(handlers::list_metadata_schema_bindings))
+        .route("/api/admin/metadata/schemas/:id/bindings", post(handlers::bind_metadata_schema))
+        .route("/api/admin/metadata/bindings/:id", delete(handlers::delete_metadata_schema_binding))
+        .route("/api/admin/tags", post(handlers::create_tag))
+        .route("/api/admin/tags/:id", delete(handlers::delete_tag))
+        .route("/api/admin/tags/rename", post(handlers::rename_tag))
+        .route("/api/admin/tags/merge", post(handlers::merge_tags))
+        .route("/api/admin/tags/normalize", post(handlers::normalize_tags))
+        // Limits are per route group, see RouteGroup
+        .layer(from_fn_with_state(state, rate_limit))
 }
 
 async fn health_check() -> &'static str {
@@ -1,5 +1,103 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    admin_audit_logs (id) {
+        id -> Uuid,
+        admin_id -> Uuid,
+        admin_username -> Varchar,
+        action -> Varchar,
+        target_user_id -> Uuid,
+        target_username -> Varchar,
+        details -> Nullable<Text>,
+        ip_address -> Nullable<Varchar>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    api_tokens (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        name -> Varchar,
+        token_hash -> Varchar,
+        token_prefix -> Varchar,
+        scope -> Varchar,
+        folder_id -> Nullable<Uuid>,
+        expires_at -> Nullable<Timestamp>,
+        last_used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    login_failures (key) {
+        key -> Varchar,
+        failure_count -> Int4,
+        last_failure_at -> Timestamp,
+        locked_until -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    email_verification_tokens (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        token_hash -> Varchar,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    invitation_groups (invitation_id, group_id) {
+        invitation_id -> Uuid,
+        group_id -> Uuid,
+    }
+}
+
+diesel::table! {
+    invitations (id) {
+        id -> Uuid,
+        email -> Varchar,
+        token_hash -> Varchar,
+        role -> Varchar,
+        invited_by -> Uuid,
+        expires_at -> Timestamp,
+        accepted_at -> Nullable<Timestamp>,
+        accepted_user_id -> Nullable<Uuid>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    approval_steps (id) {
+        id -> Uuid,
+        workflow_id -> Uuid,
+        step_order -> Int4,
+        reviewer_user_id -> Nullable<Uuid>,
+        reviewer_group_id -> Nullable<Uuid>,
+        status -> Varchar,
+        decided_by -> Nullable<Uuid>,
+        decision_comment -> Nullable<Text>,
+        decided_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    approval_workflows (id) {
+        id -> Uuid,
+        document_id -> Uuid,
+        document_version -> Int4,
+        submitted_by -> Uuid,
+        mode -> Varchar,
+        status -> Varchar,
+        comment -> Nullable<Text>,
+        created_at -> Timestamp,
+        completed_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     access_logs (id) {
         id -> Uuid,
@@ -24,6 +122,70 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    document_renditions (id) {
+        id -> Uuid,
+        document_id -> Uuid,
+        version -> Int4,
+        format -> Varchar,
+        file_path -> Varchar,
+        file_size -> Int8,
+        created_by -> Uuid,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    document_status_history (id) {
+        id -> Uuid,
+        document_id -> Uuid,
+        from_status -> Varchar,
+        to_status -> Varchar,
+        comment -> Nullable<Text>,
+        changed_by -> Uuid,
+        changed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    document_status_transitions (id) {
+        id -> Uuid,
+        from_status -> Varchar,
+        to_status -> Varchar,
+        required_permission -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    document_templates (id) {
+        id -> Uuid,
+        name -> Varchar,
+        description -> Nullable<Text>,
+        kind -> Varchar,
+        file_name -> Varchar,
+        file_path -> Varchar,
+        file_size -> Int8,
+        mime_type -> Varchar,
+        group_id -> Nullable<Uuid>,
+        created_by -> Uuid,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    document_thumbnails (id) {
+        id -> Uuid,
+        document_id -> Uuid,
+        version -> Int4,
+        status -> Varchar,
+        file_path -> Nullable<Varchar>,
+        error -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     document_versions (id) {
         id -> Uuid,
@@ -34,6 +196,11 @@ diesel::table! {
         comment -> Nullable<Text>,
         created_by -> Uuid,
         created_at -> Timestamp,
+        document_key -> Nullable<Varchar>,
+        is_forcesave -> Bool,
+        changes_path -> Nullable<Varchar>,
+        history -> Nullable<Jsonb>,
+        share_link_id -> Nullable<Uuid>,
     }
 }
 
@@ -55,6 +222,23 @@ diesel::table! {
         created_at -> Timestamp,
         updated_at -> Timestamp,
         deleted_at -> Nullable<Timestamp>,
+        locked_by -> Nullable<Uuid>,
+        locked_at -> Nullable<Timestamp>,
+        lock_expires_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    editor_sessions (id) {
+        id -> Uuid,
+        document_id -> Uuid,
+        user_id -> Nullable<Uuid>,
+        document_key -> Varchar,
+        started_at -> Timestamp,
+        last_seen_at -> Timestamp,
+        ended_at -> Nullable<Timestamp>,
+        share_link_id -> Nullable<Uuid>,
+        editor_id -> Varchar,
     }
 }
 
@@ -65,6 +249,7 @@ diesel::table! {
         user_id -> Uuid,
         role -> Varchar,
         joined_at -> Timestamp,
+        source -> Varchar,
     }
 }
 
@@ -85,6 +270,89 @@ diesel::table! {
         id -> Uuid,
         name -> Varchar,
         description -> Nullable<Text>,
+        created_by -> Nullable<Uuid>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    legal_holds (id) {
+        id -> Uuid,
+        document_id -> Uuid,
+        reason -> Text,
+        created_by -> Uuid,
+        created_at -> Timestamp,
+        released_by -> Nullable<Uuid>,
+        released_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    metadata_schema_bindings (id) {
+        id -> Uuid,
+        schema_id -> Uuid,
+        folder_id -> Nullable<Uuid>,
+        mime_type -> Nullable<Varchar>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    metadata_schemas (id) {
+        id -> Uuid,
+        name -> Varchar,
+        description -> Nullable<Text>,
+        fields -> Jsonb,
+        created_by -> Uuid,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    oidc_login_requests (id) {
+        id -> Uuid,
+        state_hash -> Varchar,
+        pkce_verifier -> Varchar,
+        nonce -> Varchar,
+        expires_at -> Timestamp,
+        created_at -> Timestamp,        login_request_hash -> Varchar,
+    }
+}
+
+diesel::table! {
+    password_reset_tokens (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        token_hash -> Varchar,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    retention_audit_logs (id) {
+        id -> Uuid,
+        policy_id -> Nullable<Uuid>,
+        document_id -> Uuid,
+        document_name -> Varchar,
+        action -> Varchar,
+        details -> Nullable<Text>,
+        executed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    retention_policies (id) {
+        id -> Uuid,
+        name -> Varchar,
+        folder_id -> Nullable<Uuid>,
+        tag -> Nullable<Varchar>,
+        mime_type -> Nullable<Varchar>,
+        retention_days -> Int4,
+        is_active -> Bool,
         created_by -> Uuid,
         created_at -> Timestamp,
         updated_at -> Timestamp,
@@ -106,6 +374,53 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    storage_quotas (id) {
+        id -> Uuid,
+        user_id -> Nullable<Uuid>,
+        group_id -> Nullable<Uuid>,
+        max_bytes -> Int8,
+        created_by -> Uuid,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    tags (id) {
+        id -> Uuid,
+        name -> Varchar,
+        description -> Nullable<Text>,
+        created_by -> Uuid,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    totp_recovery_codes (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        code_hash -> Varchar,
+        used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    user_sessions (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        refresh_token_hash -> Varchar,
+        user_agent -> Nullable<Text>,
+        ip_address -> Nullable<Varchar>,
+        created_at -> Timestamp,
+        last_used_at -> Timestamp,
+        expires_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
+        impersonator_id -> Nullable<Uuid>,
+    }
+}
+
 diesel::table! {
     users (id) {
         id -> Uuid,
@@ -117,28 +432,93 @@ diesel::table! {
         is_active -> Bool,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        totp_secret -> Nullable<Varchar>,
+        totp_enabled -> Bool,
+        totp_required -> Bool,
+        totp_last_step -> Nullable<Int8>,
+        oidc_subject -> Nullable<Varchar>,
+        ldap_dn -> Nullable<Varchar>,
+        is_service_account -> Bool,
+        email_verified_at -> Nullable<Timestamp>,
     }
 }
 
 diesel::joinable!(access_logs -> documents (document_id));
+diesel::joinable!(api_tokens -> documents (folder_id));
+diesel::joinable!(api_tokens -> users (user_id));
+diesel::joinable!(approval_steps -> approval_workflows (workflow_id));
+diesel::joinable!(approval_steps -> groups (reviewer_group_id));
+diesel::joinable!(approval_workflows -> documents (document_id));
+diesel::joinable!(approval_workflows -> users (submitted_by));
 diesel::joinable!(document_permissions -> documents (document_id));
 diesel::joinable!(document_permissions -> users (user_id));
+diesel::joinable!(document_renditions -> documents (document_id));
+diesel::joinable!(document_renditions -> users (created_by));
+diesel::joinable!(document_status_history -> documents (document_id));
+diesel::joinable!(document_status_history -> users (changed_by));
+diesel::joinable!(document_templates -> groups (group_id));
+diesel::joinable!(document_templates -> users (created_by));
+diesel::joinable!(document_thumbnails -> documents (document_id));
 diesel::joinable!(document_versions -> documents (document_id));
+diesel::joinable!(document_versions -> share_links (share_link_id));
+diesel::joinable!(editor_sessions -> documents (document_id));
+diesel::joinable!(editor_sessions -> share_links (share_link_id));
+diesel::joinable!(editor_sessions -> users (user_id));
+diesel::joinable!(email_verification_tokens -> users (user_id));
 diesel::joinable!(group_members -> groups (group_id));
 diesel::joinable!(group_members -> users (user_id));
 diesel::joinable!(group_permissions -> documents (document_id));
 diesel::joinable!(group_permissions -> groups (group_id));
+diesel::joinable!(invitation_groups -> groups (group_id));
+diesel::joinable!(invitation_groups -> invitations (invitation_id));
+diesel::joinable!(legal_holds -> documents (document_id));
+diesel::joinable!(metadata_schema_bindings -> documents (folder_id));
+diesel::joinable!(metadata_schema_bindings -> metadata_schemas (schema_id));
+diesel::joinable!(metadata_schemas -> users (created_by));
+diesel::joinable!(password_reset_tokens -> users (user_id));
+diesel::joinable!(retention_audit_logs -> retention_policies (policy_id));
+diesel::joinable!(retention_policies -> documents (folder_id));
+diesel::joinable!(retention_policies -> users (created_by));
 diesel::joinable!(share_links -> documents (document_id));
+diesel::joinable!(storage_quotas -> groups (group_id));
+diesel::joinable!(tags -> users (created_by));
+diesel::joinable!(totp_recovery_codes -> users (user_id));
+diesel::joinable!(user_sessions -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     access_logs,
+    admin_audit_logs,
+    api_tokens,
+    approval_steps,
+    approval_workflows,
     document_permissions,
+    document_renditions,
+    document_status_history,
+    document_status_transitions,
+    document_templates,
+    document_thumbnails,
     document_versions,
     documents,
+    editor_sessions,
+    email_verification_tokens,
     group_members,
     group_permissions,
     groups,
+    invitation_groups,
+    invitations,
+    legal_holds,
+    login_failures,
+    metadata_schema_bindings,
+    metadata_schemas,
+    oidc_login_requests,
+    password_reset_tokens,
+    retention_audit_logs,
+    retention_policies,
     share_links,
+    storage_quotas,
+    tags,
+    totp_recovery_codes,
+    user_sessions,
     users,
 );
 
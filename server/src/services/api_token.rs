@@ -0,0 +1,184 @@
+use chrono::Duration;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::api_token::{
+    ApiToken, ApiTokenScope, CreateApiTokenRequest, CreateApiTokenResponse, CreateServiceAccountRequest,
+    NewApiToken,
+};
+use crate::models::user::{NewUser, User, UserRole};
+use crate::schema::{api_tokens, documents, users};
+use crate::services::FolderService;
+use crate::utils::{generate_token, hash_password, hash_token};
+
+/// Marks API tokens, so they can be told apart from JWTs in the Authorization header
+pub const API_TOKEN_PREFIX: &str = "dms_";
+const DISPLAY_PREFIX_CHARS: usize = 12;
+// 最近使用时间只精确到分钟，避免每个请求都写数据库
+const LAST_USED_RESOLUTION_SECS: i64 = 60;
+
+pub struct ApiTokenService;
+
+impl ApiTokenService {
+    pub fn is_api_token(token: &str) -> bool {
+        token.starts_with(API_TOKEN_PREFIX)
+    }
+
+    /// Issue a token for a user.
+    ///
+    /// The token itself is only in the response; just its digest is stored.
+    pub fn create(
+        conn: &mut DbConnection,
+        user_id: Uuid,
+        request: CreateApiTokenRequest,
+    ) -> Result<CreateApiTokenResponse> {
+        if let Some(folder_id) = request.folder_id {
+            let is_folder = documents::table
+                .find(folder_id)
+                .filter(documents::deleted_at.is_null())
+                .select(documents::is_folder)
+                .first::<bool>(conn)
+                .optional()?
+                .ok_or_else(|| AppError::NotFound("Folder not found".to_string()))?;
+
+            if !is_folder {
+                return Err(AppError::BadRequest("Tokens can only be limited to a folder".to_string()));
+            }
+        }
+
+        let token = format!("{}{}", API_TOKEN_PREFIX, generate_token());
+        let expires_at = request
+            .expires_in_days
+            .map(|days| chrono::Local::now().naive_local() + Duration::days(days));
+
+        let api_token = diesel::insert_into(api_tokens::table)
+            .values(&NewApiToken {
+                user_id,
+                name: request.name,
+                token_hash: hash_token(&token),
+                token_prefix: token.chars().take(DISPLAY_PREFIX_CHARS).collect(),
+                scope: request.scope.unwrap_or(ApiTokenScope::Write).as_str().to_string(),
+                folder_id: request.folder_id,
+                expires_at,
+            })
+            .returning(ApiToken::as_returning())
+            .get_result(conn)?;
+
+        Ok(CreateApiTokenResponse { token, api_token })
+    }
+
+    pub fn list(conn: &mut DbConnection, user_id: Uuid) -> Result<Vec<ApiToken>> {
+        let tokens = api_tokens::table
+            .filter(api_tokens::user_id.eq(user_id))
+            .order(api_tokens::created_at.desc())
+            .select(ApiToken::as_select())
+            .load(conn)?;
+
+        Ok(tokens)
+    }
+
+    /// Delete one of a user's tokens; false if there was no such token
+    pub fn revoke(conn: &mut DbConnection, user_id: Uuid, token_id: Uuid) -> Result<bool> {
+        let deleted = diesel::delete(
+            api_tokens::table
+                .find(token_id)
+                .filter(api_tokens::user_id.eq(user_id)),
+        )
+        .execute(conn)?;
+
+        Ok(deleted > 0)
+    }
+
+    /// The token and its user, if the token is valid and the user still active
+    pub fn authenticate(conn: &mut DbConnection, token: &str) -> Result<(ApiToken, User)> {
+        let (api_token, user) = api_tokens::table
+            .inner_join(users::table)
+            .filter(api_tokens::token_hash.eq(hash_token(token)))
+            .filter(
+                api_tokens::expires_at
+                    .is_null()
+                    .or(api_tokens::expires_at.gt(diesel::dsl::now)),
+            )
+            .filter(users::is_active.eq(true))
+            .select((ApiToken::as_select(), User::as_select()))
+            .first::<(ApiToken, User)>(conn)
+            .optional()?
+            .ok_or_else(|| AppError::Unauthorized("Invalid or expired API token".to_string()))?;
+
+        let stale_before = chrono::Local::now().naive_local() - Duration::seconds(LAST_USED_RESOLUTION_SECS);
+        diesel::update(
+            api_tokens::table.find(api_token.id).filter(
+                api_tokens::last_used_at
+                    .is_null()
+                    .or(api_tokens::last_used_at.lt(stale_before)),
+            ),
+        )
+        .set(api_tokens::last_used_at.eq(diesel::dsl::now.nullable()))
+        .execute(conn)?;
+
+        Ok((api_token, user))
+    }
+
+    /// Whether a token may touch a document; `None` is the top level, outside every folder
+    pub fn in_scope(conn: &mut DbConnection, api_token: &ApiToken, document_id: Option<Uuid>) -> Result<bool> {
+        let Some(scope_folder) = api_token.folder_id else {
+            return Ok(true);
+        };
+
+        Ok(FolderService::ancestor_chain(conn, document_id)?.contains(&scope_folder))
+    }
+
+    /// Create a user that can only be used through API tokens
+    pub fn create_service_account(conn: &mut DbConnection, request: CreateServiceAccountRequest) -> Result<User> {
+        let taken = diesel::select(diesel::dsl::exists(
+            users::table.filter(
+                users::username
+                    .eq(&request.username)
+                    .or(users::email.eq(&request.email)),
+            ),
+        ))
+        .get_result::<bool>(conn)?;
+
+        if taken {
+            return Err(AppError::BadRequest("Username or email already exists".to_string()));
+        }
+
+        let new_user = NewUser {
+            username: request.username,
+            email: request.email,
+            // Nobody knows this password, and service accounts can't log in anyway
+            password_hash: hash_password(&generate_token())?,
+            full_name: request.full_name,
+            role: UserRole::User.as_str().to_string(),
+        };
+
+        let user = diesel::insert_into(users::table)
+            .values((&new_user, users::is_service_account.eq(true)))
+            .returning(User::as_returning())
+            .get_result(conn)?;
+
+        Ok(user)
+    }
+
+    pub fn list_service_accounts(conn: &mut DbConnection) -> Result<Vec<User>> {
+        let accounts = users::table
+            .filter(users::is_service_account.eq(true))
+            .order(users::username.asc())
+            .select(User::as_select())
+            .load(conn)?;
+
+        Ok(accounts)
+    }
+
+    pub fn find_service_account(conn: &mut DbConnection, user_id: Uuid) -> Result<User> {
+        users::table
+            .find(user_id)
+            .filter(users::is_service_account.eq(true))
+            .select(User::as_select())
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound("Service account not found".to_string()))
+    }
+}
@@ -0,0 +1,473 @@
+use diesel::prelude::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::approval::{
+    ApprovalDecision, ApprovalMode, ApprovalStep, ApprovalWorkflow, ApprovalWorkflowResponse,
+    NewApprovalStep, NewApprovalWorkflow, PendingReviewResponse, SubmitApprovalRequest,
+    STEP_APPROVED, STEP_PENDING, STEP_REJECTED, STEP_SKIPPED, WORKFLOW_APPROVED,
+    WORKFLOW_CANCELLED, WORKFLOW_PENDING, WORKFLOW_REJECTED,
+};
+use crate::models::document::Document;
+use crate::models::lifecycle::DocumentStatus;
+use crate::models::permission::PermissionType;
+use crate::schema::{approval_steps, approval_workflows, documents, group_members};
+use crate::services::{LifecycleService, PermissionService};
+
+pub struct ApprovalService;
+
+impl ApprovalService {
+    /// Submit a document for approval and move it to `in_review`.
+    ///
+    /// Sequential workflows get one step per reviewer in the given order;
+    /// parallel workflows put every reviewer on the same step.
+    pub fn submit(
+        conn: &mut DbConnection,
+        document: &Document,
+        user_id: Uuid,
+        is_admin: bool,
+        request: SubmitApprovalRequest,
+    ) -> Result<ApprovalWorkflowResponse> {
+        if request.reviewers.is_empty() {
+            return Err(AppError::BadRequest("At least one reviewer is required".to_string()));
+        }
+
+        if request
+            .reviewers
+            .iter()
+            .any(|r| r.user_id.is_some() == r.group_id.is_some())
+        {
+            return Err(AppError::BadRequest(
+                "Each reviewer needs exactly one of user_id or group_id".to_string(),
+            ));
+        }
+
+        if request.reviewers.iter().any(|r| r.user_id == Some(user_id)) {
+            return Err(AppError::BadRequest("You cannot review your own submission".to_string()));
+        }
+
+        for reviewer in &request.reviewers {
+            let can_read = match (reviewer.user_id, reviewer.group_id) {
+                (Some(reviewer_id), _) => {
+                    PermissionService::check_permission(conn, reviewer_id, document.id, PermissionType::Read)?
+                }
+                (_, Some(group_id)) => Self::group_can_review(conn, group_id, document.id, user_id)?,
+                _ => false,
+            };
+
+            if !can_read {
+                return Err(AppError::BadRequest(
+                    "Every reviewer must be able to read the document".to_string(),
+                ));
+            }
+        }
+
+        if Self::has_pending_workflow(conn, document.id)? {
+            return Err(AppError::BadRequest(
+                "Document already has a pending approval workflow".to_string(),
+            ));
+        }
+
+        conn.transaction::<ApprovalWorkflowResponse, AppError, _>(|conn| {
+            LifecycleService::change_status(
+                conn,
+                document,
+                DocumentStatus::InReview,
+                user_id,
+                is_admin,
+                request.comment.clone(),
+            )?;
+
+            let new_workflow = NewApprovalWorkflow {
+                document_id: document.id,
+                document_version: document.version,
+                submitted_by: user_id,
+                mode: request.mode.as_str().to_string(),
+                comment: request.comment,
+            };
+
+            let workflow = diesel::insert_into(approval_workflows::table)
+                .values(&new_workflow)
+                .returning(ApprovalWorkflow::as_returning())
+                .get_result::<ApprovalWorkflow>(conn)?;
+
+            let new_steps: Vec<NewApprovalStep> = request
+                .reviewers
+                .iter()
+                .enumerate()
+                .map(|(index, reviewer)| NewApprovalStep {
+                    workflow_id: workflow.id,
+                    step_order: match request.mode {
+                        ApprovalMode::Sequential => index as i32,
+                        ApprovalMode::Parallel => 0,
+                    },
+                    reviewer_user_id: reviewer.user_id,
+                    reviewer_group_id: reviewer.group_id,
+                })
+                .collect();
+
+            let steps = diesel::insert_into(approval_steps::table)
+                .values(&new_steps)
+                .returning(ApprovalStep::as_returning())
+                .get_results::<ApprovalStep>(conn)?;
+
+            Ok(ApprovalWorkflowResponse { workflow, steps })
+        })
+    }
+
+    /// Record a reviewer's decision on a step.
+    ///
+    /// A rejection ends the workflow and returns the document to `draft`; the
+    /// last approval ends it and moves the document to `approved`.
+    pub fn decide(
+        conn: &mut DbConnection,
+        step_id: Uuid,
+        user_id: Uuid,
+        decision: ApprovalDecision,
+        comment: Option<String>,
+    ) -> Result<ApprovalWorkflowResponse> {
+        let step = approval_steps::table
+            .find(step_id)
+            .select(ApprovalStep::as_select())
+            .first::<ApprovalStep>(conn)?;
+
+        let workflow = approval_workflows::table
+            .find(step.workflow_id)
+            .select(ApprovalWorkflow::as_select())
+            .first::<ApprovalWorkflow>(conn)?;
+
+        if workflow.status != WORKFLOW_PENDING || step.status != STEP_PENDING {
+            return Err(AppError::BadRequest("This review step is already closed".to_string()));
+        }
+
+        if !Self::is_reviewer(conn, &workflow, &step, user_id)? {
+            return Err(AppError::Forbidden("You are not a reviewer for this step".to_string()));
+        }
+
+        if Self::current_step_order(conn, workflow.id)? != Some(step.step_order) {
+            return Err(AppError::BadRequest(
+                "Earlier review steps must be completed first".to_string(),
+            ));
+        }
+
+        let document = documents::table
+            .find(workflow.document_id)
+            .select(Document::as_select())
+            .first::<Document>(conn)?;
+
+        if document.version != workflow.document_version {
+            return Err(AppError::BadRequest(format!(
+                "Document changed since submission (version {} submitted, now {}); it must be resubmitted",
+                workflow.document_version, document.version
+            )));
+        }
+
+        conn.transaction::<(), AppError, _>(|conn| {
+            // Concurrent decisions on the same workflow queue up here, so only
+            // one of them can see the last pending step and close it
+            let workflow_status = approval_workflows::table
+                .find(workflow.id)
+                .select(approval_workflows::status)
+                .for_update()
+                .first::<String>(conn)?;
+
+            if workflow_status != WORKFLOW_PENDING {
+                return Err(AppError::BadRequest("This review step is already closed".to_string()));
+            }
+
+            let step_status = match decision {
+                ApprovalDecision::Approve => STEP_APPROVED,
+                ApprovalDecision::Reject => STEP_REJECTED,
+            };
+
+            let updated = diesel::update(
+                approval_steps::table
+                    .find(step.id)
+                    .filter(approval_steps::status.eq(STEP_PENDING)),
+            )
+            .set((
+                approval_steps::status.eq(step_status),
+                approval_steps::decided_by.eq(Some(user_id)),
+                approval_steps::decision_comment.eq(&comment),
+                approval_steps::decided_at.eq(diesel::dsl::now.nullable()),
+            ))
+            .execute(conn)?;
+
+            if updated == 0 {
+                return Err(AppError::BadRequest("This review step is already closed".to_string()));
+            }
+
+            let outcome = match decision {
+                ApprovalDecision::Reject => {
+                    diesel::update(
+                        approval_steps::table
+                            .filter(approval_steps::workflow_id.eq(workflow.id))
+                            .filter(approval_steps::status.eq(STEP_PENDING)),
+                    )
+                    .set(approval_steps::status.eq(STEP_SKIPPED))
+                    .execute(conn)?;
+
+                    Some((WORKFLOW_REJECTED, DocumentStatus::Draft))
+                }
+                ApprovalDecision::Approve => {
+                    if Self::current_step_order(conn, workflow.id)?.is_none() {
+                        Some((WORKFLOW_APPROVED, DocumentStatus::Approved))
+                    } else {
+                        None
+                    }
+                }
+            };
+
+            if let Some((workflow_status, document_status)) = outcome {
+                Self::close_workflow(conn, &workflow, workflow_status)?;
+                LifecycleService::apply_status(conn, &document, document_status, user_id, comment)?;
+            }
+
+            Ok(())
+        })?;
+
+        Self::get_workflow(conn, workflow.id)
+    }
+
+    /// Cancel a pending workflow and return the document to `draft`
+    pub fn cancel(
+        conn: &mut DbConnection,
+        workflow_id: Uuid,
+        user_id: Uuid,
+        is_admin: bool,
+    ) -> Result<ApprovalWorkflowResponse> {
+        let workflow = approval_workflows::table
+            .find(workflow_id)
+            .select(ApprovalWorkflow::as_select())
+            .first::<ApprovalWorkflow>(conn)?;
+
+        if workflow.submitted_by != user_id && !is_admin {
+            return Err(AppError::Forbidden(
+                "Only the submitter or an admin can cancel this workflow".to_string(),
+            ));
+        }
+
+        if workflow.status != WORKFLOW_PENDING {
+            return Err(AppError::BadRequest("Workflow is not pending".to_string()));
+        }
+
+        let document = documents::table
+            .find(workflow.document_id)
+            .select(Document::as_select())
+            .first::<Document>(conn)?;
+
+        conn.transaction::<(), AppError, _>(|conn| {
+            diesel::update(
+                approval_steps::table
+                    .filter(approval_steps::workflow_id.eq(workflow.id))
+                    .filter(approval_steps::status.eq(STEP_PENDING)),
+            )
+            .set(approval_steps::status.eq(STEP_SKIPPED))
+            .execute(conn)?;
+
+            Self::close_workflow(conn, &workflow, WORKFLOW_CANCELLED)?;
+            LifecycleService::apply_status(
+                conn,
+                &document,
+                DocumentStatus::Draft,
+                user_id,
+                Some("Approval workflow cancelled".to_string()),
+            )?;
+
+            Ok(())
+        })?;
+
+        Self::get_workflow(conn, workflow.id)
+    }
+
+    /// Review steps currently waiting on `user_id`, directly or via a group
+    pub fn pending_for_user(conn: &mut DbConnection, user_id: Uuid) -> Result<Vec<PendingReviewResponse>> {
+        let group_ids = group_members::table
+            .filter(group_members::user_id.eq(user_id))
+            .select(group_members::group_id)
+            .load::<Uuid>(conn)?;
+
+        let candidates = approval_steps::table
+            .inner_join(approval_workflows::table)
+            .inner_join(documents::table.on(documents::id.eq(approval_workflows::document_id)))
+            .filter(approval_workflows::status.eq(WORKFLOW_PENDING))
+            .filter(approval_steps::status.eq(STEP_PENDING))
+            .filter(
+                approval_steps::reviewer_user_id
+                    .eq(user_id)
+                    .or(approval_steps::reviewer_group_id.eq_any(&group_ids)),
+            )
+            .order(approval_workflows::created_at.asc())
+            .select((
+                ApprovalStep::as_select(),
+                ApprovalWorkflow::as_select(),
+                documents::name,
+            ))
+            .load::<(ApprovalStep, ApprovalWorkflow, String)>(conn)?;
+
+        let mut current_orders: HashMap<Uuid, Option<i32>> = HashMap::new();
+        let mut pending = Vec::new();
+        for (step, workflow, document_name) in candidates {
+            let current = match current_orders.get(&workflow.id) {
+                Some(order) => *order,
+                None => {
+                    let order = Self::current_step_order(conn, workflow.id)?;
+                    current_orders.insert(workflow.id, order);
+                    order
+                }
+            };
+
+            // Group steps may include the submitter, who doesn't review their own work
+            if current == Some(step.step_order) && workflow.submitted_by != user_id {
+                pending.push(PendingReviewResponse {
+                    step,
+                    workflow,
+                    document_name,
+                });
+            }
+        }
+
+        Ok(pending)
+    }
+
+    /// All workflows for a document with their steps, newest first
+    pub fn history_for_document(
+        conn: &mut DbConnection,
+        document_id: Uuid,
+    ) -> Result<Vec<ApprovalWorkflowResponse>> {
+        let workflows = approval_workflows::table
+            .filter(approval_workflows::document_id.eq(document_id))
+            .order(approval_workflows::created_at.desc())
+            .select(ApprovalWorkflow::as_select())
+            .load::<ApprovalWorkflow>(conn)?;
+
+        let workflow_ids: Vec<Uuid> = workflows.iter().map(|w| w.id).collect();
+        let steps = approval_steps::table
+            .filter(approval_steps::workflow_id.eq_any(&workflow_ids))
+            .order((approval_steps::step_order.asc(), approval_steps::id.asc()))
+            .select(ApprovalStep::as_select())
+            .load::<ApprovalStep>(conn)?;
+
+        let mut steps_by_workflow: HashMap<Uuid, Vec<ApprovalStep>> = HashMap::new();
+        for step in steps {
+            steps_by_workflow.entry(step.workflow_id).or_default().push(step);
+        }
+
+        Ok(workflows
+            .into_iter()
+            .map(|workflow| {
+                let steps = steps_by_workflow.remove(&workflow.id).unwrap_or_default();
+                ApprovalWorkflowResponse { workflow, steps }
+            })
+            .collect())
+    }
+
+    pub fn get_workflow(conn: &mut DbConnection, workflow_id: Uuid) -> Result<ApprovalWorkflowResponse> {
+        let workflow = approval_workflows::table
+            .find(workflow_id)
+            .select(ApprovalWorkflow::as_select())
+            .first::<ApprovalWorkflow>(conn)?;
+
+        let steps = approval_steps::table
+            .filter(approval_steps::workflow_id.eq(workflow_id))
+            .order((approval_steps::step_order.asc(), approval_steps::id.asc()))
+            .select(ApprovalStep::as_select())
+            .load::<ApprovalStep>(conn)?;
+
+        Ok(ApprovalWorkflowResponse { workflow, steps })
+    }
+
+    pub fn has_pending_workflow(conn: &mut DbConnection, document_id: Uuid) -> Result<bool> {
+        let has_pending = diesel::select(diesel::dsl::exists(
+            approval_workflows::table
+                .filter(approval_workflows::document_id.eq(document_id))
+                .filter(approval_workflows::status.eq(WORKFLOW_PENDING)),
+        ))
+        .get_result::<bool>(conn)?;
+
+        Ok(has_pending)
+    }
+
+    /// The lowest step order that still has pending steps, if any
+    fn current_step_order(conn: &mut DbConnection, workflow_id: Uuid) -> Result<Option<i32>> {
+        let order = approval_steps::table
+            .filter(approval_steps::workflow_id.eq(workflow_id))
+            .filter(approval_steps::status.eq(STEP_PENDING))
+            .select(diesel::dsl::min(approval_steps::step_order))
+            .first::<Option<i32>>(conn)?;
+
+        Ok(order)
+    }
+
+    fn is_reviewer(
+        conn: &mut DbConnection,
+        workflow: &ApprovalWorkflow,
+        step: &ApprovalStep,
+        user_id: Uuid,
+    ) -> Result<bool> {
+        if workflow.submitted_by == user_id {
+            return Ok(false);
+        }
+
+        if !PermissionService::check_permission(conn, user_id, workflow.document_id, PermissionType::Read)? {
+            return Ok(false);
+        }
+
+        if step.reviewer_user_id == Some(user_id) {
+            return Ok(true);
+        }
+
+        match step.reviewer_group_id {
+            Some(group_id) => {
+                let is_member = diesel::select(diesel::dsl::exists(
+                    group_members::table
+                        .filter(group_members::group_id.eq(group_id))
+                        .filter(group_members::user_id.eq(user_id)),
+                ))
+                .get_result::<bool>(conn)?;
+
+                Ok(is_member)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Whether some member of the group other than the submitter can read the document
+    fn group_can_review(
+        conn: &mut DbConnection,
+        group_id: Uuid,
+        document_id: Uuid,
+        submitter_id: Uuid,
+    ) -> Result<bool> {
+        let member_ids = group_members::table
+            .filter(group_members::group_id.eq(group_id))
+            .filter(group_members::user_id.ne(submitter_id))
+            .select(group_members::user_id)
+            .load::<Uuid>(conn)?;
+
+        for member_id in member_ids {
+            if PermissionService::check_permission(conn, member_id, document_id, PermissionType::Read)? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn close_workflow(
+        conn: &mut DbConnection,
+        workflow: &ApprovalWorkflow,
+        status: &str,
+    ) -> std::result::Result<(), diesel::result::Error> {
+        diesel::update(approval_workflows::table.find(workflow.id))
+            .set((
+                approval_workflows::status.eq(status),
+                approval_workflows::completed_at.eq(diesel::dsl::now.nullable()),
+            ))
+            .execute(conn)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use chrono::Duration;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::error::Result;
+use crate::models::document::Document;
+use crate::models::editor_session::{ActiveEditor, NewEditorSession};
+use crate::schema::{editor_sessions, share_links, users};
+use crate::services::{OnlyOfficeService, VersionService};
+
+// Document Server 异常退出时不会发送关闭回调，超过此时间的会话视为已结束
+const STALE_SESSION_HOURS: i64 = 24;
+
+pub struct EditorSessionService;
+
+impl EditorSessionService {
+    /// Make the open sessions of a document match the editors Document Server
+    /// reports: users, and anonymous editors on the document's share links
+    pub fn sync_editors(
+        conn: &mut DbConnection,
+        document_id: Uuid,
+        document_key: &str,
+        editor_ids: &[String],
+    ) -> Result<()> {
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            // Close sessions of editors who left, or who were editing an older version
+            diesel::update(
+                editor_sessions::table
+                    .filter(editor_sessions::document_id.eq(document_id))
+                    .filter(editor_sessions::ended_at.is_null())
+                    .filter(
+                        editor_sessions::editor_id
+                            .ne_all(editor_ids)
+                            .or(editor_sessions::document_key.ne(document_key)),
+                    ),
+            )
+            .set(editor_sessions::ended_at.eq(diesel::dsl::now.nullable()))
+            .execute(conn)?;
+
+            for editor_id in editor_ids {
+                let touched = diesel::update(
+                    editor_sessions::table
+                        .filter(editor_sessions::document_id.eq(document_id))
+                        .filter(editor_sessions::editor_id.eq(editor_id))
+                        .filter(editor_sessions::ended_at.is_null()),
+                )
+                .set(editor_sessions::last_seen_at.eq(diesel::dsl::now))
+                .execute(conn)?;
+
+                if touched > 0 {
+                    continue;
+                }
+
+                let (user_id, share_link_id) = match Uuid::parse_str(editor_id) {
+                    Ok(user_id) => {
+                        let exists = diesel::select(diesel::dsl::exists(users::table.find(user_id)))
+                            .get_result::<bool>(conn)?;
+                        (exists.then_some(user_id), None)
+                    }
+                    Err(_) => {
+                        let share_link_id = match OnlyOfficeService::parse_share_user_id(editor_id) {
+                            Some(link_id) => share_links::table
+                                .find(link_id)
+                                .filter(share_links::document_id.eq(document_id))
+                                .select(share_links::id)
+                                .first::<Uuid>(conn)
+                                .optional()?,
+                            None => None,
+                        };
+                        (None, share_link_id)
+                    }
+                };
+
+                // Deleted users and revoked links have nobody left to track
+                if user_id.is_none() && share_link_id.is_none() {
+                    continue;
+                }
+
+                diesel::insert_into(editor_sessions::table)
+                    .values(&NewEditorSession {
+                        document_id,
+                        user_id,
+                        document_key: document_key.to_string(),
+                        share_link_id,
+                        editor_id: editor_id.clone(),
+                    })
+                    .execute(conn)?;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    /// Close every open session, e.g. once all editors have left
+    pub fn end_all(conn: &mut DbConnection, document_id: Uuid) -> Result<()> {
+        diesel::update(
+            editor_sessions::table
+                .filter(editor_sessions::document_id.eq(document_id))
+                .filter(editor_sessions::ended_at.is_null()),
+        )
+        .set(editor_sessions::ended_at.eq(diesel::dsl::now.nullable()))
+        .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Key new editors should open the document with.
+    ///
+    /// A force save adds a version while the session carries on under its
+    /// original key, so editors joining that session must use the same key.
+    pub fn editing_key(conn: &mut DbConnection, document: &Document) -> Result<String> {
+        let session_key = VersionService::current_version(conn, document)?
+            .filter(|version| version.is_forcesave)
+            .and_then(|version| version.document_key);
+
+        if let Some(key) = session_key {
+            let cutoff = chrono::Local::now().naive_local() - Duration::hours(STALE_SESSION_HOURS);
+
+            let in_progress = diesel::select(diesel::dsl::exists(
+                editor_sessions::table
+                    .filter(editor_sessions::document_id.eq(document.id))
+                    .filter(editor_sessions::document_key.eq(&key))
+                    .filter(editor_sessions::ended_at.is_null())
+                    .filter(editor_sessions::last_seen_at.gt(cutoff)),
+            ))
+            .get_result::<bool>(conn)?;
+
+            if in_progress {
+                return Ok(key);
+            }
+        }
+
+        Ok(OnlyOfficeService::document_key(document.id, document.version))
+    }
+
+    /// Users and share link editors currently editing a document
+    pub fn active_editors(conn: &mut DbConnection, document_id: Uuid) -> Result<Vec<ActiveEditor>> {
+        let cutoff = chrono::Local::now().naive_local() - Duration::hours(STALE_SESSION_HOURS);
+
+        let editors = editor_sessions::table
+            .left_join(users::table)
+            .filter(editor_sessions::document_id.eq(document_id))
+            .filter(editor_sessions::ended_at.is_null())
+            .filter(editor_sessions::last_seen_at.gt(cutoff))
+            .order(editor_sessions::started_at.asc())
+            .select((
+                editor_sessions::user_id,
+                users::username.nullable(),
+                editor_sessions::share_link_id,
+                editor_sessions::started_at,
+                editor_sessions::last_seen_at,
+            ))
+            .load::<ActiveEditor>(conn)?;
+
+        Ok(editors)
+    }
+}
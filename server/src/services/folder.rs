@@ -0,0 +1,57 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::error::Result;
+use crate::schema::documents;
+
+// 防止 parent_folder_id 出现环时无限循环
+const MAX_FOLDER_DEPTH: usize = 64;
+
+pub struct FolderService;
+
+impl FolderService {
+    /// `start` followed by each of its ancestor folders, nearest first
+    pub fn ancestor_chain(conn: &mut DbConnection, start: Option<Uuid>) -> Result<Vec<Uuid>> {
+        let mut ids = Vec::new();
+        let mut current = start;
+
+        while let Some(folder_id) = current {
+            if ids.contains(&folder_id) || ids.len() > MAX_FOLDER_DEPTH {
+                break;
+            }
+            ids.push(folder_id);
+
+            current = documents::table
+                .find(folder_id)
+                .select(documents::parent_folder_id)
+                .first::<Option<Uuid>>(conn)
+                .optional()?
+                .flatten();
+        }
+
+        Ok(ids)
+    }
+
+    /// Everything inside a folder at any depth, level by level
+    pub fn descendant_ids(conn: &mut DbConnection, folder_id: Uuid) -> Result<Vec<Uuid>> {
+        let mut ids = Vec::new();
+        let mut level = vec![folder_id];
+
+        for _ in 0..MAX_FOLDER_DEPTH {
+            level = documents::table
+                .filter(documents::parent_folder_id.eq_any(&level))
+                .filter(documents::id.ne_all(&ids))
+                .filter(documents::id.ne(folder_id))
+                .select(documents::id)
+                .load::<Uuid>(conn)?;
+
+            if level.is_empty() {
+                break;
+            }
+            ids.extend(&level);
+        }
+
+        Ok(ids)
+    }
+}
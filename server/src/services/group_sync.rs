@@ -0,0 +1,79 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::error::Result;
+use crate::schema::{group_members, groups};
+
+const MAX_GROUP_NAME_LENGTH: usize = 100;
+
+pub struct GroupSyncService;
+
+impl GroupSyncService {
+    /// Make a user's memberships from an external source match `group_names`.
+    ///
+    /// Groups are matched by name and created when missing. Memberships
+    /// added by hand or by another source are left alone.
+    pub fn sync_memberships(
+        conn: &mut DbConnection,
+        user_id: Uuid,
+        source: &str,
+        group_names: &[String],
+        created_by: Option<Uuid>,
+    ) -> Result<()> {
+        let mut names: Vec<String> = group_names
+            .iter()
+            .map(|name| name.trim().chars().take(MAX_GROUP_NAME_LENGTH).collect::<String>())
+            .filter(|name| !name.is_empty())
+            .collect();
+        names.sort();
+        names.dedup();
+
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            diesel::insert_into(groups::table)
+                .values(
+                    names
+                        .iter()
+                        .map(|name| (groups::name.eq(name), groups::created_by.eq(created_by)))
+                        .collect::<Vec<_>>(),
+                )
+                .on_conflict(groups::name)
+                .do_nothing()
+                .execute(conn)?;
+
+            let group_ids = groups::table
+                .filter(groups::name.eq_any(&names))
+                .select(groups::id)
+                .load::<Uuid>(conn)?;
+
+            diesel::delete(
+                group_members::table
+                    .filter(group_members::user_id.eq(user_id))
+                    .filter(group_members::source.eq(source))
+                    .filter(group_members::group_id.ne_all(&group_ids)),
+            )
+            .execute(conn)?;
+
+            diesel::insert_into(group_members::table)
+                .values(
+                    group_ids
+                        .iter()
+                        .map(|group_id| {
+                            (
+                                group_members::group_id.eq(group_id),
+                                group_members::user_id.eq(user_id),
+                                group_members::source.eq(source),
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .on_conflict((group_members::group_id, group_members::user_id))
+                .do_nothing()
+                .execute(conn)?;
+
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,381 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use diesel::prelude::*;
+use ldap3::adapters::{Adapter, EntriesOnly, PagedResults};
+use ldap3::{ldap_escape, Ldap, LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry};
+use serde::Serialize;
+
+use crate::config::LdapConfig;
+use crate::db::{AppState, DbConnection};
+use crate::error::{AppError, Result};
+use crate::models::group::GROUP_MEMBER_SOURCE_LDAP;
+use crate::models::user::{NewUser, User, UserRole};
+use crate::schema::users;
+use crate::services::{GroupSyncService, SessionService};
+use crate::utils::{generate_token, hash_password};
+
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+const PAGE_SIZE: i32 = 500;
+// LDAP 结果码 49：用户名或密码错误
+const INVALID_CREDENTIALS: u32 = 49;
+
+/// A user entry from the directory
+#[derive(Debug, Clone)]
+pub struct LdapUser {
+    /// Lower-cased, so DNs compare the way the directory compares them
+    pub dn: String,
+    pub username: String,
+    pub email: Option<String>,
+    pub full_name: Option<String>,
+    pub groups: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct LdapSyncSummary {
+    pub users: usize,
+    pub groups: usize,
+    pub deactivated: usize,
+    pub failed: usize,
+}
+
+pub struct LdapService {
+    config: Arc<LdapConfig>,
+}
+
+impl LdapService {
+    pub fn new(config: &LdapConfig) -> Result<Self> {
+        if !config.enabled {
+            return Err(AppError::NotFound("LDAP is not enabled".to_string()));
+        }
+
+        Ok(Self {
+            config: Arc::new(config.clone()),
+        })
+    }
+
+    /// Check a username and password by binding as the user's entry.
+    ///
+    /// Returns None when the user doesn't exist or the password is wrong.
+    pub async fn authenticate(&self, username: &str, password: &str) -> Result<Option<LdapUser>> {
+        // An empty password would be an anonymous bind, which always succeeds
+        if password.is_empty() {
+            return Ok(None);
+        }
+
+        let mut ldap = self.service_bind().await?;
+
+        let filter = format!(
+            "(&{}({}={}))",
+            self.config.user_filter,
+            self.config.username_attribute,
+            ldap_escape(username)
+        );
+        let mut entries = self.search(&mut ldap, &self.config.user_base_dn, &filter, self.user_attributes()).await?;
+
+        // Ambiguous usernames are refused rather than guessed
+        if entries.len() != 1 {
+            let _ = ldap.unbind().await;
+            return Ok(None);
+        }
+
+        let entry = entries.remove(0);
+        let Some(mut user) = self.user_from_entry(entry) else {
+            let _ = ldap.unbind().await;
+            return Ok(None);
+        };
+
+        let mut user_ldap = self.connect().await?;
+        let bind = user_ldap.simple_bind(&user.dn, password).await.map_err(Self::ldap_error)?;
+        let _ = user_ldap.unbind().await;
+
+        if bind.rc == INVALID_CREDENTIALS {
+            let _ = ldap.unbind().await;
+            return Ok(None);
+        }
+        bind.success().map_err(Self::ldap_error)?;
+
+        let filter = format!(
+            "(&{}({}={}))",
+            self.config.group_filter,
+            self.config.group_member_attribute,
+            ldap_escape(user.dn.as_str())
+        );
+        let groups = self
+            .search(&mut ldap, &self.config.group_base_dn, &filter, vec![self.config.group_name_attribute.clone()])
+            .await?;
+        let _ = ldap.unbind().await;
+
+        user.groups = groups
+            .into_iter()
+            .filter_map(|group| self.first_attribute(&group, &self.config.group_name_attribute))
+            .collect();
+
+        Ok(Some(user))
+    }
+
+    /// The local account linked to a directory user, or a newly created one.
+    /// Name and email follow the directory.
+    ///
+    /// Local accounts are never linked by username: a directory entry named
+    /// like a local admin or service account would take it over.
+    pub fn find_or_create_user(conn: &mut DbConnection, ldap_user: &LdapUser) -> Result<User> {
+        let existing = users::table
+            .filter(users::ldap_dn.eq(&ldap_user.dn))
+            .first::<User>(conn)
+            .optional()?;
+
+        if let Some(user) = existing {
+            let email = ldap_user.email.clone().unwrap_or_else(|| user.email.clone());
+
+            let user = diesel::update(users::table.find(user.id))
+                .set((
+                    users::ldap_dn.eq(&ldap_user.dn),
+                    users::email.eq(email),
+                    users::full_name.eq(ldap_user.full_name.clone().or(user.full_name)),
+                    users::updated_at.eq(diesel::dsl::now),
+                ))
+                .returning(User::as_returning())
+                .get_result(conn)?;
+
+            return Ok(user);
+        }
+
+        let email = ldap_user.email.clone().ok_or_else(|| {
+            AppError::BadRequest(format!("Directory entry {} has no email address", ldap_user.dn))
+        })?;
+
+        let username_taken = diesel::select(diesel::dsl::exists(
+            users::table.filter(users::username.eq(&ldap_user.username)),
+        ))
+        .get_result::<bool>(conn)?;
+
+        if username_taken {
+            return Err(AppError::BadRequest(format!(
+                "Username {} of directory entry {} belongs to a local account",
+                ldap_user.username, ldap_user.dn
+            )));
+        }
+
+        let new_user = NewUser {
+            username: ldap_user.username.clone(),
+            email,
+            // Directory accounts never log in with a local password
+            password_hash: hash_password(&generate_token())?,
+            full_name: ldap_user.full_name.clone(),
+            role: UserRole::User.as_str().to_string(),
+        };
+
+        let user = diesel::insert_into(users::table)
+            .values((&new_user, users::ldap_dn.eq(&ldap_user.dn)))
+            .returning(User::as_returning())
+            .get_result(conn)?;
+
+        Ok(user)
+    }
+
+    /// Mirror the directory: create and update users, sync their group
+    /// memberships and deactivate users that are no longer in the directory
+    pub async fn sync(&self, conn: &mut DbConnection) -> Result<LdapSyncSummary> {
+        let mut ldap = self.service_bind().await?;
+
+        let user_entries = self
+            .search(&mut ldap, &self.config.user_base_dn, &self.config.user_filter, self.user_attributes())
+            .await?;
+        let group_entries = self
+            .search(
+                &mut ldap,
+                &self.config.group_base_dn,
+                &self.config.group_filter,
+                vec![self.config.group_name_attribute.clone(), self.config.group_member_attribute.clone()],
+            )
+            .await?;
+        let _ = ldap.unbind().await;
+
+        // Member DN -> names of the groups it belongs to
+        let mut memberships: HashMap<String, Vec<String>> = HashMap::new();
+        for group in &group_entries {
+            let Some(name) = self.first_attribute(group, &self.config.group_name_attribute) else {
+                continue;
+            };
+
+            for member in self.attribute_values(group, &self.config.group_member_attribute) {
+                memberships.entry(member.to_lowercase()).or_default().push(name.clone());
+            }
+        }
+
+        let mut summary = LdapSyncSummary {
+            groups: group_entries.len(),
+            ..Default::default()
+        };
+        let mut seen_dns = Vec::new();
+
+        for entry in user_entries {
+            let Some(mut ldap_user) = self.user_from_entry(entry) else {
+                continue;
+            };
+            ldap_user.groups = memberships.remove(&ldap_user.dn).unwrap_or_default();
+            seen_dns.push(ldap_user.dn.clone());
+
+            let synced = Self::find_or_create_user(conn, &ldap_user).and_then(|user| {
+                GroupSyncService::sync_memberships(conn, user.id, GROUP_MEMBER_SOURCE_LDAP, &ldap_user.groups, None)
+            });
+
+            match synced {
+                Ok(()) => summary.users += 1,
+                Err(e) => {
+                    tracing::warn!("Failed to sync directory user {}: {}", ldap_user.dn, e);
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        // An empty result is more likely a misconfigured filter than an empty directory
+        if seen_dns.is_empty() {
+            tracing::warn!("LDAP sync found no users; skipping deactivation");
+            return Ok(summary);
+        }
+
+        let gone = diesel::update(
+            users::table
+                .filter(users::ldap_dn.is_not_null())
+                .filter(users::ldap_dn.ne_all(&seen_dns))
+                .filter(users::is_active.eq(true)),
+        )
+        .set((users::is_active.eq(false), users::updated_at.eq(diesel::dsl::now)))
+        .returning(users::id)
+        .get_results::<uuid::Uuid>(conn)?;
+
+        for user_id in &gone {
+            SessionService::revoke_all(conn, *user_id)?;
+        }
+        summary.deactivated = gone.len();
+
+        Ok(summary)
+    }
+
+    pub async fn run_sync(state: &AppState) -> Result<LdapSyncSummary> {
+        let ldap_service = Self::new(&state.config.ldap)?;
+        let mut conn = state.get_connection()?;
+
+        ldap_service.sync(&mut conn).await
+    }
+
+    /// Sync the directory forever at the configured interval
+    pub async fn run_scheduler(state: AppState) {
+        let interval_secs = state.config.ldap.sync_interval_secs;
+        if !state.config.ldap.enabled || interval_secs == 0 {
+            return;
+        }
+
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            match Self::run_sync(&state).await {
+                Ok(summary) => tracing::info!(
+                    "LDAP sync updated {} users and {} groups, deactivated {}, {} failed",
+                    summary.users,
+                    summary.groups,
+                    summary.deactivated,
+                    summary.failed
+                ),
+                Err(e) => tracing::error!("LDAP sync failed: {}", e),
+            }
+        }
+    }
+
+    async fn connect(&self) -> Result<Ldap> {
+        let settings = LdapConnSettings::new()
+            .set_conn_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+            .set_starttls(self.config.starttls);
+
+        let (conn, ldap) = LdapConnAsync::with_settings(settings, &self.config.url)
+            .await
+            .map_err(Self::ldap_error)?;
+        ldap3::drive!(conn);
+
+        Ok(ldap)
+    }
+
+    async fn service_bind(&self) -> Result<Ldap> {
+        let mut ldap = self.connect().await?;
+
+        ldap.simple_bind(&self.config.bind_dn, &self.config.bind_password)
+            .await
+            .and_then(|result| result.success())
+            .map_err(Self::ldap_error)?;
+
+        Ok(ldap)
+    }
+
+    // Paged, so Active Directory's 1000-entry limit doesn't truncate results
+    async fn search(
+        &self,
+        ldap: &mut Ldap,
+        base: &str,
+        filter: &str,
+        attributes: Vec<String>,
+    ) -> Result<Vec<SearchEntry>> {
+        let adapters: Vec<Box<dyn Adapter<_, _>>> = vec![
+            Box::new(EntriesOnly::new()),
+            Box::new(PagedResults::new(PAGE_SIZE)),
+        ];
+
+        let mut stream = ldap
+            .streaming_search_with(adapters, base, Scope::Subtree, filter, attributes)
+            .await
+            .map_err(Self::ldap_error)?;
+
+        let mut entries = Vec::new();
+        while let Some(entry) = stream.next().await.map_err(Self::ldap_error)? {
+            entries.push(SearchEntry::construct(entry));
+        }
+        stream.finish().await.success().map_err(Self::ldap_error)?;
+
+        Ok(entries)
+    }
+
+    fn user_attributes(&self) -> Vec<String> {
+        vec![
+            self.config.username_attribute.clone(),
+            self.config.email_attribute.clone(),
+            self.config.name_attribute.clone(),
+        ]
+    }
+
+    fn user_from_entry(&self, entry: SearchEntry) -> Option<LdapUser> {
+        let username = self.first_attribute(&entry, &self.config.username_attribute)?;
+
+        Some(LdapUser {
+            dn: entry.dn.to_lowercase(),
+            username,
+            email: self.first_attribute(&entry, &self.config.email_attribute),
+            full_name: self.first_attribute(&entry, &self.config.name_attribute),
+            groups: Vec::new(),
+        })
+    }
+
+    // Attribute names are case-insensitive in LDAP
+    fn attribute_values<'e>(&self, entry: &'e SearchEntry, name: &str) -> &'e [String] {
+        entry
+            .attrs
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, values)| values.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn first_attribute(&self, entry: &SearchEntry, name: &str) -> Option<String> {
+        self.attribute_values(entry, name)
+            .first()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn ldap_error(e: LdapError) -> AppError {
+        AppError::InternalServerError(format!("LDAP error: {}", e))
+    }
+}
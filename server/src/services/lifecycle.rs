@@ -0,0 +1,132 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::document::Document;
+use crate::models::lifecycle::{
+    DocumentStatus, NewStatusHistoryEntry, StatusHistoryEntry, StatusTransition,
+};
+use crate::models::permission::PermissionType;
+use crate::schema::{document_status_history, document_status_transitions, documents};
+use crate::services::{PermissionService, RetentionService};
+
+pub struct LifecycleService;
+
+impl LifecycleService {
+    /// Move a document to `target` if a configured transition allows it and the
+    /// user holds the permission that transition requires. Transitions that
+    /// require `admin` are reserved for administrators, not document owners.
+    pub fn change_status(
+        conn: &mut DbConnection,
+        document: &Document,
+        target: DocumentStatus,
+        user_id: Uuid,
+        is_admin: bool,
+        comment: Option<String>,
+    ) -> Result<Document> {
+        if document.is_folder {
+            return Err(AppError::BadRequest("Folders have no lifecycle status".to_string()));
+        }
+
+        RetentionService::ensure_not_on_hold(conn, document)?;
+
+        let transition = document_status_transitions::table
+            .filter(document_status_transitions::from_status.eq(&document.status))
+            .filter(document_status_transitions::to_status.eq(target.as_str()))
+            .select(StatusTransition::as_select())
+            .first::<StatusTransition>(conn)
+            .optional()?
+            .ok_or_else(|| {
+                AppError::BadRequest(format!(
+                    "Transition from '{}' to '{}' is not allowed",
+                    document.status,
+                    target.as_str()
+                ))
+            })?;
+
+        let required_permission = transition.permission_type().ok_or_else(|| {
+            AppError::InternalServerError(format!(
+                "Invalid permission '{}' on status transition",
+                transition.required_permission
+            ))
+        })?;
+
+        // Owners hold every document permission, so `admin` means the global role here
+        let allowed = match required_permission {
+            PermissionType::Admin => is_admin,
+            _ => PermissionService::check_permission(conn, user_id, document.id, required_permission)?,
+        };
+
+        if !allowed {
+            return Err(AppError::Forbidden(format!(
+                "No permission to move this document to '{}'",
+                target.as_str()
+            )));
+        }
+
+        Self::apply_status(conn, document, target, user_id, comment)
+    }
+
+    /// Set the status and record it in the history without checking transitions
+    pub fn apply_status(
+        conn: &mut DbConnection,
+        document: &Document,
+        target: DocumentStatus,
+        user_id: Uuid,
+        comment: Option<String>,
+    ) -> Result<Document> {
+        let updated = conn.transaction::<Document, diesel::result::Error, _>(|conn| {
+            let entry = NewStatusHistoryEntry {
+                document_id: document.id,
+                from_status: document.status.clone(),
+                to_status: target.as_str().to_string(),
+                comment,
+                changed_by: user_id,
+            };
+
+            diesel::insert_into(document_status_history::table)
+                .values(&entry)
+                .execute(conn)?;
+
+            diesel::update(documents::table.find(document.id))
+                .set((
+                    documents::status.eq(target.as_str()),
+                    documents::updated_at.eq(diesel::dsl::now),
+                ))
+                .returning(Document::as_returning())
+                .get_result(conn)
+        })?;
+
+        Ok(updated)
+    }
+
+    /// Status change history of a document, oldest first
+    pub fn get_history(conn: &mut DbConnection, document_id: Uuid) -> Result<Vec<StatusHistoryEntry>> {
+        let history = document_status_history::table
+            .filter(document_status_history::document_id.eq(document_id))
+            .order(document_status_history::changed_at.asc())
+            .select(StatusHistoryEntry::as_select())
+            .load(conn)?;
+
+        Ok(history)
+    }
+
+    /// Fail if the document's status makes it read-only
+    pub fn ensure_editable(document: &Document) -> Result<()> {
+        if Self::is_read_only(document) {
+            return Err(AppError::Forbidden(format!(
+                "Document is {} and read-only; start a new draft to edit it",
+                document.status
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn is_read_only(document: &Document) -> bool {
+        DocumentStatus::from_str(&document.status)
+            .map(|status| status.is_read_only())
+            .unwrap_or(false)
+    }
+}
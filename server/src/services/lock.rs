@@ -0,0 +1,83 @@
+use chrono::Duration;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::document::Document;
+use crate::schema::documents;
+
+/// Default check-out duration: one working day
+pub const DEFAULT_LOCK_MINUTES: i64 = 8 * 60;
+/// Upper bound on a single check-out
+pub const MAX_LOCK_MINUTES: i64 = 7 * 24 * 60;
+
+pub struct LockService;
+
+impl LockService {
+    /// Take an exclusive lock on a document.
+    ///
+    /// Succeeds if the document is unlocked, the previous lock has expired, or
+    /// `user_id` already holds it (which extends the lock).
+    pub fn checkout(
+        conn: &mut DbConnection,
+        document_id: Uuid,
+        user_id: Uuid,
+        duration_minutes: Option<i64>,
+    ) -> Result<Document> {
+        let minutes = duration_minutes.unwrap_or(DEFAULT_LOCK_MINUTES);
+        if minutes <= 0 || minutes > MAX_LOCK_MINUTES {
+            return Err(AppError::BadRequest(format!(
+                "Lock duration must be between 1 and {} minutes",
+                MAX_LOCK_MINUTES
+            )));
+        }
+
+        let now = chrono::Local::now().naive_local();
+        let expires_at = now + Duration::minutes(minutes);
+
+        let locked = diesel::update(
+            documents::table
+                .filter(documents::id.eq(document_id))
+                .filter(
+                    documents::locked_by
+                        .is_null()
+                        .or(documents::locked_by.eq(user_id))
+                        .or(documents::lock_expires_at.lt(now)),
+                ),
+        )
+        .set((
+            documents::locked_by.eq(Some(user_id)),
+            documents::locked_at.eq(Some(now)),
+            documents::lock_expires_at.eq(Some(expires_at)),
+        ))
+        .returning(Document::as_returning())
+        .get_result::<Document>(conn)
+        .optional()?;
+
+        locked.ok_or_else(|| AppError::Locked("Document is checked out by another user".to_string()))
+    }
+
+    /// Release the lock on a document
+    pub fn release(conn: &mut DbConnection, document_id: Uuid) -> Result<Document> {
+        let document = diesel::update(documents::table.find(document_id))
+            .set((
+                documents::locked_by.eq(None::<Uuid>),
+                documents::locked_at.eq(None::<chrono::NaiveDateTime>),
+                documents::lock_expires_at.eq(None::<chrono::NaiveDateTime>),
+            ))
+            .returning(Document::as_returning())
+            .get_result(conn)?;
+
+        Ok(document)
+    }
+
+    /// Fail if the document is checked out by someone other than `user_id`
+    pub fn ensure_not_locked_by_other(document: &Document, user_id: Uuid) -> Result<()> {
+        if document.is_locked_by_other(user_id) {
+            return Err(AppError::Locked("Document is checked out by another user".to_string()));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use chrono::{Duration, NaiveDateTime};
+use diesel::prelude::*;
+
+use crate::config::LoginThrottleConfig;
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::login_failure::LoginFailure;
+use crate::schema::login_failures;
+
+const ACCOUNT_KEY_PREFIX: &str = "user:";
+const IP_KEY_PREFIX: &str = "ip:";
+const MAX_KEY_LENGTH: usize = 150;
+
+/// Slows down password guessing: after a few failures each further attempt
+/// has to wait twice as long, and after many the account or address is locked
+/// for a while. Blocked attempts are turned away before any password hashing.
+pub struct LoginThrottleService;
+
+impl LoginThrottleService {
+    /// Usernames are tracked whether or not the account exists, so the
+    /// responses don't reveal which ones do
+    pub fn account_key(username: &str) -> String {
+        Self::key(ACCOUNT_KEY_PREFIX, &username.trim().to_lowercase())
+    }
+
+    pub fn ip_key(ip_address: &str) -> String {
+        Self::key(IP_KEY_PREFIX, ip_address)
+    }
+
+    /// Fail with `429` while any of the keys has to wait
+    pub fn check(conn: &mut DbConnection, config: &LoginThrottleConfig, keys: &[String]) -> Result<()> {
+        if !config.enabled {
+            return Ok(());
+        }
+
+        let now = chrono::Local::now().naive_local();
+
+        let wait = login_failures::table
+            .filter(login_failures::key.eq_any(keys))
+            .select(LoginFailure::as_select())
+            .load::<LoginFailure>(conn)?
+            .iter()
+            .filter_map(|failure| Self::retry_at(config, failure, now))
+            .max()
+            .map(|retry_at| (retry_at - now).num_seconds().max(1));
+
+        match wait {
+            Some(seconds) => Err(AppError::TooManyRequests(
+                "Too many failed login attempts; try again later".to_string(),
+                seconds as u64,
+            )),
+            None => Ok(()),
+        }
+    }
+
+    pub fn record_failure(conn: &mut DbConnection, config: &LoginThrottleConfig, keys: &[String]) -> Result<()> {
+        if !config.enabled {
+            return Ok(());
+        }
+
+        let now = chrono::Local::now().naive_local();
+        let window_start = now - Duration::seconds(config.failure_window);
+
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            for key in keys {
+                let previous = login_failures::table
+                    .find(key)
+                    .select(LoginFailure::as_select())
+                    .for_update()
+                    .first::<LoginFailure>(conn)
+                    .optional()?;
+
+                let failure_count = match previous {
+                    Some(failure) if failure.last_failure_at > window_start => failure.failure_count + 1,
+                    _ => 1,
+                };
+
+                let (_, lockout_attempts) = Self::limits(config, key);
+                let locked_until = (failure_count >= lockout_attempts)
+                    .then(|| now + Duration::seconds(config.lockout_duration));
+
+                diesel::insert_into(login_failures::table)
+                    .values((
+                        login_failures::key.eq(key),
+                        login_failures::failure_count.eq(failure_count),
+                        login_failures::last_failure_at.eq(now),
+                        login_failures::locked_until.eq(locked_until),
+                    ))
+                    .on_conflict(login_failures::key)
+                    .do_update()
+                    .set((
+                        login_failures::failure_count.eq(failure_count),
+                        login_failures::last_failure_at.eq(now),
+                        login_failures::locked_until.eq(locked_until),
+                    ))
+                    .execute(conn)?;
+            }
+
+            // Counts nobody has added to for a whole window are forgotten
+            diesel::delete(
+                login_failures::table
+                    .filter(login_failures::last_failure_at.lt(window_start))
+                    .filter(
+                        login_failures::locked_until
+                            .is_null()
+                            .or(login_failures::locked_until.lt(now)),
+                    ),
+            )
+            .execute(conn)?;
+
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    /// Forget an account's failures once it has logged in
+    pub fn reset(conn: &mut DbConnection, key: &str) -> Result<()> {
+        diesel::delete(login_failures::table.find(key)).execute(conn)?;
+
+        Ok(())
+    }
+
+    /// When the next attempt is allowed, if not yet
+    fn retry_at(config: &LoginThrottleConfig, failure: &LoginFailure, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        if let Some(locked_until) = failure.locked_until.filter(|locked_until| *locked_until > now) {
+            return Some(locked_until);
+        }
+
+        if failure.last_failure_at < now - Duration::seconds(config.failure_window) {
+            return None;
+        }
+
+        let (free_attempts, _) = Self::limits(config, &failure.key);
+        if failure.failure_count <= free_attempts {
+            return None;
+        }
+
+        let doublings = (failure.failure_count - free_attempts - 1).min(30) as u32;
+        let delay = config.base_delay.saturating_mul(1 << doublings).min(config.max_delay);
+
+        Some(failure.last_failure_at + Duration::seconds(delay)).filter(|retry_at| *retry_at > now)
+    }
+
+    // Many people can share an address behind NAT, so addresses get more attempts than accounts
+    fn limits(config: &LoginThrottleConfig, key: &str) -> (i32, i32) {
+        if key.starts_with(IP_KEY_PREFIX) {
+            (config.ip_free_attempts, config.ip_lockout_attempts)
+        } else {
+            (config.account_free_attempts, config.account_lockout_attempts)
+        }
+    }
+
+    fn key(prefix: &str, value: &str) -> String {
+        format!("{}{}", prefix, value).chars().take(MAX_KEY_LENGTH).collect()
+    }
+}
@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use crate::config::MailConfig;
+use crate::error::{AppError, Result};
+
+#[derive(Debug, Clone)]
+pub struct MailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Outgoing mail, chosen by `MAIL_TRANSPORT`
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, message: MailMessage) -> Result<()>;
+}
+
+/// Build the mailer configured by `MAIL_TRANSPORT`
+pub fn from_config(config: &MailConfig) -> Result<Box<dyn Mailer>> {
+    match config.transport.as_str() {
+        "smtp" => Ok(Box::new(SmtpMailer::new(config)?)),
+        "log" => Ok(Box::new(LogMailer)),
+        other => Err(AppError::InternalServerError(format!(
+            "Unknown MAIL_TRANSPORT: {}",
+            other
+        ))),
+    }
+}
+
+/// Sends through an SMTP server; without credentials it speaks plain SMTP,
+/// which suits a local catcher such as MailHog
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(config: &MailConfig) -> Result<Self> {
+        let from = config
+            .from
+            .parse::<Mailbox>()
+            .map_err(|e| AppError::InternalServerError(format!("Invalid MAIL_FROM: {}", e)))?;
+
+        let transport = match (&config.smtp_username, &config.smtp_password) {
+            (Some(username), Some(password)) => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)
+                    .map_err(|e| AppError::InternalServerError(format!("Invalid SMTP_HOST: {}", e)))?
+                    .port(config.smtp_port)
+                    .credentials(Credentials::new(username.clone(), password.clone()))
+                    .build()
+            }
+            _ => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.smtp_host)
+                .port(config.smtp_port)
+                .build(),
+        };
+
+        Ok(Self { transport, from })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, message: MailMessage) -> Result<()> {
+        let to = message
+            .to
+            .parse::<Mailbox>()
+            .map_err(|e| AppError::BadRequest(format!("Invalid recipient address: {}", e)))?;
+
+        let email = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(message.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(message.body)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to build email: {}", e)))?;
+
+        self.transport
+            .send(email)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to send email: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+/// Writes messages to the log instead of sending them, for development
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, message: MailMessage) -> Result<()> {
+        tracing::info!(
+            "Mail to {}: {}\n{}",
+            message.to,
+            message.subject,
+            message.body
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use serde_json::{Map, Value as JsonValue};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::metadata::{MetadataField, MetadataFieldType, MetadataSchema};
+use crate::schema::{metadata_schema_bindings, metadata_schemas};
+use crate::services::{FolderService, SearchService};
+
+pub struct MetadataService;
+
+impl MetadataService {
+    /// Check a schema's field definitions before saving them
+    pub fn validate_fields(fields: &[MetadataField]) -> Result<()> {
+        let mut names = HashSet::new();
+
+        for field in fields {
+            let valid_name = field
+                .name
+                .chars()
+                .next()
+                .map(|c| c.is_ascii_lowercase())
+                .unwrap_or(false)
+                && field
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+            if !valid_name || field.name.len() > 64 {
+                return Err(AppError::ValidationError(format!(
+                    "Invalid field name '{}': use lowercase letters, digits and underscores",
+                    field.name
+                )));
+            }
+
+            if !names.insert(field.name.as_str()) {
+                return Err(AppError::ValidationError(format!(
+                    "Duplicate field name '{}'",
+                    field.name
+                )));
+            }
+
+            let has_options = field.options.as_ref().map(|o| !o.is_empty()).unwrap_or(false);
+            if field.field_type == MetadataFieldType::Enum && !has_options {
+                return Err(AppError::ValidationError(format!(
+                    "Enum field '{}' needs at least one option",
+                    field.name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The schema that applies to a document in `folder_id` with `mime_type`.
+    ///
+    /// The nearest folder binding wins; otherwise a binding for the exact
+    /// MIME type, then one for its `type/*` family.
+    pub fn resolve_schema(
+        conn: &mut DbConnection,
+        folder_id: Option<Uuid>,
+        mime_type: &str,
+    ) -> Result<Option<MetadataSchema>> {
+        for ancestor_id in FolderService::ancestor_chain(conn, folder_id)? {
+            let schema = metadata_schema_bindings::table
+                .inner_join(metadata_schemas::table)
+                .filter(metadata_schema_bindings::folder_id.eq(ancestor_id))
+                .select(MetadataSchema::as_select())
+                .first::<MetadataSchema>(conn)
+                .optional()?;
+
+            if schema.is_some() {
+                return Ok(schema);
+            }
+        }
+
+        let mut candidates = vec![mime_type.to_string()];
+        if let Some((family, _)) = mime_type.split_once('/') {
+            candidates.push(format!("{}/*", family));
+        }
+
+        for candidate in candidates {
+            let schema = metadata_schema_bindings::table
+                .inner_join(metadata_schemas::table)
+                .filter(metadata_schema_bindings::mime_type.eq(&candidate))
+                .select(MetadataSchema::as_select())
+                .first::<MetadataSchema>(conn)
+                .optional()?;
+
+            if schema.is_some() {
+                return Ok(schema);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Validate metadata for a document and return the value to store.
+    ///
+    /// Without a schema any JSON object is accepted as-is.
+    pub fn validate_for_document(
+        conn: &mut DbConnection,
+        folder_id: Option<Uuid>,
+        mime_type: &str,
+        metadata: Option<JsonValue>,
+    ) -> Result<Option<JsonValue>> {
+        match Self::resolve_schema(conn, folder_id, mime_type)? {
+            Some(schema) => {
+                let metadata = metadata.unwrap_or_else(|| JsonValue::Object(Map::new()));
+                Self::validate(&schema, &metadata).map(Some)
+            }
+            None => match metadata {
+                Some(JsonValue::Object(_)) | None => Ok(metadata),
+                Some(_) => Err(AppError::ValidationError(
+                    "Metadata must be a JSON object".to_string(),
+                )),
+            },
+        }
+    }
+
+    /// Check metadata against a schema, dropping null values
+    pub fn validate(schema: &MetadataSchema, metadata: &JsonValue) -> Result<JsonValue> {
+        let object = metadata.as_object().ok_or_else(|| {
+            AppError::ValidationError("Metadata must be a JSON object".to_string())
+        })?;
+
+        let fields = schema.field_definitions();
+
+        if let Some(unknown) = object.keys().find(|k| !fields.iter().any(|f| &f.name == *k)) {
+            return Err(AppError::ValidationError(format!(
+                "Unknown metadata field '{}' for schema '{}'",
+                unknown, schema.name
+            )));
+        }
+
+        let mut normalized = Map::new();
+
+        for field in &fields {
+            match object.get(&field.name) {
+                None | Some(JsonValue::Null) => {
+                    if field.required {
+                        return Err(AppError::ValidationError(format!(
+                            "Metadata field '{}' is required",
+                            field.name
+                        )));
+                    }
+                }
+                Some(value) => {
+                    Self::validate_value(field, value)?;
+                    normalized.insert(field.name.clone(), value.clone());
+                }
+            }
+        }
+
+        Ok(JsonValue::Object(normalized))
+    }
+
+    fn validate_value(field: &MetadataField, value: &JsonValue) -> Result<()> {
+        let valid = match field.field_type {
+            MetadataFieldType::String => value.is_string(),
+            MetadataFieldType::Number => value.is_number(),
+            MetadataFieldType::Integer => value.is_i64() || value.is_u64(),
+            MetadataFieldType::Boolean => value.is_boolean(),
+            MetadataFieldType::Date => value
+                .as_str()
+                .map(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok())
+                .unwrap_or(false),
+            MetadataFieldType::Enum => value
+                .as_str()
+                .map(|s| {
+                    field
+                        .options
+                        .as_ref()
+                        .map(|options| options.iter().any(|o| o == s))
+                        .unwrap_or(false)
+                })
+                .unwrap_or(false),
+        };
+
+        if !valid {
+            let expected = match field.field_type {
+                MetadataFieldType::Date => "a date (YYYY-MM-DD)".to_string(),
+                MetadataFieldType::Enum => format!(
+                    "one of: {}",
+                    field.options.clone().unwrap_or_default().join(", ")
+                ),
+                other => format!("{:?}", other).to_lowercase(),
+            };
+
+            return Err(AppError::ValidationError(format!(
+                "Metadata field '{}' must be {}",
+                field.name, expected
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Register every schema field marked filterable or sortable with the search index
+    pub async fn sync_search_attributes(
+        conn: &mut DbConnection,
+        search_service: &SearchService,
+    ) -> Result<()> {
+        let schemas = metadata_schemas::table
+            .select(MetadataSchema::as_select())
+            .load::<MetadataSchema>(conn)?;
+
+        let mut filterable = Vec::new();
+        let mut sortable = Vec::new();
+
+        for field in schemas.iter().flat_map(|s| s.field_definitions()) {
+            let attribute = format!("metadata.{}", field.name);
+
+            if field.filterable && !filterable.contains(&attribute) {
+                filterable.push(attribute.clone());
+            }
+            if field.sortable && !sortable.contains(&attribute) {
+                sortable.push(attribute);
+            }
+        }
+
+        search_service
+            .set_attribute_settings(&filterable, &sortable)
+            .await
+    }
+}
@@ -2,9 +2,60 @@ pub mod storage;
 pub mod search;
 pub mod onlyoffice;
 pub mod permission;
+pub mod quota;
+pub mod lock;
+pub mod version;
+pub mod lifecycle;
+pub mod approval;
+pub mod retention;
+pub mod folder;
+pub mod metadata;
+pub mod tag;
+pub mod thumbnail;
+pub mod rendition;
+pub mod editor_session;
+pub mod template;
+pub mod session;
+pub mod mailer;
+pub mod password_policy;
+pub mod password_reset;
+pub mod totp;
+pub mod oidc;
+pub mod group_sync;
+pub mod ldap;
+pub mod api_token;
+pub mod user_admin;
+pub mod registration;
+pub mod login_throttle;
+pub mod rate_limit;
 
 pub use storage::StorageService;
 pub use search::SearchService;
 pub use onlyoffice::OnlyOfficeService;
 pub use permission::PermissionService;
+pub use quota::QuotaService;
+pub use lock::LockService;
+pub use version::VersionService;
+pub use lifecycle::LifecycleService;
+pub use approval::ApprovalService;
+pub use retention::RetentionService;
+pub use folder::FolderService;
+pub use metadata::MetadataService;
+pub use tag::TagService;
+pub use thumbnail::ThumbnailService;
+pub use rendition::RenditionService;
+pub use editor_session::EditorSessionService;
+pub use template::TemplateService;
+pub use session::SessionService;
+pub use password_policy::PasswordPolicyService;
+pub use password_reset::PasswordResetService;
+pub use totp::TotpService;
+pub use oidc::OidcService;
+pub use group_sync::GroupSyncService;
+pub use ldap::LdapService;
+pub use api_token::ApiTokenService;
+pub use user_admin::UserAdminService;
+pub use registration::RegistrationService;
+pub use login_throttle::LoginThrottleService;
+pub use rate_limit::RateLimiter;
 
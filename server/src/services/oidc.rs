@@ -0,0 +1,473 @@
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::Duration;
+use diesel::prelude::*;
+use openidconnect::core::{CoreAuthenticationFlow, CoreClient, CoreProviderMetadata};
+use openidconnect::{
+    AccessTokenHash, AuthorizationCode, ClientId, ClientSecret, CsrfToken, EndpointMaybeSet,
+    EndpointNotSet, EndpointSet, IssuerUrl, Nonce, OAuth2TokenResponse, PkceCodeChallenge,
+    PkceCodeVerifier, RedirectUrl, Scope, TokenResponse,
+};
+use uuid::Uuid;
+
+use crate::config::OidcConfig;
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::user::{NewUser, User, UserRole};
+use crate::schema::{oidc_login_requests, users};
+use crate::utils::{generate_token, hash_password, hash_token};
+
+// 用户在 IdP 登录页面停留的最长时间
+const LOGIN_REQUEST_MINUTES: i64 = 10;
+
+type OidcClient = CoreClient<
+    EndpointSet,
+    EndpointNotSet,
+    EndpointNotSet,
+    EndpointNotSet,
+    EndpointMaybeSet,
+    EndpointMaybeSet,
+>;
+
+/// The verified identity from an ID token
+#[derive(Debug)]
+pub struct OidcIdentity {
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+    pub full_name: Option<String>,
+    /// None when group sync is turned off
+    pub groups: Option<Vec<String>>,
+}
+
+pub struct OidcService {
+    config: Arc<OidcConfig>,
+}
+
+impl OidcService {
+    pub fn new(config: &OidcConfig) -> Result<Self> {
+        if !config.enabled {
+            return Err(AppError::NotFound("Single sign-on is not enabled".to_string()));
+        }
+
+        Ok(Self {
+            config: Arc::new(config.clone()),
+        })
+    }
+
+    /// Start a login: remember the PKCE verifier and nonce, and return the
+    /// identity provider URL to send the browser to, along with the login
+    /// request ID the browser has to present when it comes back
+    pub async fn authorization_url(&self, conn: &mut DbConnection) -> Result<(String, String)> {
+        let (client, _) = self.client().await?;
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+        let mut request = client
+            .authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                CsrfToken::new_random,
+                Nonce::new_random,
+            )
+            .set_pkce_challenge(pkce_challenge);
+
+        for scope in self.config.scopes.iter().filter(|scope| scope.as_str() != "openid") {
+            request = request.add_scope(Scope::new(scope.clone()));
+        }
+
+        let (url, state, nonce) = request.url();
+        let login_request_id = generate_token();
+        let expires_at = chrono::Local::now().naive_local() + Duration::minutes(LOGIN_REQUEST_MINUTES);
+
+        // Abandoned logins are cleaned up as new ones start
+        diesel::delete(oidc_login_requests::table.filter(oidc_login_requests::expires_at.lt(diesel::dsl::now)))
+            .execute(conn)?;
+
+        diesel::insert_into(oidc_login_requests::table)
+            .values((
+                oidc_login_requests::state_hash.eq(hash_token(state.secret())),
+                oidc_login_requests::pkce_verifier.eq(pkce_verifier.secret()),
+                oidc_login_requests::nonce.eq(nonce.secret()),
+                oidc_login_requests::expires_at.eq(expires_at),
+                oidc_login_requests::login_request_hash.eq(hash_token(&login_request_id)),
+            ))
+            .execute(conn)?;
+
+        Ok((url.to_string(), login_request_id))
+    }
+
+    /// Finish a login: exchange the authorization code and verify the ID token.
+    ///
+    /// The state only counts together with the login request ID of the
+    /// browser that started the login, so nobody can slip their own
+    /// authorization response to someone else and log them in as themselves.
+    pub async fn complete(
+        &self,
+        conn: &mut DbConnection,
+        code: &str,
+        state: &str,
+        login_request_id: &str,
+    ) -> Result<OidcIdentity> {
+        // Each state is good for one callback
+        let (pkce_verifier, nonce) = diesel::delete(
+            oidc_login_requests::table
+                .filter(oidc_login_requests::state_hash.eq(hash_token(state)))
+                .filter(oidc_login_requests::login_request_hash.eq(hash_token(login_request_id)))
+                .filter(oidc_login_requests::expires_at.gt(diesel::dsl::now)),
+        )
+        .returning((oidc_login_requests::pkce_verifier, oidc_login_requests::nonce))
+        .get_result::<(String, String)>(conn)
+        .optional()?
+        .ok_or_else(|| AppError::Unauthorized("Invalid or expired login request".to_string()))?;
+
+        self.exchange(code, pkce_verifier, nonce).await
+    }
+
+    async fn exchange(&self, code: &str, pkce_verifier: String, nonce: String) -> Result<OidcIdentity> {
+        let (client, http_client) = self.client().await?;
+
+        let token_response = client
+            .exchange_code(AuthorizationCode::new(code.to_string()))
+            .map_err(|e| AppError::InternalServerError(format!("Identity provider has no token endpoint: {}", e)))?
+            .set_pkce_verifier(PkceCodeVerifier::new(pkce_verifier))
+            .request_async(&http_client)
+            .await
+            .map_err(|e| AppError::Unauthorized(format!("Failed to exchange authorization code: {}", e)))?;
+
+        let id_token = token_response
+            .id_token()
+            .ok_or_else(|| AppError::Unauthorized("Identity provider returned no ID token".to_string()))?;
+
+        let verifier = client.id_token_verifier();
+        let claims = id_token
+            .claims(&verifier, &Nonce::new(nonce))
+            .map_err(|e| AppError::Unauthorized(format!("Invalid ID token: {}", e)))?;
+
+        // The access token must be the one the ID token was issued with
+        if let Some(expected_hash) = claims.access_token_hash() {
+            let invalid = |e: String| AppError::Unauthorized(format!("Invalid access token: {}", e));
+            let actual_hash = AccessTokenHash::from_token(
+                token_response.access_token(),
+                id_token.signing_alg().map_err(|e| invalid(e.to_string()))?,
+                id_token.signing_key(&verifier).map_err(|e| invalid(e.to_string()))?,
+            )
+            .map_err(|e| invalid(e.to_string()))?;
+
+            if actual_hash != *expected_hash {
+                return Err(invalid("hash mismatch".to_string()));
+            }
+        }
+
+        let groups = match &self.config.groups_claim {
+            Some(claim) => Some(Self::string_list_claim(&id_token.to_string(), claim)?),
+            None => None,
+        };
+
+        Ok(OidcIdentity {
+            subject: claims.subject().to_string(),
+            email: claims.email().map(|email| email.to_string()),
+            email_verified: claims.email_verified().unwrap_or(false),
+            preferred_username: claims.preferred_username().map(|username| username.to_string()),
+            full_name: claims.name().and_then(|name| name.get(None)).map(|name| name.to_string()),
+            groups,
+        })
+    }
+
+    /// The local user for an identity: linked by subject, then by email, or newly created
+    pub fn find_or_create_user(conn: &mut DbConnection, config: &OidcConfig, identity: &OidcIdentity) -> Result<User> {
+        let linked = users::table
+            .filter(users::oidc_subject.eq(&identity.subject))
+            .first::<User>(conn)
+            .optional()?;
+
+        if let Some(user) = linked {
+            return Ok(user);
+        }
+
+        let email = identity
+            .email
+            .as_deref()
+            .ok_or_else(|| AppError::Unauthorized("Identity provider did not return an email address".to_string()))?;
+
+        // Linking by email hands over the existing account, so the address must be verified
+        if identity.email_verified || config.trust_unverified_email {
+            let user = diesel::update(
+                users::table
+                    .filter(users::email.eq(email))
+                    .filter(users::oidc_subject.is_null())
+                    .filter(users::is_service_account.eq(false)),
+            )
+            .set((
+                users::oidc_subject.eq(&identity.subject),
+                users::updated_at.eq(diesel::dsl::now),
+            ))
+            .returning(User::as_returning())
+            .get_result(conn)
+            .optional()?;
+
+            if let Some(user) = user {
+                return Ok(user);
+            }
+        }
+
+        let email_taken = diesel::select(diesel::dsl::exists(users::table.filter(users::email.eq(email))))
+            .get_result::<bool>(conn)?;
+
+        if email_taken {
+            return Err(AppError::Unauthorized(
+                "An account with this email address already exists and can't be linked automatically".to_string(),
+            ));
+        }
+
+        let base_username = identity
+            .preferred_username
+            .clone()
+            .unwrap_or_else(|| email.split('@').next().unwrap_or(email).to_string());
+        let username = Self::available_username(conn, &base_username)?;
+
+        let new_user = NewUser {
+            username,
+            email: email.to_string(),
+            // Nobody knows this password; the account logs in through the identity provider
+            password_hash: hash_password(&generate_token())?,
+            full_name: identity.full_name.clone(),
+            role: UserRole::User.as_str().to_string(),
+        };
+
+        let user = diesel::insert_into(users::table)
+            .values((&new_user, users::oidc_subject.eq(&identity.subject)))
+            .returning(User::as_returning())
+            .get_result(conn)?;
+
+        Ok(user)
+    }
+
+    async fn client(&self) -> Result<(OidcClient, reqwest::Client)> {
+        // Following redirects would let a compromised provider point us anywhere
+        let http_client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to build HTTP client: {}", e)))?;
+
+        let issuer_url = IssuerUrl::new(self.config.issuer_url.clone())
+            .map_err(|e| AppError::InternalServerError(format!("Invalid OIDC issuer URL: {}", e)))?;
+
+        let metadata = CoreProviderMetadata::discover_async(issuer_url, &http_client)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("OIDC discovery failed: {}", e)))?;
+
+        let redirect_url = RedirectUrl::new(self.config.redirect_url.clone())
+            .map_err(|e| AppError::InternalServerError(format!("Invalid OIDC redirect URL: {}", e)))?;
+
+        let client = CoreClient::from_provider_metadata(
+            metadata,
+            ClientId::new(self.config.client_id.clone()),
+            self.config.client_secret.clone().map(ClientSecret::new),
+        )
+        .set_redirect_uri(redirect_url);
+
+        Ok((client, http_client))
+    }
+
+    // The core claim types drop non-standard claims, so read them from the
+    // token payload; only call this once the token has been verified
+    fn string_list_claim(id_token: &str, claim: &str) -> Result<Vec<String>> {
+        let invalid = || AppError::Unauthorized("Malformed ID token".to_string());
+
+        let payload = id_token.split('.').nth(1).ok_or_else(invalid)?;
+        let payload = URL_SAFE_NO_PAD.decode(payload).map_err(|_| invalid())?;
+        let payload: serde_json::Value = serde_json::from_slice(&payload).map_err(|_| invalid())?;
+
+        let values = match payload.get(claim) {
+            Some(serde_json::Value::Array(values)) => values
+                .iter()
+                .filter_map(|value| value.as_str().map(|value| value.to_string()))
+                .collect(),
+            Some(serde_json::Value::String(value)) => vec![value.clone()],
+            _ => Vec::new(),
+        };
+
+        Ok(values)
+    }
+
+    // Usernames from the identity provider may already be taken locally
+    fn available_username(conn: &mut DbConnection, base: &str) -> Result<String> {
+        let base: String = base
+            .chars()
+            .filter(|c| c.is_alphanumeric() || matches!(c, '.' | '_' | '-'))
+            .take(90)
+            .collect();
+        let base = if base.chars().count() < 3 { format!("user-{}", base) } else { base };
+
+        for suffix in 1..100 {
+            let candidate = if suffix == 1 { base.clone() } else { format!("{}-{}", base, suffix) };
+
+            let taken = diesel::select(diesel::dsl::exists(users::table.filter(users::username.eq(&candidate))))
+                .get_result::<bool>(conn)?;
+
+            if !taken {
+                return Ok(candidate);
+            }
+        }
+
+        Ok(format!("{}-{}", base, &Uuid::new_v4().simple().to_string()[..8]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use axum::{
+        extract::State,
+        routing::{get, post},
+        Json, Router,
+    };
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+    use serde_json::{json, Value as JsonValue};
+
+    use super::*;
+
+    const CLIENT_ID: &str = "dms";
+    const CLIENT_SECRET: &str = "a-client-secret-long-enough-for-hs256";
+    const PKCE_VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    const NONCE: &str = "n-0S6_WzA2Mj";
+
+    /// Stands in for the identity provider: discovery, keys and the token endpoint
+    struct MockIdentityProvider {
+        issuer: String,
+        // Key the ID token is signed with; the client secret unless a test forges one
+        signing_secret: String,
+        // Form bodies posted to the token endpoint
+        token_requests: Mutex<Vec<String>>,
+    }
+
+    async fn discovery(State(idp): State<Arc<MockIdentityProvider>>) -> Json<JsonValue> {
+        Json(json!({
+            "issuer": idp.issuer,
+            "authorization_endpoint": format!("{}/authorize", idp.issuer),
+            "token_endpoint": format!("{}/token", idp.issuer),
+            "jwks_uri": format!("{}/jwks", idp.issuer),
+            "response_types_supported": ["code"],
+            "subject_types_supported": ["public"],
+            "id_token_signing_alg_values_supported": ["HS256"],
+        }))
+    }
+
+    async fn jwks() -> Json<JsonValue> {
+        Json(json!({ "keys": [] }))
+    }
+
+    async fn token(State(idp): State<Arc<MockIdentityProvider>>, body: String) -> Json<JsonValue> {
+        idp.token_requests.lock().unwrap().push(body);
+
+        let now = chrono::Utc::now().timestamp();
+        let claims = json!({
+            "iss": idp.issuer,
+            "aud": CLIENT_ID,
+            "sub": "idp-user-1",
+            "iat": now,
+            "exp": now + 300,
+            "nonce": NONCE,
+            "email": "alice@example.com",
+            "email_verified": true,
+            "preferred_username": "alice",
+            "name": "Alice",
+            "groups": ["财务部", "Staff"],
+        });
+        let id_token = encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(idp.signing_secret.as_bytes()),
+        )
+        .unwrap();
+
+        Json(json!({
+            "access_token": "access-token",
+            "token_type": "Bearer",
+            "expires_in": 300,
+            "id_token": id_token,
+        }))
+    }
+
+    async fn start(signing_secret: &str) -> (OidcService, Arc<MockIdentityProvider>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+
+        let idp = Arc::new(MockIdentityProvider {
+            issuer: issuer.clone(),
+            signing_secret: signing_secret.to_string(),
+            token_requests: Mutex::new(Vec::new()),
+        });
+
+        let app = Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/jwks", get(jwks))
+            .route("/token", post(token))
+            .with_state(idp.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let service = OidcService::new(&OidcConfig {
+            enabled: true,
+            issuer_url: issuer,
+            client_id: CLIENT_ID.to_string(),
+            client_secret: Some(CLIENT_SECRET.to_string()),
+            redirect_url: "http://dms.test/oidc/callback".to_string(),
+            scopes: vec!["openid".to_string(), "email".to_string()],
+            groups_claim: Some("groups".to_string()),
+            trust_unverified_email: false,
+            trust_idp_mfa: false,
+        })
+        .unwrap();
+
+        (service, idp)
+    }
+
+    #[tokio::test]
+    async fn exchange_returns_the_verified_identity() {
+        let (service, idp) = start(CLIENT_SECRET).await;
+
+        let identity = service
+            .exchange("auth-code", PKCE_VERIFIER.to_string(), NONCE.to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(identity.subject, "idp-user-1");
+        assert_eq!(identity.email.as_deref(), Some("alice@example.com"));
+        assert!(identity.email_verified);
+        assert_eq!(identity.preferred_username.as_deref(), Some("alice"));
+        assert_eq!(identity.full_name.as_deref(), Some("Alice"));
+        assert_eq!(identity.groups, Some(vec!["财务部".to_string(), "Staff".to_string()]));
+
+        // The code is redeemed together with the PKCE verifier of its login request
+        let requests = idp.token_requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("code=auth-code"));
+        assert!(requests[0].contains(&format!("code_verifier={}", PKCE_VERIFIER)));
+    }
+
+    #[tokio::test]
+    async fn exchange_rejects_an_id_token_for_another_login_request() {
+        let (service, _) = start(CLIENT_SECRET).await;
+
+        let error = service
+            .exchange("auth-code", PKCE_VERIFIER.to_string(), "another-nonce".to_string())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, AppError::Unauthorized(_)), "{:?}", error);
+    }
+
+    #[tokio::test]
+    async fn exchange_rejects_a_forged_id_token() {
+        let (service, _) = start("not-the-client-secret-of-this-client").await;
+
+        let error = service
+            .exchange("auth-code", PKCE_VERIFIER.to_string(), NONCE.to_string())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, AppError::Unauthorized(_)), "{:?}", error);
+    }
+}
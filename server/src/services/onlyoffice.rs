@@ -1,9 +1,19 @@
-use jsonwebtoken::{encode, EncodingKey, Header};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
 use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
 use crate::error::{AppError, Result};
+use crate::models::permission::PermissionType;
+
+// 转换服务轮询间隔与最长等待时间
+const CONVERSION_POLL_INTERVAL_MS: u64 = 1000;
+const CONVERSION_TIMEOUT_SECS: u64 = 120;
+
+// 命令服务返回的错误码：4 表示文档自上次保存后没有修改
+const COMMAND_NO_CHANGES: i32 = 4;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OnlyOfficeEditorResponse {
@@ -37,6 +47,86 @@ pub struct OnlyOfficePermissions {
     pub print: bool,
     pub review: bool,
     pub comment: bool,
+    #[serde(rename = "fillForms")]
+    pub fill_forms: bool,
+}
+
+/// What a user may do in the editor.
+///
+/// Document Server opens a document for commenting, review or form filling
+/// only when `edit` is false and the narrower flag is set, so the flags are
+/// passed through as they are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EditorAccess {
+    pub edit: bool,
+    pub review: bool,
+    pub comment: bool,
+    pub fill_forms: bool,
+    pub download: bool,
+    pub print: bool,
+}
+
+impl EditorAccess {
+    /// Access from a user's document permissions.
+    ///
+    /// `modifiable` is false while the file must not change (checked out by
+    /// someone else, read-only status, legal hold); every level that saves
+    /// a new version is then withheld.
+    pub fn from_permissions(granted: &[PermissionType], modifiable: bool, file_type: &str) -> Self {
+        let has = |permission: PermissionType| granted.contains(&permission);
+        let edit = modifiable && has(PermissionType::Write);
+        let review = modifiable && (edit || has(PermissionType::Review));
+
+        Self {
+            edit,
+            review,
+            comment: modifiable && (review || has(PermissionType::Comment)),
+            fill_forms: modifiable
+                && file_type == "pdf"
+                && (edit || has(PermissionType::FillForms)),
+            download: true,
+            print: true,
+        }
+    }
+
+    /// Access through a share link; view-only links cannot download or print
+    pub fn for_share_link(permission: PermissionType, modifiable: bool, file_type: &str) -> Self {
+        let granted = match permission {
+            PermissionType::Read => vec![],
+            PermissionType::Comment | PermissionType::Review | PermissionType::FillForms => vec![permission],
+            // Delete, share and admin links are treated as write links
+            _ => vec![PermissionType::Write],
+        };
+        let view_only = permission == PermissionType::Read;
+
+        Self {
+            download: !view_only,
+            print: !view_only,
+            ..Self::from_permissions(&granted, modifiable, file_type)
+        }
+    }
+
+    pub fn can_read(granted: &[PermissionType]) -> bool {
+        granted.iter().any(|permission| {
+            matches!(
+                permission,
+                PermissionType::Read
+                    | PermissionType::Comment
+                    | PermissionType::Review
+                    | PermissionType::FillForms
+                    | PermissionType::Write
+            )
+        })
+    }
+
+    /// Document Server only applies the narrower flags in edit mode
+    pub fn mode(&self) -> &'static str {
+        if self.edit || self.review || self.comment || self.fill_forms {
+            "edit"
+        } else {
+            "view"
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -51,42 +141,123 @@ pub struct OnlyOfficeCallbackData {
     pub status: i32,
     pub url: Option<String>,
     pub users: Option<Vec<String>>,
+    pub actions: Option<Vec<OnlyOfficeCallbackAction>>,
+    /// What triggered a force save (status 6/7): 0 command, 1 button, 2 timer, 3 form submission
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forcesavetype: Option<i32>,
+    /// Archive of the changes since the previous version, for the history panel
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changesurl: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<JsonValue>,
+    /// Signed copy of the callback body, when sent in the body rather than the header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Request body of the Document Server conversion API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversionRequest {
+    #[serde(rename = "async")]
+    pub is_async: bool,
+    pub filetype: String,
+    pub key: String,
+    pub outputtype: String,
+    pub title: String,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversionResponse {
+    #[serde(rename = "endConvert", default)]
+    pub end_convert: bool,
+    #[serde(rename = "fileUrl")]
+    pub file_url: Option<String>,
+    #[serde(rename = "fileType")]
+    pub file_type: Option<String>,
+    pub percent: Option<i32>,
+    pub error: Option<i32>,
+}
+
+/// Request body of the Document Server command service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandRequest {
+    pub c: String,
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandResponse {
+    pub error: i32,
+    pub key: Option<String>,
+}
+
+/// A user connecting to (type 1) or disconnecting from (type 0) the document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnlyOfficeCallbackAction {
+    #[serde(rename = "type")]
+    pub action_type: i32,
+    pub userid: String,
+}
+
+impl OnlyOfficeCallbackData {
+    /// Version comment describing how the file was saved
+    pub fn save_comment(&self) -> &'static str {
+        match (self.status, self.forcesavetype) {
+            (6, Some(0)) => "Force-saved via OnlyOffice command",
+            (6, Some(1)) => "Force-saved from the OnlyOffice editor",
+            (6, Some(2)) => "Force-saved by OnlyOffice autosave timer",
+            (6, Some(3)) => "Form submitted via OnlyOffice",
+            (6, _) => "Force-saved via OnlyOffice",
+            _ => "Updated via OnlyOffice",
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct OnlyOfficeService {
     config: Arc<crate::config::OnlyOfficeConfig>,
+    conversion_poll_interval: Duration,
+    conversion_timeout: Duration,
 }
 
 impl OnlyOfficeService {
     pub fn new(config: &crate::config::OnlyOfficeConfig) -> Self {
         Self {
             config: Arc::new(config.clone()),
+            conversion_poll_interval: Duration::from_millis(CONVERSION_POLL_INTERVAL_MS),
+            conversion_timeout: Duration::from_secs(CONVERSION_TIMEOUT_SECS),
         }
     }
 
     pub fn generate_editor_config(
         &self,
         document_id: Uuid,
+        document_key: &str,
         document_name: &str,
         file_type: &str,
         file_url: &str,
-        user_id: Uuid,
+        user_id: &str,
         user_name: &str,
-        can_edit: bool,
+        access: EditorAccess,
         app_url: &str,
     ) -> Result<OnlyOfficeEditorResponse> {
         let permissions = OnlyOfficePermissions {
-            download: true,
-            edit: can_edit,
-            print: true,
-            review: can_edit,
-            comment: can_edit,
+            download: access.download,
+            edit: access.edit,
+            print: access.print,
+            review: access.review,
+            comment: access.comment,
+            fill_forms: access.fill_forms,
         };
 
         let document = OnlyOfficeDocumentConfig {
             file_type: file_type.to_string(),
-            key: document_id.to_string(),
+            key: document_key.to_string(),
             title: document_name.to_string(),
             url: file_url.to_string(),
             permissions,
@@ -94,7 +265,7 @@ impl OnlyOfficeService {
 
         let editor_config = OnlyOfficeEditorConfig {
             callback_url: format!("{}/api/onlyoffice/callback/{}", app_url, document_id),
-            mode: if can_edit { "edit".to_string() } else { "view".to_string() },
+            mode: access.mode().to_string(),
             user: OnlyOfficeUser {
                 id: user_id.to_string(),
                 name: user_name.to_string(),
@@ -108,6 +279,125 @@ impl OnlyOfficeService {
         })
     }
 
+    /// Key identifying a document version to Document Server.
+    ///
+    /// Document Server caches edited files by key, so it changes with
+    /// every saved version.
+    pub fn document_key(document_id: Uuid, version: i32) -> String {
+        format!("{}_{}", document_id, version)
+    }
+
+    /// Split a key from `document_key` into document ID and version
+    pub fn parse_document_key(key: &str) -> Option<(Uuid, i32)> {
+        let (id, version) = key.rsplit_once('_')?;
+        Some((Uuid::parse_str(id).ok()?, version.parse().ok()?))
+    }
+
+    /// Editor user ID for an anonymous editor opening a share link.
+    ///
+    /// Each call gets a distinct suffix so co-editors on one link are told apart.
+    pub fn share_user_id(share_link_id: Uuid) -> String {
+        format!("share_{}_{}", share_link_id, &Uuid::new_v4().simple().to_string()[..8])
+    }
+
+    /// Share link ID from an editor user ID made by `share_user_id`
+    pub fn parse_share_user_id(user_id: &str) -> Option<Uuid> {
+        let (link_id, _) = user_id.strip_prefix("share_")?.rsplit_once('_')?;
+        Uuid::parse_str(link_id).ok()
+    }
+
+    /// Verify the JWT Document Server attached to a callback and return the
+    /// callback data from its claims.
+    ///
+    /// The token is taken from the body, or else from the `Authorization`
+    /// header where the claims wrap the body in a `payload` field.
+    pub fn verify_callback(
+        &self,
+        body: &OnlyOfficeCallbackData,
+        authorization: Option<&str>,
+    ) -> Result<OnlyOfficeCallbackData> {
+        let (token, from_header) = match (&body.token, authorization) {
+            (Some(token), _) => (token.as_str(), false),
+            (None, Some(header)) => (header.strip_prefix("Bearer ").unwrap_or(header), true),
+            (None, None) => {
+                return Err(AppError::Unauthorized("Missing OnlyOffice callback token".to_string()));
+            }
+        };
+
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.required_spec_claims.clear();
+
+        let claims = decode::<JsonValue>(
+            token,
+            &DecodingKey::from_secret(self.config.jwt_secret.as_bytes()),
+            &validation,
+        )
+        .map_err(|e| AppError::Unauthorized(format!("Invalid OnlyOffice callback token: {}", e)))?
+        .claims;
+
+        let payload = match (from_header, claims.get("payload")) {
+            (true, Some(payload)) => payload.clone(),
+            _ => claims,
+        };
+
+        let verified: OnlyOfficeCallbackData = serde_json::from_value(payload)
+            .map_err(|e| AppError::Unauthorized(format!("Invalid OnlyOffice callback token payload: {}", e)))?;
+
+        if verified.key != body.key || verified.status != body.status || verified.url != body.url {
+            return Err(AppError::Unauthorized(
+                "OnlyOffice callback body does not match its token".to_string(),
+            ));
+        }
+
+        Ok(verified)
+    }
+
+    /// Download a file Document Server produced (an edited or converted file),
+    /// refusing URLs outside the configured Document Server
+    pub async fn download_server_file(&self, url: &str) -> Result<Vec<u8>> {
+        let url = reqwest::Url::parse(url)
+            .map_err(|e| AppError::BadRequest(format!("Invalid download URL: {}", e)))?;
+        let server = reqwest::Url::parse(&self.config.server)
+            .map_err(|e| AppError::InternalServerError(format!("Invalid ONLYOFFICE_SERVER: {}", e)))?;
+
+        let same_origin = matches!(url.scheme(), "http" | "https")
+            && url.host_str() == server.host_str()
+            && url.port_or_known_default() == server.port_or_known_default();
+
+        if !same_origin {
+            return Err(AppError::Forbidden(
+                "Download URL is not on the configured OnlyOffice server".to_string(),
+            ));
+        }
+
+        // A redirect could lead anywhere, so don't follow it
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to create HTTP client: {}", e)))?;
+
+        let response = client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to download file from Document Server: {}", e)))?;
+
+        // A redirect isn't an error status, but its body isn't the file either
+        if !response.status().is_success() {
+            return Err(AppError::InternalServerError(format!(
+                "Failed to download file from Document Server: {}",
+                response.status()
+            )));
+        }
+
+        let data = response
+            .bytes()
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to read file data: {}", e)))?;
+
+        Ok(data.to_vec())
+    }
+
     pub fn generate_jwt_token(&self, config: &OnlyOfficeEditorResponse) -> Result<String> {
         let token = encode(
             &Header::default(),
@@ -119,6 +409,61 @@ impl OnlyOfficeService {
         Ok(token)
     }
 
+    /// Sign arbitrary data for Document Server, e.g. `setHistoryData` payloads
+    pub fn sign(&self, claims: &JsonValue) -> Result<String> {
+        encode(
+            &Header::default(),
+            claims,
+            &EncodingKey::from_secret(self.config.jwt_secret.as_bytes()),
+        )
+        .map_err(|e| AppError::InternalServerError(format!("Failed to generate OnlyOffice JWT: {}", e)))
+    }
+
+    /// Ask Document Server to save an open editing session without closing it.
+    ///
+    /// Returns false when the session has no changes since its last save.
+    /// The saved file arrives later through a status 6 callback.
+    pub async fn forcesave(&self, key: &str) -> Result<bool> {
+        let mut request = CommandRequest {
+            c: "forcesave".to_string(),
+            key: key.to_string(),
+            token: None,
+        };
+
+        let token = encode(
+            &Header::default(),
+            &request,
+            &EncodingKey::from_secret(self.config.jwt_secret.as_bytes()),
+        )
+        .map_err(|e| AppError::InternalServerError(format!("Failed to sign command request: {}", e)))?;
+        request.token = Some(token.clone());
+
+        let endpoint = format!(
+            "{}/coauthoring/CommandService.ashx",
+            self.config.server.trim_end_matches('/')
+        );
+
+        let response = reqwest::Client::new()
+            .post(&endpoint)
+            .bearer_auth(&token)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Command request failed: {}", e)))?
+            .error_for_status()
+            .map_err(|e| AppError::InternalServerError(format!("Command request failed: {}", e)))?
+            .json::<CommandResponse>()
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Invalid command response: {}", e)))?;
+
+        match response.error {
+            0 => Ok(true),
+            COMMAND_NO_CHANGES => Ok(false),
+            1 => Err(AppError::BadRequest("Document is not open in the editor".to_string())),
+            code => Err(AppError::InternalServerError(format!("Force save failed with error {}", code))),
+        }
+    }
+
     pub fn get_file_type_from_extension(extension: &str) -> &str {
         match extension.to_lowercase().as_str() {
             "docx" | "doc" => "docx",
@@ -135,11 +480,120 @@ impl OnlyOfficeService {
             "docx" | "doc" | "txt" => "word",
             "xlsx" | "xls" => "cell",
             "pptx" | "ppt" => "slide",
-            "pdf" => "word", // PDF 当作只读文档处理
+            "pdf" => "pdf", // 需要 Document Server 8.1 及以上版本，支持 PDF 表单填写
             _ => "word",
         }
     }
 
+    /// Output formats the conversion API can produce from a file type
+    pub fn conversion_targets(file_type: &str) -> &'static [&'static str] {
+        match file_type.to_lowercase().as_str() {
+            "docx" | "doc" | "odt" | "rtf" | "txt" => &["pdf", "docx", "odt", "rtf", "txt"],
+            "xlsx" | "xls" | "ods" | "csv" => &["pdf", "xlsx", "ods", "csv"],
+            "pptx" | "ppt" | "odp" => &["pdf", "pptx", "odp"],
+            _ => &[],
+        }
+    }
+
+    pub fn mime_type_for_file_type(file_type: &str) -> &'static str {
+        match file_type {
+            "pdf" => "application/pdf",
+            "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+            "odt" => "application/vnd.oasis.opendocument.text",
+            "ods" => "application/vnd.oasis.opendocument.spreadsheet",
+            "odp" => "application/vnd.oasis.opendocument.presentation",
+            "rtf" => "application/rtf",
+            "txt" => "text/plain",
+            "csv" => "text/csv",
+            _ => "application/octet-stream",
+        }
+    }
+
+    /// Convert a file with the Document Server conversion API and download the result.
+    ///
+    /// `key` identifies the source revision; Document Server caches
+    /// conversions by key, so it must change whenever the file does.
+    pub async fn convert(
+        &self,
+        file_url: &str,
+        file_type: &str,
+        output_type: &str,
+        title: &str,
+        key: &str,
+    ) -> Result<Vec<u8>> {
+        let mut request = ConversionRequest {
+            is_async: true,
+            filetype: file_type.to_string(),
+            key: key.to_string(),
+            outputtype: output_type.to_string(),
+            title: title.to_string(),
+            url: file_url.to_string(),
+            token: None,
+        };
+
+        // The token signs the request parameters themselves
+        let token = encode(
+            &Header::default(),
+            &request,
+            &EncodingKey::from_secret(self.config.jwt_secret.as_bytes()),
+        )
+        .map_err(|e| AppError::InternalServerError(format!("Failed to sign conversion request: {}", e)))?;
+        request.token = Some(token.clone());
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/ConvertService.ashx", self.config.server.trim_end_matches('/'));
+        let deadline = std::time::Instant::now() + self.conversion_timeout;
+
+        // Async conversions are polled by repeating the same request
+        let file_url = loop {
+            let response = client
+                .post(&endpoint)
+                .header(reqwest::header::ACCEPT, "application/json")
+                .bearer_auth(&token)
+                .json(&request)
+                .send()
+                .await
+                .map_err(|e| AppError::InternalServerError(format!("Conversion request failed: {}", e)))?
+                .error_for_status()
+                .map_err(|e| AppError::InternalServerError(format!("Conversion request failed: {}", e)))?
+                .json::<ConversionResponse>()
+                .await
+                .map_err(|e| AppError::InternalServerError(format!("Invalid conversion response: {}", e)))?;
+
+            if let Some(code) = response.error.filter(|code| *code != 0) {
+                return Err(Self::conversion_error(code));
+            }
+
+            if response.end_convert {
+                break response.file_url.ok_or_else(|| {
+                    AppError::InternalServerError("Conversion finished without a file URL".to_string())
+                })?;
+            }
+
+            if std::time::Instant::now() >= deadline {
+                return Err(AppError::InternalServerError("Conversion timed out".to_string()));
+            }
+
+            tokio::time::sleep(self.conversion_poll_interval).await;
+        };
+
+        self.download_server_file(&file_url).await
+    }
+
+    fn conversion_error(code: i32) -> AppError {
+        match code {
+            -5 => AppError::BadRequest("Document is password protected".to_string()),
+            -7 | -9 => AppError::BadRequest(format!("Document Server cannot convert this file (error {})", code)),
+            -3 => AppError::BadRequest("Document Server failed to convert the file".to_string()),
+            -8 => AppError::InternalServerError("Document Server rejected the conversion token".to_string()),
+            -4 => AppError::InternalServerError("Document Server could not download the file".to_string()),
+            -2 => AppError::InternalServerError("Conversion timed out".to_string()),
+            _ => AppError::InternalServerError(format!("Conversion failed with error {}", code)),
+        }
+    }
+
     pub fn can_edit_file(mime_type: &str) -> bool {
         matches!(
             mime_type,
@@ -173,6 +627,14 @@ impl OnlyOfficeService {
                     Err(AppError::BadRequest("Missing document URL in callback".to_string()))
                 }
             }
+            7 => {
+                tracing::warn!("OnlyOffice force save failed for key {}", callback_data.key);
+                Ok(CallbackResponse {
+                    error: 0,
+                    message: "Force save error received".to_string(),
+                    download_url: None,
+                })
+            }
             1 | 4 => {
                 // Document closed, no action needed
                 Ok(CallbackResponse {
@@ -201,3 +663,278 @@ pub struct CallbackResponse {
     pub download_url: Option<String>,
 }
 
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    use axum::{
+        extract::State,
+        http::{header, HeaderMap, StatusCode},
+        response::IntoResponse,
+        routing::{get, post},
+        Json, Router,
+    };
+    use serde_json::json;
+
+    use super::*;
+    use crate::config::OnlyOfficeConfig;
+
+    const SECRET: &str = "test-secret";
+
+    /// Stands in for Document Server's ConvertService, answering with scripted responses
+    #[derive(Default)]
+    struct MockConvertService {
+        // The last response is repeated once the others are used up
+        responses: Mutex<VecDeque<JsonValue>>,
+        // Authorization header and body of each conversion request
+        requests: Mutex<Vec<(Option<String>, JsonValue)>>,
+    }
+
+    async fn convert_service(
+        State(mock): State<Arc<MockConvertService>>,
+        headers: HeaderMap,
+        Json(body): Json<JsonValue>,
+    ) -> Json<JsonValue> {
+        let authorization = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        mock.requests.lock().unwrap().push((authorization, body));
+
+        let mut responses = mock.responses.lock().unwrap();
+        let response = if responses.len() > 1 {
+            responses.pop_front().unwrap()
+        } else {
+            responses.front().cloned().unwrap()
+        };
+
+        Json(response)
+    }
+
+    async fn converted_file() -> &'static str {
+        "converted"
+    }
+
+    async fn redirect() -> impl IntoResponse {
+        (StatusCode::FOUND, [(header::LOCATION, "/files/result.pdf")])
+    }
+
+    /// Start the mock server; `responses` gets its base URL to build file URLs with
+    async fn start(
+        responses: impl FnOnce(&str) -> Vec<JsonValue>,
+    ) -> (OnlyOfficeService, Arc<MockConvertService>, String) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+
+        let mock = Arc::new(MockConvertService {
+            responses: Mutex::new(responses(&base).into()),
+            ..Default::default()
+        });
+
+        let app = Router::new()
+            .route("/ConvertService.ashx", post(convert_service))
+            .route("/files/result.pdf", get(converted_file))
+            .route("/files/redirect", get(redirect))
+            .with_state(mock.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let service = OnlyOfficeService {
+            config: Arc::new(OnlyOfficeConfig {
+                server: base.clone(),
+                jwt_secret: SECRET.to_string(),
+            }),
+            conversion_poll_interval: Duration::from_millis(10),
+            conversion_timeout: Duration::from_millis(200),
+        };
+
+        (service, mock, base)
+    }
+
+    async fn convert(service: &OnlyOfficeService) -> Result<Vec<u8>> {
+        service
+            .convert("http://dms.test/files/source", "docx", "pdf", "report.docx", "key-1")
+            .await
+    }
+
+    fn finished(file_url: String) -> JsonValue {
+        json!({ "endConvert": true, "fileUrl": file_url, "fileType": "pdf", "percent": 100 })
+    }
+
+    #[tokio::test]
+    async fn convert_signs_the_request_body() {
+        let (service, mock, _) = start(|base| vec![finished(format!("{}/files/result.pdf", base))]).await;
+
+        convert(&service).await.unwrap();
+
+        let requests = mock.requests.lock().unwrap();
+        let (authorization, body) = &requests[0];
+        let token = body["token"].as_str().unwrap();
+        assert_eq!(authorization.as_deref(), Some(format!("Bearer {}", token).as_str()));
+
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.required_spec_claims.clear();
+        let claims = decode::<JsonValue>(token, &DecodingKey::from_secret(SECRET.as_bytes()), &validation)
+            .unwrap()
+            .claims;
+
+        assert_eq!(
+            claims,
+            json!({
+                "async": true,
+                "filetype": "docx",
+                "key": "key-1",
+                "outputtype": "pdf",
+                "title": "report.docx",
+                "url": "http://dms.test/files/source",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn convert_polls_until_the_conversion_ends() {
+        let (service, mock, _) = start(|base| {
+            vec![
+                json!({ "endConvert": false, "percent": 0 }),
+                json!({ "endConvert": false, "percent": 50 }),
+                finished(format!("{}/files/result.pdf", base)),
+            ]
+        })
+        .await;
+
+        let data = convert(&service).await.unwrap();
+
+        assert_eq!(data, b"converted");
+
+        // Every poll repeats the original request
+        let requests = mock.requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|(_, body)| body == &requests[0].1));
+    }
+
+    #[tokio::test]
+    async fn convert_maps_error_codes() {
+        for (code, client_error) in [(-5, true), (-9, true), (-3, true), (-8, false), (-4, false)] {
+            let (service, _, _) = start(|_| vec![json!({ "endConvert": false, "error": code })]).await;
+
+            let error = convert(&service).await.unwrap_err();
+
+            if client_error {
+                assert!(matches!(error, AppError::BadRequest(_)), "error {}: {:?}", code, error);
+            } else {
+                assert!(matches!(error, AppError::InternalServerError(_)), "error {}: {:?}", code, error);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn convert_times_out() {
+        let (service, mock, _) = start(|_| vec![json!({ "endConvert": false, "percent": 10 })]).await;
+
+        let error = convert(&service).await.unwrap_err();
+
+        assert!(matches!(&error, AppError::InternalServerError(message) if message == "Conversion timed out"));
+        assert!(mock.requests.lock().unwrap().len() > 1);
+    }
+
+    #[tokio::test]
+    async fn convert_refuses_results_on_another_host() {
+        let (service, _, _) = start(|_| vec![finished("http://attacker.test/files/result.pdf".to_string())]).await;
+
+        let error = convert(&service).await.unwrap_err();
+
+        assert!(matches!(error, AppError::Forbidden(_)), "{:?}", error);
+    }
+
+    #[tokio::test]
+    async fn convert_does_not_follow_redirects() {
+        let (service, _, _) = start(|base| vec![finished(format!("{}/files/redirect", base))]).await;
+
+        let error = convert(&service).await.unwrap_err();
+
+        assert!(matches!(error, AppError::InternalServerError(_)), "{:?}", error);
+    }
+
+    fn callback_service() -> OnlyOfficeService {
+        OnlyOfficeService::new(&OnlyOfficeConfig {
+            server: "http://onlyoffice.test".to_string(),
+            jwt_secret: SECRET.to_string(),
+        })
+    }
+
+    fn saved_callback() -> JsonValue {
+        json!({
+            "key": "key-1",
+            "status": 2,
+            "url": "http://onlyoffice.test/cache/files/output.docx",
+            "users": ["editor-1"],
+        })
+    }
+
+    fn callback_body(value: JsonValue) -> OnlyOfficeCallbackData {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn verify_callback_accepts_a_body_token() {
+        let service = callback_service();
+        let mut body = saved_callback();
+        body["token"] = service.sign(&saved_callback()).unwrap().into();
+
+        let verified = service.verify_callback(&callback_body(body), None).unwrap();
+
+        assert_eq!(verified.key, "key-1");
+        assert_eq!(verified.status, 2);
+        assert_eq!(verified.users, Some(vec!["editor-1".to_string()]));
+    }
+
+    #[test]
+    fn verify_callback_reads_the_header_token_payload() {
+        let service = callback_service();
+        let token = service.sign(&json!({ "payload": saved_callback() })).unwrap();
+
+        let verified = service
+            .verify_callback(&callback_body(saved_callback()), Some(&format!("Bearer {}", token)))
+            .unwrap();
+
+        assert_eq!(verified.key, "key-1");
+        assert_eq!(verified.url.as_deref(), Some("http://onlyoffice.test/cache/files/output.docx"));
+    }
+
+    #[test]
+    fn verify_callback_rejects_a_body_that_differs_from_its_token() {
+        let service = callback_service();
+        let mut body = saved_callback();
+        body["url"] = "http://attacker.test/output.docx".into();
+        body["token"] = service.sign(&saved_callback()).unwrap().into();
+
+        let error = service.verify_callback(&callback_body(body), None).unwrap_err();
+
+        assert!(matches!(error, AppError::Unauthorized(_)), "{:?}", error);
+    }
+
+    #[test]
+    fn verify_callback_rejects_a_token_signed_with_another_secret() {
+        let service = callback_service();
+        let other = OnlyOfficeService::new(&OnlyOfficeConfig {
+            server: "http://onlyoffice.test".to_string(),
+            jwt_secret: "other-secret".to_string(),
+        });
+        let mut body = saved_callback();
+        body["token"] = other.sign(&saved_callback()).unwrap().into();
+
+        let error = service.verify_callback(&callback_body(body), None).unwrap_err();
+
+        assert!(matches!(error, AppError::Unauthorized(_)), "{:?}", error);
+    }
+
+    #[test]
+    fn verify_callback_requires_a_token() {
+        let error = callback_service()
+            .verify_callback(&callback_body(saved_callback()), None)
+            .unwrap_err();
+
+        assert!(matches!(error, AppError::Unauthorized(_)), "{:?}", error);
+    }
+}
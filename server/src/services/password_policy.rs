@@ -0,0 +1,76 @@
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use crate::config::PasswordConfig;
+use crate::error::{AppError, Result};
+
+// 泄露密码列表只在首次使用时读取一次
+static BREACHED_PASSWORDS: OnceLock<HashSet<String>> = OnceLock::new();
+
+const MAX_PASSWORD_LENGTH: usize = 128;
+
+pub struct PasswordPolicyService;
+
+impl PasswordPolicyService {
+    /// Check a new password against the configured policy, reporting every
+    /// rule it breaks
+    pub fn validate(config: &PasswordConfig, password: &str) -> Result<()> {
+        let mut problems = Vec::new();
+        let length = password.chars().count();
+
+        if length < config.min_length {
+            problems.push(format!("be at least {} characters long", config.min_length));
+        }
+        if length > MAX_PASSWORD_LENGTH {
+            problems.push(format!("be at most {} characters long", MAX_PASSWORD_LENGTH));
+        }
+        if config.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+            problems.push("contain an uppercase letter".to_string());
+        }
+        if config.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+            problems.push("contain a lowercase letter".to_string());
+        }
+        if config.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            problems.push("contain a digit".to_string());
+        }
+        if config.require_symbol && !password.chars().any(|c| !c.is_alphanumeric()) {
+            problems.push("contain a symbol".to_string());
+        }
+
+        if !problems.is_empty() {
+            return Err(AppError::ValidationError(format!(
+                "Password must {}",
+                problems.join(", ")
+            )));
+        }
+
+        if let Some(path) = &config.breached_list_path {
+            if Self::breached_passwords(path).contains(password) {
+                return Err(AppError::ValidationError(
+                    "Password appears in a list of breached passwords".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn breached_passwords(path: &str) -> &'static HashSet<String> {
+        BREACHED_PASSWORDS.get_or_init(|| match std::fs::read_to_string(path) {
+            Ok(contents) => {
+                let passwords: HashSet<String> = contents
+                    .lines()
+                    .map(|line| line.trim_end_matches('\r'))
+                    .filter(|line| !line.is_empty())
+                    .map(|line| line.to_string())
+                    .collect();
+                tracing::info!("Loaded {} breached passwords from {}", passwords.len(), path);
+                passwords
+            }
+            Err(e) => {
+                tracing::error!("Failed to read breached password list {}: {}", path, e);
+                HashSet::new()
+            }
+        })
+    }
+}
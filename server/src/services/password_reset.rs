@@ -0,0 +1,87 @@
+use chrono::Duration;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::schema::{password_reset_tokens, users};
+use crate::utils::{generate_token, hash_token};
+
+pub struct PasswordResetService;
+
+impl PasswordResetService {
+    /// Issue a reset token for a user, replacing any unused one
+    pub fn create_token(conn: &mut DbConnection, user_id: Uuid, expiration_seconds: i64) -> Result<String> {
+        let token = generate_token();
+        let expires_at = chrono::Local::now().naive_local() + Duration::seconds(expiration_seconds);
+
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            Self::expire_outstanding(conn, user_id)?;
+
+            diesel::insert_into(password_reset_tokens::table)
+                .values((
+                    password_reset_tokens::user_id.eq(user_id),
+                    password_reset_tokens::token_hash.eq(hash_token(&token)),
+                    password_reset_tokens::expires_at.eq(expires_at),
+                ))
+                .execute(conn)?;
+
+            Ok(())
+        })?;
+
+        Ok(token)
+    }
+
+    /// Set a new password hash using a reset token; each token works once
+    pub fn reset_password(conn: &mut DbConnection, token: &str, password_hash: &str) -> Result<Uuid> {
+        let user_id = conn.transaction::<Option<Uuid>, diesel::result::Error, _>(|conn| {
+            let user_id = diesel::update(
+                password_reset_tokens::table
+                    .filter(password_reset_tokens::token_hash.eq(hash_token(token)))
+                    .filter(password_reset_tokens::used_at.is_null())
+                    .filter(password_reset_tokens::expires_at.gt(diesel::dsl::now)),
+            )
+            .set(password_reset_tokens::used_at.eq(diesel::dsl::now.nullable()))
+            .returning(password_reset_tokens::user_id)
+            .get_result::<Uuid>(conn)
+            .optional()?;
+
+            let Some(user_id) = user_id else {
+                return Ok(None);
+            };
+
+            Self::expire_outstanding(conn, user_id)?;
+            Self::set_password(conn, user_id, password_hash)?;
+
+            Ok(Some(user_id))
+        })?;
+
+        user_id.ok_or_else(|| AppError::BadRequest("Invalid or expired reset token".to_string()))
+    }
+
+    pub fn set_password(
+        conn: &mut DbConnection,
+        user_id: Uuid,
+        password_hash: &str,
+    ) -> std::result::Result<(), diesel::result::Error> {
+        diesel::update(users::table.find(user_id))
+            .set((
+                users::password_hash.eq(password_hash),
+                users::updated_at.eq(diesel::dsl::now),
+            ))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    // A newer token or a completed reset invalidates the others
+    fn expire_outstanding(conn: &mut DbConnection, user_id: Uuid) -> std::result::Result<usize, diesel::result::Error> {
+        diesel::update(
+            password_reset_tokens::table
+                .filter(password_reset_tokens::user_id.eq(user_id))
+                .filter(password_reset_tokens::used_at.is_null()),
+        )
+        .set(password_reset_tokens::used_at.eq(diesel::dsl::now.nullable()))
+        .execute(conn)
+    }
+}
@@ -3,12 +3,28 @@ use uuid::Uuid;
 
 use crate::db::DbConnection;
 use crate::error::{AppError, Result};
+use crate::models::document::Document;
 use crate::models::permission::{DocumentPermission, PermissionType};
-use crate::schema::{document_permissions, documents, group_members, group_permissions};
+use crate::models::user::UserRole;
+use crate::schema::{document_permissions, documents, group_members, group_permissions, users};
 
 pub struct PermissionService;
 
 impl PermissionService {
+    /// Load a document the user is allowed to read
+    pub fn load_readable(conn: &mut DbConnection, user_id: Uuid, document_id: Uuid) -> Result<Document> {
+        if !Self::check_permission(conn, user_id, document_id, PermissionType::Read)? {
+            return Err(AppError::Forbidden("No permission to view this document".to_string()));
+        }
+
+        let document = documents::table
+            .find(document_id)
+            .select(Document::as_select())
+            .first::<Document>(conn)?;
+
+        Ok(document)
+    }
+
     /// Check if a user has a specific permission on a document
     pub fn check_permission(
         conn: &mut DbConnection,
@@ -16,6 +32,10 @@ impl PermissionService {
         document_id: Uuid,
         required_permission: PermissionType,
     ) -> Result<bool> {
+        if Self::global_permissions(conn, user_id)?.contains(&required_permission) {
+            return Ok(true);
+        }
+
         // First check if user is the owner
         let is_owner: bool = diesel::select(diesel::dsl::exists(
             documents::table
@@ -65,6 +85,109 @@ impl PermissionService {
         Ok(has_group_permission)
     }
 
+    /// Every permission a user holds on a document, directly or through a group
+    pub fn effective_permissions(
+        conn: &mut DbConnection,
+        user_id: Uuid,
+        document_id: Uuid,
+    ) -> Result<Vec<PermissionType>> {
+        let is_owner: bool = diesel::select(diesel::dsl::exists(
+            documents::table
+                .filter(documents::id.eq(document_id))
+                .filter(documents::owner_id.eq(user_id))
+        ))
+        .get_result::<bool>(conn)?;
+
+        if is_owner {
+            return Ok(PermissionType::ALL.to_vec());
+        }
+
+        let mut granted = document_permissions::table
+            .filter(document_permissions::document_id.eq(document_id))
+            .filter(document_permissions::user_id.eq(user_id))
+            .filter(
+                document_permissions::expires_at
+                    .is_null()
+                    .or(document_permissions::expires_at.gt(diesel::dsl::now)),
+            )
+            .select(document_permissions::permission)
+            .load::<String>(conn)?;
+
+        let group_granted = group_permissions::table
+            .inner_join(group_members::table.on(group_permissions::group_id.eq(group_members::group_id)))
+            .filter(group_permissions::document_id.eq(document_id))
+            .filter(group_members::user_id.eq(user_id))
+            .filter(
+                group_permissions::expires_at
+                    .is_null()
+                    .or(group_permissions::expires_at.gt(diesel::dsl::now)),
+            )
+            .select(group_permissions::permission)
+            .load::<String>(conn)?;
+
+        granted.extend(group_granted);
+        let global = Self::global_permissions(conn, user_id)?;
+
+        Ok(PermissionType::ALL
+            .into_iter()
+            .filter(|p| global.contains(p) || granted.iter().any(|g| g == p.as_str()))
+            .collect())
+    }
+
+    /// Documents a user owns or may read through a grant, e.g. to limit search results
+    pub fn readable_document_ids(conn: &mut DbConnection, user_id: Uuid) -> Result<Vec<Uuid>> {
+        let permission_str = PermissionType::Read.as_str();
+
+        let mut ids = documents::table
+            .filter(documents::owner_id.eq(user_id))
+            .filter(documents::deleted_at.is_null())
+            .select(documents::id)
+            .load::<Uuid>(conn)?;
+
+        let direct = document_permissions::table
+            .filter(document_permissions::user_id.eq(user_id))
+            .filter(document_permissions::permission.eq(permission_str))
+            .filter(
+                document_permissions::expires_at
+                    .is_null()
+                    .or(document_permissions::expires_at.gt(diesel::dsl::now)),
+            )
+            .select(document_permissions::document_id)
+            .load::<Uuid>(conn)?;
+
+        let through_groups = group_permissions::table
+            .inner_join(group_members::table.on(group_permissions::group_id.eq(group_members::group_id)))
+            .filter(group_members::user_id.eq(user_id))
+            .filter(group_permissions::permission.eq(permission_str))
+            .filter(
+                group_permissions::expires_at
+                    .is_null()
+                    .or(group_permissions::expires_at.gt(diesel::dsl::now)),
+            )
+            .select(group_permissions::document_id)
+            .load::<Uuid>(conn)?;
+
+        ids.extend(direct);
+        ids.extend(through_groups);
+        ids.sort_unstable();
+        ids.dedup();
+
+        Ok(ids)
+    }
+
+    // Permissions that come with the user's role rather than a grant
+    fn global_permissions(conn: &mut DbConnection, user_id: Uuid) -> Result<&'static [PermissionType]> {
+        let role = users::table
+            .find(user_id)
+            .select(users::role)
+            .first::<String>(conn)
+            .optional()?;
+
+        Ok(role
+            .and_then(|role| UserRole::from_str(&role))
+            .map_or(&[], |role| role.global_permissions()))
+    }
+
     /// Check if user has admin permission (owner or admin permission)
     pub fn check_admin_permission(
         conn: &mut DbConnection,
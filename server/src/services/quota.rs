@@ -0,0 +1,100 @@
+use diesel::prelude::*;
+use diesel::sql_types::Uuid as SqlUuid;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::quota::{UsageResponse, UserUsageRow};
+use crate::schema::{group_members, storage_quotas};
+
+// 用量 = 未删除文档的当前文件大小 + 仍保留的历史版本文件大小
+// 历史版本中与文档当前文件相同的那一条不重复计算
+const USAGE_SELECT: &str = r#"
+    SELECT
+        u.id AS user_id,
+        u.username AS username,
+        COALESCE((
+            SELECT SUM(d.file_size)
+            FROM documents d
+            WHERE d.owner_id = u.id AND d.deleted_at IS NULL AND d.is_folder = false
+        ), 0)::BIGINT AS document_bytes,
+        COALESCE((
+            SELECT SUM(v.file_size)
+            FROM document_versions v
+            JOIN documents d ON d.id = v.document_id
+            WHERE d.owner_id = u.id AND d.deleted_at IS NULL AND v.file_path <> d.file_path
+        ), 0)::BIGINT AS version_bytes
+    FROM users u
+"#;
+
+pub struct QuotaService;
+
+impl QuotaService {
+    /// Compute storage usage for a single user
+    pub fn get_usage(conn: &mut DbConnection, user_id: Uuid) -> Result<UserUsageRow> {
+        let row = diesel::sql_query(format!("{} WHERE u.id = $1", USAGE_SELECT))
+            .bind::<SqlUuid, _>(user_id)
+            .get_result::<UserUsageRow>(conn)?;
+
+        Ok(row)
+    }
+
+    /// Compute storage usage for every user
+    pub fn get_all_usage(conn: &mut DbConnection) -> Result<Vec<UserUsageRow>> {
+        let rows = diesel::sql_query(format!("{} ORDER BY u.username", USAGE_SELECT))
+            .load::<UserUsageRow>(conn)?;
+
+        Ok(rows)
+    }
+
+    /// Get the quota that applies to a user.
+    ///
+    /// A quota set on the user wins; otherwise the most generous quota among the
+    /// user's groups applies. `None` means unlimited.
+    pub fn get_effective_quota(conn: &mut DbConnection, user_id: Uuid) -> Result<Option<i64>> {
+        let user_quota = storage_quotas::table
+            .filter(storage_quotas::user_id.eq(user_id))
+            .select(storage_quotas::max_bytes)
+            .first::<i64>(conn)
+            .optional()?;
+
+        if user_quota.is_some() {
+            return Ok(user_quota);
+        }
+
+        let group_quota = storage_quotas::table
+            .inner_join(
+                group_members::table
+                    .on(storage_quotas::group_id.eq(group_members::group_id.nullable())),
+            )
+            .filter(group_members::user_id.eq(user_id))
+            .select(diesel::dsl::max(storage_quotas::max_bytes))
+            .first::<Option<i64>>(conn)?;
+
+        Ok(group_quota)
+    }
+
+    /// Usage report for a user including the applicable quota
+    pub fn get_usage_report(conn: &mut DbConnection, user_id: Uuid) -> Result<UsageResponse> {
+        let usage = Self::get_usage(conn, user_id)?;
+        let quota = Self::get_effective_quota(conn, user_id)?;
+
+        Ok(UsageResponse::new(usage, quota))
+    }
+
+    /// Ensure a user can store `additional_bytes` more without exceeding the quota
+    pub fn check_quota(conn: &mut DbConnection, user_id: Uuid, additional_bytes: i64) -> Result<()> {
+        let report = Self::get_usage_report(conn, user_id)?;
+
+        if let Some(quota) = report.quota_bytes {
+            if report.used_bytes + additional_bytes > quota {
+                return Err(AppError::QuotaExceeded(format!(
+                    "Storage quota exceeded: {} of {} bytes used, upload needs {} bytes",
+                    report.used_bytes, quota, additional_bytes
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
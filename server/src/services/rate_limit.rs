@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use axum::http::HeaderMap;
+
+use crate::config::RateLimitConfig;
+use crate::services::session::ClientInfo;
+
+const WINDOW: Duration = Duration::from_secs(60);
+// 超过该数量的客户端时清理已结束的计数窗口
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Routes that share a request limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteGroup {
+    /// Login, registration and the other `/api/auth` endpoints
+    Auth,
+    /// Public share links, where the token in the path is the only secret
+    Shares,
+    Api,
+}
+
+impl RouteGroup {
+    /// The group a path is limited under; `None` outside the API.
+    ///
+    /// Document Server callbacks are exempt: they all come from the one
+    /// server address, carry a signed token, and a rejected save is lost.
+    pub fn for_path(path: &str) -> Option<Self> {
+        if path.starts_with("/api/onlyoffice/callback/") {
+            None
+        } else if path.starts_with("/api/auth/") {
+            Some(RouteGroup::Auth)
+        } else if path.starts_with("/api/shares/access/") {
+            Some(RouteGroup::Shares)
+        } else if path.starts_with("/api/") {
+            Some(RouteGroup::Api)
+        } else {
+            None
+        }
+    }
+
+    /// Requests per minute for each client; 0 means unlimited
+    pub fn limit(&self, config: &RateLimitConfig) -> u32 {
+        match self {
+            RouteGroup::Auth => config.auth_per_minute,
+            RouteGroup::Shares => config.shares_per_minute,
+            RouteGroup::Api => config.api_per_minute,
+        }
+    }
+}
+
+struct Window {
+    started: Instant,
+    requests: u32,
+}
+
+/// Counts requests per client in one-minute windows.
+///
+/// Counts are kept in memory, so each server instance limits on its own.
+#[derive(Default)]
+pub struct RateLimiter {
+    windows: Mutex<HashMap<(RouteGroup, String), Window>>,
+}
+
+impl RateLimiter {
+    /// Count a request; once the client is over the limit, the seconds until the window ends
+    pub fn hit(&self, group: RouteGroup, client: &str, limit: u32) -> Result<(), u64> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if windows.len() > PRUNE_THRESHOLD {
+            windows.retain(|_, window| now.duration_since(window.started) < WINDOW);
+        }
+
+        let window = windows
+            .entry((group, client.to_string()))
+            .or_insert(Window { started: now, requests: 0 });
+
+        if now.duration_since(window.started) >= WINDOW {
+            window.started = now;
+            window.requests = 0;
+        }
+
+        if window.requests >= limit {
+            let remaining = WINDOW.saturating_sub(now.duration_since(window.started));
+            return Err(remaining.as_secs().max(1));
+        }
+
+        window.requests += 1;
+
+        Ok(())
+    }
+
+    /// The address requests are counted against.
+    ///
+    /// Forwarding headers are only believed behind a trusted proxy; anyone can send them.
+    pub fn client_ip(config: &RateLimitConfig, headers: &HeaderMap, peer: SocketAddr) -> String {
+        let forwarded = config
+            .trust_proxy
+            .then(|| ClientInfo::forwarded_ip(headers))
+            .flatten();
+
+        forwarded.unwrap_or_else(|| peer.ip().to_string())
+    }
+}
@@ -0,0 +1,379 @@
+use chrono::Duration;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::config::RegistrationConfig;
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::registration::{
+    CreateInvitationRequest, Invitation, InvitationResponse, NewInvitation, RegistrationMode,
+};
+use crate::models::user::{NewUser, User, UserRole};
+use crate::schema::{email_verification_tokens, group_members, groups, invitation_groups, invitations, users};
+use crate::utils::{generate_token, hash_token};
+
+pub struct RegistrationService;
+
+impl RegistrationService {
+    pub fn mode(config: &RegistrationConfig) -> Result<RegistrationMode> {
+        RegistrationMode::from_str(&config.mode).ok_or_else(|| {
+            AppError::InternalServerError(format!("Unknown REGISTRATION_MODE: {}", config.mode))
+        })
+    }
+
+    /// Fail unless the registration mode lets this address sign up.
+    ///
+    /// An invitation works in every mode but disabled, and only for the address it was sent to.
+    pub fn ensure_allowed(config: &RegistrationConfig, email: &str, invitation: Option<&Invitation>) -> Result<()> {
+        let mode = Self::mode(config)?;
+
+        if mode == RegistrationMode::Disabled {
+            return Err(AppError::Forbidden("Registration is disabled".to_string()));
+        }
+
+        if let Some(invitation) = invitation {
+            if !invitation.email.eq_ignore_ascii_case(email) {
+                return Err(AppError::BadRequest(
+                    "The invitation was sent to a different email address".to_string(),
+                ));
+            }
+
+            return Ok(());
+        }
+
+        match mode {
+            RegistrationMode::Invite => Err(AppError::Forbidden("Registration requires an invitation".to_string())),
+            RegistrationMode::Domain => {
+                let domain = email.rsplit_once('@').map(|(_, domain)| domain.to_lowercase());
+
+                if domain.is_some_and(|domain| config.allowed_domains.contains(&domain)) {
+                    Ok(())
+                } else {
+                    Err(AppError::Forbidden(
+                        "Registration is not open to this email domain".to_string(),
+                    ))
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Create the user, accepting the invitation they signed up with.
+    ///
+    /// Users who still have to verify their email address start out inactive.
+    pub fn register(
+        conn: &mut DbConnection,
+        new_user: NewUser,
+        invitation: Option<&Invitation>,
+        verified: bool,
+    ) -> Result<User> {
+        let result = conn.transaction::<User, diesel::result::Error, _>(|conn| {
+            let user = diesel::insert_into(users::table)
+                .values((
+                    &new_user,
+                    users::is_active.eq(verified),
+                    users::email_verified_at.eq(verified.then(|| chrono::Local::now().naive_local())),
+                ))
+                .returning(User::as_returning())
+                .get_result(conn)?;
+
+            let Some(invitation) = invitation else {
+                return Ok(user);
+            };
+
+            // Whoever claims the invitation first gets it
+            let accepted = diesel::update(
+                invitations::table
+                    .find(invitation.id)
+                    .filter(invitations::accepted_at.is_null())
+                    .filter(invitations::expires_at.gt(diesel::dsl::now)),
+            )
+            .set((
+                invitations::accepted_at.eq(diesel::dsl::now.nullable()),
+                invitations::accepted_user_id.eq(user.id),
+            ))
+            .execute(conn)?;
+
+            if accepted == 0 {
+                return Err(diesel::result::Error::RollbackTransaction);
+            }
+
+            let group_ids = invitation_groups::table
+                .filter(invitation_groups::invitation_id.eq(invitation.id))
+                .select(invitation_groups::group_id)
+                .load::<Uuid>(conn)?;
+
+            diesel::insert_into(group_members::table)
+                .values(
+                    group_ids
+                        .iter()
+                        .map(|group_id| (group_members::group_id.eq(group_id), group_members::user_id.eq(user.id)))
+                        .collect::<Vec<_>>(),
+                )
+                .on_conflict((group_members::group_id, group_members::user_id))
+                .do_nothing()
+                .execute(conn)?;
+
+            Ok(user)
+        });
+
+        result.map_err(|e| match e {
+            diesel::result::Error::RollbackTransaction => {
+                AppError::BadRequest("Invalid or expired invitation".to_string())
+            }
+            e => e.into(),
+        })
+    }
+
+    /// Issue a verification token for a user, replacing any unused one
+    pub fn create_verification_token(conn: &mut DbConnection, user_id: Uuid, expiration_seconds: i64) -> Result<String> {
+        let token = generate_token();
+        let expires_at = chrono::Local::now().naive_local() + Duration::seconds(expiration_seconds);
+
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            Self::expire_outstanding(conn, user_id)?;
+
+            diesel::insert_into(email_verification_tokens::table)
+                .values((
+                    email_verification_tokens::user_id.eq(user_id),
+                    email_verification_tokens::token_hash.eq(hash_token(&token)),
+                    email_verification_tokens::expires_at.eq(expires_at),
+                ))
+                .execute(conn)?;
+
+            Ok(())
+        })?;
+
+        Ok(token)
+    }
+
+    /// Confirm a user's email address and activate them; each token works once
+    pub fn verify_email(conn: &mut DbConnection, token: &str) -> Result<User> {
+        let user = conn.transaction::<Option<User>, diesel::result::Error, _>(|conn| {
+            let user_id = diesel::update(
+                email_verification_tokens::table
+                    .filter(email_verification_tokens::token_hash.eq(hash_token(token)))
+                    .filter(email_verification_tokens::used_at.is_null())
+                    .filter(email_verification_tokens::expires_at.gt(diesel::dsl::now)),
+            )
+            .set(email_verification_tokens::used_at.eq(diesel::dsl::now.nullable()))
+            .returning(email_verification_tokens::user_id)
+            .get_result::<Uuid>(conn)
+            .optional()?;
+
+            let Some(user_id) = user_id else {
+                return Ok(None);
+            };
+
+            Self::expire_outstanding(conn, user_id)?;
+
+            diesel::update(
+                users::table
+                    .find(user_id)
+                    .filter(users::email_verified_at.is_null()),
+            )
+            .set((
+                users::is_active.eq(true),
+                users::email_verified_at.eq(diesel::dsl::now.nullable()),
+                users::updated_at.eq(diesel::dsl::now),
+            ))
+            .returning(User::as_returning())
+            .get_result(conn)
+            .optional()
+        })?;
+
+        user.ok_or_else(|| AppError::BadRequest("Invalid or expired verification token".to_string()))
+    }
+
+    /// Whether an inactive user is only waiting to confirm their email address
+    pub fn awaiting_verification(conn: &mut DbConnection, user: &User) -> Result<bool> {
+        if user.is_active || user.email_verified_at.is_some() {
+            return Ok(false);
+        }
+
+        let pending = diesel::select(diesel::dsl::exists(
+            email_verification_tokens::table.filter(email_verification_tokens::user_id.eq(user.id)),
+        ))
+        .get_result::<bool>(conn)?;
+
+        Ok(pending)
+    }
+
+    /// Delete self-registrations holding a username or email address that
+    /// were never verified and can't be any more, so someone can sign up with
+    /// them again. Returns how many were deleted.
+    pub fn release_abandoned(conn: &mut DbConnection, username: &str, email: &str) -> Result<usize> {
+        let outstanding = email_verification_tokens::table
+            .filter(email_verification_tokens::user_id.eq(users::id))
+            .filter(email_verification_tokens::used_at.is_null())
+            .filter(email_verification_tokens::expires_at.gt(diesel::dsl::now));
+
+        // Only accounts that were sent a link; admins create inactive users too
+        let deleted = diesel::delete(
+            users::table
+                .filter(users::username.eq(username).or(users::email.eq(email)))
+                .filter(users::is_active.eq(false))
+                .filter(users::email_verified_at.is_null())
+                .filter(diesel::dsl::exists(
+                    email_verification_tokens::table.filter(email_verification_tokens::user_id.eq(users::id)),
+                ))
+                .filter(diesel::dsl::not(diesel::dsl::exists(outstanding))),
+        )
+        .execute(conn)?;
+
+        Ok(deleted)
+    }
+
+    /// A self-registered user who hasn't verified the address yet
+    pub fn find_unverified(conn: &mut DbConnection, email: &str) -> Result<Option<User>> {
+        let user = users::table
+            .filter(users::email.eq(email))
+            .filter(users::is_active.eq(false))
+            .filter(users::email_verified_at.is_null())
+            .select(User::as_select())
+            .first(conn)
+            .optional()?;
+
+        match user {
+            Some(user) if Self::awaiting_verification(conn, &user)? => Ok(Some(user)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Invite someone to sign up; the token is only returned here
+    pub fn create_invitation(
+        conn: &mut DbConnection,
+        invited_by: Uuid,
+        request: CreateInvitationRequest,
+        expiration_seconds: i64,
+    ) -> Result<(InvitationResponse, String)> {
+        let role = match request.role.as_deref().map(UserRole::from_str) {
+            None => UserRole::User,
+            Some(Some(role @ (UserRole::User | UserRole::Guest))) => role,
+            Some(_) => {
+                return Err(AppError::ValidationError(
+                    "Invitations can only grant the user or guest role".to_string(),
+                ))
+            }
+        };
+
+        let registered = diesel::select(diesel::dsl::exists(
+            users::table.filter(users::email.eq(&request.email)),
+        ))
+        .get_result::<bool>(conn)?;
+
+        if registered {
+            return Err(AppError::BadRequest("A user with this email already exists".to_string()));
+        }
+
+        let mut group_ids = request.group_ids;
+        group_ids.sort();
+        group_ids.dedup();
+
+        let found = groups::table
+            .filter(groups::id.eq_any(&group_ids))
+            .count()
+            .get_result::<i64>(conn)?;
+
+        if found != group_ids.len() as i64 {
+            return Err(AppError::NotFound("Group not found".to_string()));
+        }
+
+        let token = generate_token();
+        let expires_at = chrono::Local::now().naive_local() + Duration::seconds(expiration_seconds);
+
+        let invitation = conn.transaction::<Invitation, diesel::result::Error, _>(|conn| {
+            let invitation = diesel::insert_into(invitations::table)
+                .values(&NewInvitation {
+                    email: request.email,
+                    token_hash: hash_token(&token),
+                    role: role.as_str().to_string(),
+                    invited_by,
+                    expires_at,
+                })
+                .returning(Invitation::as_returning())
+                .get_result(conn)?;
+
+            diesel::insert_into(invitation_groups::table)
+                .values(
+                    group_ids
+                        .iter()
+                        .map(|group_id| {
+                            (
+                                invitation_groups::invitation_id.eq(invitation.id),
+                                invitation_groups::group_id.eq(group_id),
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .execute(conn)?;
+
+            Ok(invitation)
+        })?;
+
+        Ok((InvitationResponse { invitation, group_ids }, token))
+    }
+
+    /// An invitation that can still be accepted
+    pub fn find_invitation(conn: &mut DbConnection, token: &str) -> Result<Invitation> {
+        invitations::table
+            .filter(invitations::token_hash.eq(hash_token(token)))
+            .filter(invitations::accepted_at.is_null())
+            .filter(invitations::expires_at.gt(diesel::dsl::now))
+            .select(Invitation::as_select())
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| AppError::BadRequest("Invalid or expired invitation".to_string()))
+    }
+
+    pub fn list_invitations(conn: &mut DbConnection) -> Result<Vec<InvitationResponse>> {
+        let invitations = invitations::table
+            .order(invitations::created_at.desc())
+            .select(Invitation::as_select())
+            .load::<Invitation>(conn)?;
+
+        let ids: Vec<Uuid> = invitations.iter().map(|invitation| invitation.id).collect();
+        let memberships = invitation_groups::table
+            .filter(invitation_groups::invitation_id.eq_any(&ids))
+            .select((invitation_groups::invitation_id, invitation_groups::group_id))
+            .load::<(Uuid, Uuid)>(conn)?;
+
+        let responses = invitations
+            .into_iter()
+            .map(|invitation| {
+                let group_ids = memberships
+                    .iter()
+                    .filter(|(invitation_id, _)| *invitation_id == invitation.id)
+                    .map(|(_, group_id)| *group_id)
+                    .collect();
+
+                InvitationResponse { invitation, group_ids }
+            })
+            .collect();
+
+        Ok(responses)
+    }
+
+    /// Withdraw an invitation nobody has accepted yet; false if there was none
+    pub fn revoke_invitation(conn: &mut DbConnection, invitation_id: Uuid) -> Result<bool> {
+        let deleted = diesel::delete(
+            invitations::table
+                .find(invitation_id)
+                .filter(invitations::accepted_at.is_null()),
+        )
+        .execute(conn)?;
+
+        Ok(deleted > 0)
+    }
+
+    // A newer token or a completed verification invalidates the others
+    fn expire_outstanding(conn: &mut DbConnection, user_id: Uuid) -> std::result::Result<usize, diesel::result::Error> {
+        diesel::update(
+            email_verification_tokens::table
+                .filter(email_verification_tokens::user_id.eq(user_id))
+                .filter(email_verification_tokens::used_at.is_null()),
+        )
+        .set(email_verification_tokens::used_at.eq(diesel::dsl::now.nullable()))
+        .execute(conn)
+    }
+}
@@ -0,0 +1,175 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::document::Document;
+use crate::models::rendition::{DocumentRendition, NewDocumentRendition};
+use crate::schema::document_renditions;
+use crate::services::{OnlyOfficeService, StorageService};
+
+pub struct RenditionService;
+
+impl RenditionService {
+    /// Storage key of a converted copy, next to the original file
+    pub fn rendition_key(file_path: &str, format: &str) -> String {
+        format!("{}.rendition.{}", file_path, format)
+    }
+
+    /// The document's file type, taken from its extension
+    pub fn source_file_type(document: &Document) -> Option<String> {
+        std::path::Path::new(&document.name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+    }
+
+    /// Fail unless the document can be converted to `format`
+    pub fn ensure_convertible(document: &Document, format: &str) -> Result<String> {
+        if document.is_folder {
+            return Err(AppError::BadRequest("Cannot convert a folder".to_string()));
+        }
+
+        let file_type = Self::source_file_type(document)
+            .ok_or_else(|| AppError::BadRequest("Document has no file extension".to_string()))?;
+
+        if !OnlyOfficeService::conversion_targets(&file_type).contains(&format) {
+            return Err(AppError::BadRequest(format!(
+                "Cannot convert {} to {}",
+                file_type, format
+            )));
+        }
+
+        Ok(file_type)
+    }
+
+    /// Convert the current version of a document and return the bytes
+    pub async fn convert(
+        storage_service: &StorageService,
+        onlyoffice_service: &OnlyOfficeService,
+        document: &Document,
+        format: &str,
+    ) -> Result<Vec<u8>> {
+        let file_type = Self::ensure_convertible(document, format)?;
+        let file_url = storage_service
+            .get_file_url_for_onlyoffice(&document.file_path, 3600)
+            .await?;
+
+        // One key per source revision lets Document Server reuse earlier results
+        let key = format!("{}_{}_{}", document.id, document.version, format);
+
+        onlyoffice_service
+            .convert(&file_url, &file_type, format, &document.name, &key)
+            .await
+    }
+
+    /// Rendition of the current version in `format`, converting it if needed
+    pub async fn get_or_create(
+        conn: &mut DbConnection,
+        storage_service: &StorageService,
+        onlyoffice_service: &OnlyOfficeService,
+        document: &Document,
+        format: &str,
+        user_id: Uuid,
+    ) -> Result<DocumentRendition> {
+        if let Some(existing) = Self::find(conn, document, format)? {
+            return Ok(existing);
+        }
+
+        let data = Self::convert(storage_service, onlyoffice_service, document, format).await?;
+
+        let key = Self::rendition_key(&document.file_path, format);
+        storage_service.write_file(&key, data.clone()).await?;
+
+        let new_rendition = NewDocumentRendition {
+            document_id: document.id,
+            version: document.version,
+            format: format.to_string(),
+            file_path: key,
+            file_size: data.len() as i64,
+            created_by: user_id,
+        };
+
+        let rendition = diesel::insert_into(document_renditions::table)
+            .values(&new_rendition)
+            .on_conflict((
+                document_renditions::document_id,
+                document_renditions::version,
+                document_renditions::format,
+            ))
+            .do_update()
+            .set(document_renditions::file_size.eq(new_rendition.file_size))
+            .returning(DocumentRendition::as_returning())
+            .get_result(conn)?;
+
+        Self::remove_outdated(conn, storage_service, document).await?;
+
+        Ok(rendition)
+    }
+
+    /// Existing rendition of the document's current version
+    pub fn find(conn: &mut DbConnection, document: &Document, format: &str) -> Result<Option<DocumentRendition>> {
+        let rendition = document_renditions::table
+            .filter(document_renditions::document_id.eq(document.id))
+            .filter(document_renditions::version.eq(document.version))
+            .filter(document_renditions::format.eq(format))
+            .select(DocumentRendition::as_select())
+            .first(conn)
+            .optional()?;
+
+        Ok(rendition)
+    }
+
+    pub fn list(conn: &mut DbConnection, document_id: Uuid) -> Result<Vec<DocumentRendition>> {
+        let renditions = document_renditions::table
+            .filter(document_renditions::document_id.eq(document_id))
+            .order((document_renditions::version.desc(), document_renditions::format.asc()))
+            .select(DocumentRendition::as_select())
+            .load(conn)?;
+
+        Ok(renditions)
+    }
+
+    /// Delete every rendition of a document and its files
+    pub async fn remove_all(
+        conn: &mut DbConnection,
+        storage_service: &StorageService,
+        document_id: Uuid,
+    ) -> Result<()> {
+        let renditions = Self::list(conn, document_id)?;
+
+        diesel::delete(
+            document_renditions::table.filter(document_renditions::document_id.eq(document_id)),
+        )
+        .execute(conn)?;
+
+        for rendition in renditions {
+            storage_service.delete_file(&rendition.file_path).await?;
+        }
+
+        Ok(())
+    }
+
+    // Renditions of earlier versions are never served again
+    async fn remove_outdated(
+        conn: &mut DbConnection,
+        storage_service: &StorageService,
+        document: &Document,
+    ) -> Result<()> {
+        let outdated = diesel::delete(
+            document_renditions::table
+                .filter(document_renditions::document_id.eq(document.id))
+                .filter(document_renditions::version.lt(document.version)),
+        )
+        .returning(document_renditions::file_path)
+        .get_results::<String>(conn)?;
+
+        for path in outdated {
+            if let Err(e) = storage_service.delete_file(&path).await {
+                tracing::warn!("Failed to delete outdated rendition {}: {}", path, e);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,347 @@
+use chrono::{Duration, NaiveDateTime};
+use diesel::prelude::*;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::db::{AppState, DbConnection};
+use crate::error::{AppError, Result};
+use crate::models::document::Document;
+use crate::models::retention::{
+    LegalHold, NewRetentionAuditLog, RetentionPolicy, RetentionStatusResponse,
+};
+use crate::schema::{document_versions, documents, legal_holds, retention_audit_logs, retention_policies};
+use crate::services::{FolderService, RenditionService, SearchService, StorageService, ThumbnailService};
+
+pub struct RetentionService;
+
+impl RetentionService {
+    /// The document itself followed by its ancestor folders, nearest first
+    pub fn ancestor_ids(conn: &mut DbConnection, document: &Document) -> Result<Vec<Uuid>> {
+        let mut ids = vec![document.id];
+        ids.extend(FolderService::ancestor_chain(conn, document.parent_folder_id)?);
+
+        Ok(ids)
+    }
+
+    /// Unreleased legal holds on the document or any folder containing it
+    pub fn active_holds(conn: &mut DbConnection, document: &Document) -> Result<Vec<LegalHold>> {
+        let ancestors = Self::ancestor_ids(conn, document)?;
+
+        let holds = legal_holds::table
+            .filter(legal_holds::document_id.eq_any(&ancestors))
+            .filter(legal_holds::released_at.is_null())
+            .select(LegalHold::as_select())
+            .load(conn)?;
+
+        Ok(holds)
+    }
+
+    /// Legal hold freezes a document regardless of the caller's permissions
+    pub fn ensure_not_on_hold(conn: &mut DbConnection, document: &Document) -> Result<()> {
+        if !Self::active_holds(conn, document)?.is_empty() {
+            return Err(AppError::Forbidden("Document is under legal hold".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Active policies that apply to a document
+    pub fn matching_policies(conn: &mut DbConnection, document: &Document) -> Result<Vec<RetentionPolicy>> {
+        let ancestors = Self::ancestor_ids(conn, document)?;
+        Self::policies_within(conn, document, &ancestors)
+    }
+
+    // Active policies that would apply to a document inside the given folders
+    fn policies_within(
+        conn: &mut DbConnection,
+        document: &Document,
+        ancestors: &[Uuid],
+    ) -> Result<Vec<RetentionPolicy>> {
+        if document.is_folder {
+            return Ok(Vec::new());
+        }
+
+        let tags: Vec<String> = document
+            .tags
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let policies = retention_policies::table
+            .filter(retention_policies::is_active.eq(true))
+            .select(RetentionPolicy::as_select())
+            .load::<RetentionPolicy>(conn)?;
+
+        Ok(policies
+            .into_iter()
+            .filter(|p| p.folder_id.map(|f| ancestors.contains(&f)).unwrap_or(true))
+            .filter(|p| p.matches_tags(&tags))
+            .filter(|p| p.matches_mime_type(&document.mime_type))
+            .collect())
+    }
+
+    /// When the longest matching retention period ends, with the policies involved
+    pub fn retained_until(
+        conn: &mut DbConnection,
+        document: &Document,
+    ) -> Result<Option<(NaiveDateTime, Vec<RetentionPolicy>)>> {
+        let policies = Self::matching_policies(conn, document)?;
+
+        Ok(Self::longest_retention(document, &policies).map(|until| (until, policies)))
+    }
+
+    /// Fail if changing a document's tags or type would end an unexpired
+    /// retention period sooner
+    pub fn ensure_update_keeps_retention(conn: &mut DbConnection, before: &Document, after: &Document) -> Result<()> {
+        let ancestors = Self::ancestor_ids(conn, before)?;
+        Self::ensure_retention_kept(conn, before, &ancestors, after, &ancestors)
+    }
+
+    /// Fail if moving a document or folder would end an unexpired retention
+    /// period of anything it contains sooner
+    pub fn ensure_move_keeps_retention(
+        conn: &mut DbConnection,
+        document: &Document,
+        target_folder_id: Option<Uuid>,
+    ) -> Result<()> {
+        let target_chain = FolderService::ancestor_chain(conn, target_folder_id)?;
+        let old_parents = Self::ancestor_ids(conn, document)?.len() - 1;
+
+        let affected = if document.is_folder {
+            let ids = FolderService::descendant_ids(conn, document.id)?;
+            documents::table
+                .filter(documents::id.eq_any(&ids))
+                .filter(documents::deleted_at.is_null())
+                .filter(documents::is_folder.eq(false))
+                .select(Document::as_select())
+                .load::<Document>(conn)?
+        } else {
+            vec![document.clone()]
+        };
+
+        for document in &affected {
+            // The chain up to the moved item stays, the folders above it change
+            let before = Self::ancestor_ids(conn, document)?;
+            let mut after = before[..before.len().saturating_sub(old_parents)].to_vec();
+            after.extend(&target_chain);
+
+            Self::ensure_retention_kept(conn, document, &before, document, &after)?;
+        }
+
+        Ok(())
+    }
+
+    fn ensure_retention_kept(
+        conn: &mut DbConnection,
+        before: &Document,
+        before_ancestors: &[Uuid],
+        after: &Document,
+        after_ancestors: &[Uuid],
+    ) -> Result<()> {
+        let policies = Self::policies_within(conn, before, before_ancestors)?;
+        let Some(until) = Self::longest_retention(before, &policies) else {
+            return Ok(());
+        };
+
+        if until <= chrono::Local::now().naive_local() {
+            return Ok(());
+        }
+
+        let policies = Self::policies_within(conn, after, after_ancestors)?;
+        if Self::longest_retention(after, &policies).is_some_and(|after_until| after_until >= until) {
+            return Ok(());
+        }
+
+        Err(AppError::Forbidden(format!(
+            "Document {} is under retention until {}; the change would end it sooner",
+            before.name, until
+        )))
+    }
+
+    // End of the longest retention period among the policies; one ending
+    // past the calendar keeps the document forever
+    fn longest_retention(document: &Document, policies: &[RetentionPolicy]) -> Option<NaiveDateTime> {
+        policies
+            .iter()
+            .map(|p| {
+                document
+                    .created_at
+                    .checked_add_signed(Duration::days(p.retention_days as i64))
+                    .unwrap_or(NaiveDateTime::MAX)
+            })
+            .max()
+    }
+
+    /// Fail if a legal hold or unexpired retention period blocks deletion
+    pub fn ensure_deletable(conn: &mut DbConnection, document: &Document) -> Result<()> {
+        Self::ensure_not_on_hold(conn, document)?;
+
+        if let Some((until, _)) = Self::retained_until(conn, document)? {
+            if until > chrono::Local::now().naive_local() {
+                return Err(AppError::Forbidden(format!(
+                    "Document is under retention until {}",
+                    until
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn retention_status(conn: &mut DbConnection, document: &Document) -> Result<RetentionStatusResponse> {
+        let retained = Self::retained_until(conn, document)?;
+        let legal_holds = Self::active_holds(conn, document)?;
+
+        Ok(RetentionStatusResponse {
+            document_id: document.id,
+            retained_until: retained.as_ref().map(|(until, _)| *until),
+            policy_ids: retained
+                .map(|(_, policies)| policies.into_iter().map(|p| p.id).collect())
+                .unwrap_or_default(),
+            legal_holds,
+        })
+    }
+
+    /// Delete documents whose retention period has ended.
+    ///
+    /// Documents under legal hold, or still retained by a longer matching
+    /// policy, are left alone. Every deletion is written to the audit log.
+    pub async fn run_sweep(state: &AppState) -> Result<usize> {
+        let mut conn = state.get_connection()?;
+        let now = chrono::Local::now().naive_local();
+
+        let policies = retention_policies::table
+            .filter(retention_policies::is_active.eq(true))
+            .select(RetentionPolicy::as_select())
+            .load::<RetentionPolicy>(&mut conn)?;
+
+        let storage_service = StorageService::new(&state.config.minio)?;
+        let search_service = SearchService::new(&state.config.meilisearch)?;
+
+        let mut seen = HashSet::new();
+        let mut deleted = 0;
+
+        for policy in &policies {
+            // Nothing can be old enough for a period reaching back past the calendar
+            let Some(cutoff) = now.checked_sub_signed(Duration::days(policy.retention_days as i64)) else {
+                continue;
+            };
+            let candidates = documents::table
+                .filter(documents::deleted_at.is_null())
+                .filter(documents::is_folder.eq(false))
+                .filter(documents::created_at.lt(cutoff))
+                .select(Document::as_select())
+                .load::<Document>(&mut conn)?;
+
+            for document in candidates {
+                if !seen.insert(document.id) {
+                    continue;
+                }
+
+                let Some((until, matched)) = Self::retained_until(&mut conn, &document)? else {
+                    continue;
+                };
+
+                if until > now || !Self::active_holds(&mut conn, &document)?.is_empty() {
+                    continue;
+                }
+
+                // Attribute the deletion to the policy that retained the document longest
+                let policy_id = matched
+                    .iter()
+                    .max_by_key(|p| p.retention_days)
+                    .map(|p| p.id);
+
+                Self::purge_document(&mut conn, &storage_service, &search_service, &document).await?;
+
+                let entry = NewRetentionAuditLog {
+                    policy_id,
+                    document_id: document.id,
+                    document_name: document.name.clone(),
+                    action: "delete".to_string(),
+                    details: Some(format!("Retention expired at {}", until)),
+                };
+
+                diesel::insert_into(retention_audit_logs::table)
+                    .values(&entry)
+                    .execute(&mut conn)?;
+
+                tracing::info!("Retention deleted document {} ({})", document.id, document.name);
+                deleted += 1;
+            }
+        }
+
+        Ok(deleted)
+    }
+
+    /// Run the retention sweep forever at the configured interval; 0 turns it off
+    pub async fn run_scheduler(state: AppState) {
+        let interval_secs = state.config.retention.sweep_interval_secs;
+        if interval_secs == 0 {
+            return;
+        }
+
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            match Self::run_sweep(&state).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Retention sweep deleted {} documents", count),
+                Err(e) => tracing::error!("Retention sweep failed: {}", e),
+            }
+        }
+    }
+
+    // Soft delete the document and remove its current file and all retained versions
+    async fn purge_document(
+        conn: &mut DbConnection,
+        storage_service: &StorageService,
+        search_service: &SearchService,
+        document: &Document,
+    ) -> Result<()> {
+        let version_paths = document_versions::table
+            .filter(document_versions::document_id.eq(document.id))
+            .select((document_versions::file_path, document_versions::changes_path))
+            .load::<(String, Option<String>)>(conn)?;
+
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            diesel::delete(
+                document_versions::table.filter(document_versions::document_id.eq(document.id)),
+            )
+            .execute(conn)?;
+
+            diesel::update(documents::table.find(document.id))
+                .set(documents::deleted_at.eq(diesel::dsl::now.nullable()))
+                .execute(conn)?;
+
+            Ok(())
+        })?;
+
+        search_service.delete_document(document.id).await?;
+
+        if let Err(e) = ThumbnailService::remove(conn, storage_service, document.id).await {
+            tracing::warn!("Failed to delete thumbnail of {} during retention purge: {}", document.id, e);
+        }
+
+        if let Err(e) = RenditionService::remove_all(conn, storage_service, document.id).await {
+            tracing::warn!("Failed to delete renditions of {} during retention purge: {}", document.id, e);
+        }
+
+        let mut paths: HashSet<String> = version_paths
+            .into_iter()
+            .flat_map(|(file_path, changes_path)| std::iter::once(file_path).chain(changes_path))
+            .collect();
+        paths.insert(document.file_path.clone());
+        for path in paths {
+            if let Err(e) = storage_service.delete_file(&path).await {
+                tracing::warn!("Failed to delete {} during retention purge: {}", path, e);
+            }
+        }
+
+        Ok(())
+    }
+}
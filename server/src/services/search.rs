@@ -1,6 +1,6 @@
 use meilisearch_sdk::{client::Client, indexes::Index, search::SearchResults};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value as JsonValue};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -9,6 +9,8 @@ use crate::error::{AppError, Result};
 use crate::models::document::Document;
 
 const DOCUMENTS_INDEX: &str = "documents";
+const FILTERABLE_ATTRIBUTES: [&str; 6] = ["id", "owner_id", "mime_type", "is_folder", "status", "tags"];
+const SORTABLE_ATTRIBUTES: [&str; 3] = ["created_at", "updated_at", "name"];
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DocumentSearchIndex {
@@ -18,7 +20,9 @@ pub struct DocumentSearchIndex {
     pub mime_type: String,
     pub owner_id: String,
     pub is_folder: bool,
+    pub status: String,
     pub tags: Vec<String>,
+    pub metadata: Option<JsonValue>,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -32,11 +36,13 @@ impl From<Document> for DocumentSearchIndex {
             mime_type: doc.mime_type,
             owner_id: doc.owner_id.to_string(),
             is_folder: doc.is_folder,
+            status: doc.status,
             tags: doc.tags
                 .unwrap_or_default()
                 .into_iter()
                 .filter_map(|t| t)
                 .collect(),
+            metadata: doc.metadata,
             created_at: doc.created_at.and_utc().timestamp(),
             updated_at: doc.updated_at.and_utc().timestamp(),
         }
@@ -72,15 +78,39 @@ impl SearchService {
             .await
             .map_err(|e| AppError::InternalServerError(format!("Failed to set searchable attributes: {}", e)))?;
 
+        self.set_attribute_settings(&[], &[]).await
+    }
+
+    /// Configure filterable and sortable attributes, including extra
+    /// attributes such as metadata schema fields
+    pub async fn set_attribute_settings(
+        &self,
+        extra_filterable: &[String],
+        extra_sortable: &[String],
+    ) -> Result<()> {
+        let index = self.client.index(DOCUMENTS_INDEX);
+
+        let filterable: Vec<String> = FILTERABLE_ATTRIBUTES
+            .iter()
+            .map(|a| a.to_string())
+            .chain(extra_filterable.iter().cloned())
+            .collect();
+
+        let sortable: Vec<String> = SORTABLE_ATTRIBUTES
+            .iter()
+            .map(|a| a.to_string())
+            .chain(extra_sortable.iter().cloned())
+            .collect();
+
         // Configure filterable attributes
         index
-            .set_filterable_attributes(&["owner_id", "mime_type", "is_folder", "tags"])
+            .set_filterable_attributes(&filterable)
             .await
             .map_err(|e| AppError::InternalServerError(format!("Failed to set filterable attributes: {}", e)))?;
 
         // Configure sortable attributes
         index
-            .set_sortable_attributes(&["created_at", "updated_at", "name"])
+            .set_sortable_attributes(&sortable)
             .await
             .map_err(|e| AppError::InternalServerError(format!("Failed to set sortable attributes: {}", e)))?;
 
@@ -118,6 +148,7 @@ impl SearchService {
         &self,
         query: &str,
         filters: Option<String>,
+        sort: &[&str],
         limit: Option<usize>,
         offset: Option<usize>,
     ) -> Result<SearchResults<DocumentSearchIndex>> {
@@ -130,6 +161,10 @@ impl SearchService {
             search_query.with_filter(filter);
         }
         
+        if !sort.is_empty() {
+            search_query.with_sort(sort);
+        }
+
         if let Some(limit) = limit {
             search_query.with_limit(limit);
         }
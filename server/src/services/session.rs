@@ -0,0 +1,250 @@
+use std::net::SocketAddr;
+
+use axum::http::{header, HeaderMap};
+use chrono::Duration;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::config::{JwtConfig, RateLimitConfig};
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::session::{NewUserSession, UserSession};
+use crate::models::user::{LoginResponse, User};
+use crate::schema::{user_sessions, users};
+use crate::services::RateLimiter;
+use crate::utils::{encode_jwt, generate_token, hash_token, Claims};
+
+// 代登录会话的有效期，刷新令牌不会延长
+const IMPERSONATION_MINUTES: i64 = 60;
+
+/// Device and network details recorded for a session
+#[derive(Debug, Default)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl ClientInfo {
+    /// Read the client of a request. The address comes from `X-Forwarded-For`
+    /// only behind a trusted proxy; anyone can send that header.
+    pub fn from_request(config: &RateLimitConfig, headers: &HeaderMap, peer: SocketAddr) -> Self {
+        Self {
+            user_agent: header_value(headers, header::USER_AGENT.as_str()),
+            ip_address: Some(RateLimiter::client_ip(config, headers, peer)),
+        }
+    }
+
+    /// The client address a proxy reports; behind a proxy the first
+    /// `X-Forwarded-For` entry is the client
+    pub fn forwarded_ip(headers: &HeaderMap) -> Option<String> {
+        header_value(headers, "x-forwarded-for")
+            .and_then(|value| value.split(',').next().map(|ip| ip.trim().to_string()))
+            .or_else(|| header_value(headers, "x-real-ip"))
+            .map(|ip| ip.chars().take(45).collect())
+    }
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+pub struct SessionService;
+
+impl SessionService {
+    /// Start a session for a user who just authenticated and issue its tokens
+    pub fn start(
+        conn: &mut DbConnection,
+        config: &JwtConfig,
+        user: User,
+        client: ClientInfo,
+    ) -> Result<LoginResponse> {
+        let expires_at = Self::refresh_expiry(config);
+
+        Self::open(conn, config, user, client, expires_at, None)
+    }
+
+    /// Start a session for a user on behalf of an admin.
+    ///
+    /// The session ends after a fixed time; refreshing doesn't extend it.
+    pub fn impersonate(
+        conn: &mut DbConnection,
+        config: &JwtConfig,
+        admin_id: Uuid,
+        user: User,
+        client: ClientInfo,
+    ) -> Result<LoginResponse> {
+        let expires_at = chrono::Local::now().naive_local() + Duration::minutes(IMPERSONATION_MINUTES);
+
+        Self::open(conn, config, user, client, expires_at, Some(admin_id))
+    }
+
+    /// Exchange a refresh token for a new access token, rotating the refresh token
+    pub fn refresh(conn: &mut DbConnection, config: &JwtConfig, refresh_token: &str) -> Result<LoginResponse> {
+        let invalid = || AppError::Unauthorized("Invalid or expired refresh token".to_string());
+
+        let (session, user) = user_sessions::table
+            .inner_join(users::table)
+            .filter(user_sessions::refresh_token_hash.eq(hash_token(refresh_token)))
+            .filter(user_sessions::revoked_at.is_null())
+            .filter(user_sessions::expires_at.gt(diesel::dsl::now))
+            .select((UserSession::as_select(), User::as_select()))
+            .first::<(UserSession, User)>(conn)
+            .optional()?
+            .ok_or_else(invalid)?;
+
+        if !user.is_active {
+            return Err(AppError::Unauthorized("Account is not active".to_string()));
+        }
+
+        let new_refresh_token = generate_token();
+        let expires_at = match session.impersonator_id {
+            Some(_) => session.expires_at,
+            None => Self::refresh_expiry(config),
+        };
+
+        // Only succeeds once per token, so a replayed refresh token loses the race
+        let rotated = diesel::update(
+            user_sessions::table
+                .find(session.id)
+                .filter(user_sessions::refresh_token_hash.eq(&session.refresh_token_hash)),
+        )
+        .set((
+            user_sessions::refresh_token_hash.eq(hash_token(&new_refresh_token)),
+            user_sessions::last_used_at.eq(diesel::dsl::now),
+            user_sessions::expires_at.eq(expires_at),
+        ))
+        .execute(conn)?;
+
+        if rotated == 0 {
+            return Err(invalid());
+        }
+
+        Self::issue(config, user, session.id, new_refresh_token)
+    }
+
+    /// Check that a token's session is still open and its user still active.
+    ///
+    /// Returns the user's current role, which may have changed since the
+    /// token was issued, and the admin impersonating the user, if any.
+    pub fn validate(conn: &mut DbConnection, session_id: Uuid, user_id: Uuid) -> Result<(String, Option<Uuid>)> {
+        let session = user_sessions::table
+            .inner_join(users::table)
+            .filter(user_sessions::id.eq(session_id))
+            .filter(user_sessions::user_id.eq(user_id))
+            .filter(user_sessions::revoked_at.is_null())
+            .filter(user_sessions::expires_at.gt(diesel::dsl::now))
+            .filter(users::is_active.eq(true))
+            .select((users::role, user_sessions::impersonator_id))
+            .first::<(String, Option<Uuid>)>(conn)
+            .optional()?;
+
+        session.ok_or_else(|| AppError::Unauthorized("Session has been revoked".to_string()))
+    }
+
+    /// Open sessions of a user, most recently used first
+    pub fn list_active(conn: &mut DbConnection, user_id: Uuid) -> Result<Vec<UserSession>> {
+        let sessions = user_sessions::table
+            .filter(user_sessions::user_id.eq(user_id))
+            .filter(user_sessions::revoked_at.is_null())
+            .filter(user_sessions::expires_at.gt(diesel::dsl::now))
+            .order(user_sessions::last_used_at.desc())
+            .select(UserSession::as_select())
+            .load(conn)?;
+
+        Ok(sessions)
+    }
+
+    /// Revoke one of a user's sessions; false if it wasn't open
+    pub fn revoke(conn: &mut DbConnection, user_id: Uuid, session_id: Uuid) -> Result<bool> {
+        let revoked = diesel::update(
+            user_sessions::table
+                .find(session_id)
+                .filter(user_sessions::user_id.eq(user_id))
+                .filter(user_sessions::revoked_at.is_null()),
+        )
+        .set(user_sessions::revoked_at.eq(diesel::dsl::now.nullable()))
+        .execute(conn)?;
+
+        Ok(revoked > 0)
+    }
+
+    /// Revoke every open session of a user
+    pub fn revoke_all(conn: &mut DbConnection, user_id: Uuid) -> Result<usize> {
+        let revoked = diesel::update(
+            user_sessions::table
+                .filter(user_sessions::user_id.eq(user_id))
+                .filter(user_sessions::revoked_at.is_null()),
+        )
+        .set(user_sessions::revoked_at.eq(diesel::dsl::now.nullable()))
+        .execute(conn)?;
+
+        Ok(revoked)
+    }
+
+    /// Revoke every open session of a user except `keep`
+    pub fn revoke_others(conn: &mut DbConnection, user_id: Uuid, keep: Uuid) -> Result<usize> {
+        let revoked = diesel::update(
+            user_sessions::table
+                .filter(user_sessions::user_id.eq(user_id))
+                .filter(user_sessions::id.ne(keep))
+                .filter(user_sessions::revoked_at.is_null()),
+        )
+        .set(user_sessions::revoked_at.eq(diesel::dsl::now.nullable()))
+        .execute(conn)?;
+
+        Ok(revoked)
+    }
+
+    fn open(
+        conn: &mut DbConnection,
+        config: &JwtConfig,
+        user: User,
+        client: ClientInfo,
+        expires_at: chrono::NaiveDateTime,
+        impersonator_id: Option<Uuid>,
+    ) -> Result<LoginResponse> {
+        let refresh_token = generate_token();
+
+        let session = diesel::insert_into(user_sessions::table)
+            .values(&NewUserSession {
+                user_id: user.id,
+                refresh_token_hash: hash_token(&refresh_token),
+                user_agent: client.user_agent,
+                ip_address: client.ip_address,
+                expires_at,
+                impersonator_id,
+            })
+            .returning(UserSession::as_returning())
+            .get_result(conn)?;
+
+        Self::issue(config, user, session.id, refresh_token)
+    }
+
+    fn issue(config: &JwtConfig, user: User, session_id: Uuid, refresh_token: String) -> Result<LoginResponse> {
+        let claims = Claims::new(
+            user.id,
+            user.username.clone(),
+            user.role.clone(),
+            session_id,
+            config.expiration,
+        );
+
+        let token = encode_jwt(&claims, &config.secret)?;
+
+        Ok(LoginResponse {
+            token,
+            refresh_token,
+            expires_in: config.expiration,
+            user: user.into(),
+            recovery_codes: None,
+        })
+    }
+
+    fn refresh_expiry(config: &JwtConfig) -> chrono::NaiveDateTime {
+        chrono::Local::now().naive_local() + Duration::seconds(config.refresh_expiration)
+    }
+}
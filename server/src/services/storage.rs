@@ -50,6 +50,16 @@ impl StorageService {
         Ok(object_key)
     }
 
+    /// Write data under a caller-chosen key, e.g. for derived files
+    pub async fn write_file(&self, object_key: &str, file_data: Vec<u8>) -> Result<()> {
+        self.operator
+            .write(object_key, file_data)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to upload file: {}", e)))?;
+
+        Ok(())
+    }
+
     pub async fn download_file(&self, object_key: &str) -> Result<Vec<u8>> {
         let data = self.operator
             .read(object_key)
@@ -0,0 +1,295 @@
+use diesel::prelude::*;
+use diesel::sql_types::{Array, Text};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::config::TagConfig;
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::document::Document;
+use crate::models::tag::{NewTag, Tag, TagRewriteResponse, TagSummary, TagUsageRow};
+use crate::schema::{documents, tags};
+use crate::services::{LifecycleService, RetentionService, SearchService};
+
+const MAX_TAG_LENGTH: usize = 50;
+
+// SQL 中与 normalize() 等价的表达式
+const NORMALIZED_TAG: &str = "lower(btrim(regexp_replace(t, '\\s+', ' ', 'g')))";
+
+#[derive(QueryableByName)]
+struct DocumentIdRow {
+    #[diesel(sql_type = diesel::sql_types::Uuid)]
+    id: Uuid,
+}
+
+pub struct TagService;
+
+impl TagService {
+    /// Trim, collapse inner whitespace and lowercase a tag
+    pub fn normalize(tag: &str) -> Option<String> {
+        let normalized = tag.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+
+        if normalized.is_empty() {
+            None
+        } else {
+            Some(normalized)
+        }
+    }
+
+    /// Normalize a list of tags, dropping empty entries and duplicates
+    pub fn normalize_all<I, S>(raw: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut tags = Vec::new();
+
+        for tag in raw.into_iter().filter_map(|t| Self::normalize(t.as_ref())) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        tags
+    }
+
+    /// Normalize user-supplied tags and, in controlled vocabulary mode,
+    /// reject any tag an admin hasn't defined.
+    pub fn prepare(conn: &mut DbConnection, config: &TagConfig, raw: Vec<String>) -> Result<Vec<String>> {
+        let tags = Self::normalize_all(raw);
+
+        if let Some(tag) = tags.iter().find(|t| t.chars().count() > MAX_TAG_LENGTH) {
+            return Err(AppError::ValidationError(format!(
+                "Tag '{}' is longer than {} characters",
+                tag, MAX_TAG_LENGTH
+            )));
+        }
+
+        if config.controlled_vocabulary && !tags.is_empty() {
+            let defined = tags::table
+                .filter(tags::name.eq_any(&tags))
+                .select(tags::name)
+                .load::<String>(conn)?;
+
+            let unknown: Vec<&str> = tags
+                .iter()
+                .filter(|t| !defined.contains(t))
+                .map(String::as_str)
+                .collect();
+
+            if !unknown.is_empty() {
+                return Err(AppError::ValidationError(format!(
+                    "Unknown tags: {}",
+                    unknown.join(", ")
+                )));
+            }
+        }
+
+        Ok(tags)
+    }
+
+    /// Every tag in use or in the vocabulary, with the number of documents using it
+    pub fn list_usage(conn: &mut DbConnection) -> Result<Vec<TagSummary>> {
+        let usage = diesel::sql_query(format!(
+            "SELECT {normalized} AS name, COUNT(DISTINCT d.id) AS document_count \
+             FROM documents d, unnest(d.tags) AS t \
+             WHERE d.deleted_at IS NULL AND t IS NOT NULL AND {normalized} <> '' \
+             GROUP BY 1",
+            normalized = NORMALIZED_TAG
+        ))
+        .load::<TagUsageRow>(conn)?;
+
+        let vocabulary = tags::table
+            .select(Tag::as_select())
+            .load::<Tag>(conn)?;
+
+        let mut summaries: HashMap<String, TagSummary> = usage
+            .into_iter()
+            .map(|row| {
+                (row.name.clone(), TagSummary {
+                    name: row.name,
+                    document_count: row.document_count,
+                    defined: false,
+                    description: None,
+                })
+            })
+            .collect();
+
+        for tag in vocabulary {
+            let summary = summaries.entry(tag.name.clone()).or_insert_with(|| TagSummary {
+                name: tag.name.clone(),
+                document_count: 0,
+                defined: true,
+                description: None,
+            });
+            summary.defined = true;
+            summary.description = tag.description;
+        }
+
+        let mut summaries: Vec<TagSummary> = summaries.into_values().collect();
+        summaries.sort_by(|a, b| {
+            b.document_count
+                .cmp(&a.document_count)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        Ok(summaries)
+    }
+
+    /// Replace every tag whose normalized form is in `sources` with `target`
+    /// on all documents and reindex the documents that changed.
+    pub async fn replace_tags(
+        conn: &mut DbConnection,
+        search_service: &SearchService,
+        sources: &[String],
+        target: &str,
+    ) -> Result<TagRewriteResponse> {
+        let ids = diesel::sql_query(format!(
+            "SELECT DISTINCT d.id FROM documents d, unnest(d.tags) AS t \
+             WHERE {} = ANY($1)",
+            NORMALIZED_TAG
+        ))
+        .bind::<Array<Text>, _>(sources)
+        .load::<DocumentIdRow>(conn)?
+        .into_iter()
+        .map(|row| row.id)
+        .collect::<Vec<_>>();
+
+        Self::rewrite_documents(conn, search_service, &ids, |tag| {
+            if sources.contains(&tag) {
+                target.to_string()
+            } else {
+                tag
+            }
+        })
+        .await
+    }
+
+    /// Rewrite the tags of every document into normalized form
+    pub async fn normalize_documents(
+        conn: &mut DbConnection,
+        search_service: &SearchService,
+    ) -> Result<TagRewriteResponse> {
+        let ids = documents::table
+            .filter(documents::tags.is_not_null())
+            .select(documents::id)
+            .load::<Uuid>(conn)?;
+
+        Self::rewrite_documents(conn, search_service, &ids, |tag| tag).await
+    }
+
+    /// Rename or merge vocabulary entries to follow a tag rewrite
+    pub fn replace_vocabulary(
+        conn: &mut DbConnection,
+        sources: &[String],
+        target: &str,
+        admin_id: Uuid,
+    ) -> Result<()> {
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            let removed = diesel::delete(
+                tags::table
+                    .filter(tags::name.eq_any(sources))
+                    .filter(tags::name.ne(target)),
+            )
+            .execute(conn)?;
+
+            if removed > 0 {
+                diesel::insert_into(tags::table)
+                    .values(&NewTag {
+                        name: target.to_string(),
+                        description: None,
+                        created_by: admin_id,
+                    })
+                    .on_conflict(tags::name)
+                    .do_nothing()
+                    .execute(conn)?;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    // Apply `map` to the normalized tags of each document, saving and
+    // reindexing only the documents whose tags actually changed. Documents
+    // that can't be modified (legal hold, read-only status, or a retention
+    // period the new tags would end sooner) are left alone and reported.
+    async fn rewrite_documents<F>(
+        conn: &mut DbConnection,
+        search_service: &SearchService,
+        ids: &[Uuid],
+        map: F,
+    ) -> Result<TagRewriteResponse>
+    where
+        F: Fn(String) -> String,
+    {
+        let candidates = documents::table
+            .filter(documents::id.eq_any(ids))
+            .select(Document::as_select())
+            .load::<Document>(conn)?;
+
+        let mut changes = Vec::new();
+        let mut skipped_documents = Vec::new();
+        for document in candidates {
+            let current: Vec<String> = document
+                .tags
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .flatten()
+                .collect();
+
+            let rewritten = Self::normalize_all(
+                Self::normalize_all(&current).into_iter().map(&map),
+            );
+
+            if rewritten == current {
+                continue;
+            }
+
+            let new_tags: Vec<Option<String>> = rewritten.into_iter().map(Some).collect();
+            let after = Document { tags: Some(new_tags.clone()), ..document.clone() };
+
+            let shortens_retention = match RetentionService::ensure_update_keeps_retention(conn, &document, &after) {
+                Ok(()) => false,
+                Err(AppError::Forbidden(_)) => true,
+                Err(e) => return Err(e),
+            };
+
+            if shortens_retention
+                || LifecycleService::is_read_only(&document)
+                || !RetentionService::active_holds(conn, &document)?.is_empty()
+            {
+                skipped_documents.push(document.id);
+                continue;
+            }
+
+            changes.push((document.id, new_tags));
+        }
+
+        let updated = conn.transaction::<Vec<Document>, diesel::result::Error, _>(|conn| {
+            let mut updated = Vec::new();
+
+            for (document_id, new_tags) in changes {
+                let document = diesel::update(documents::table.find(document_id))
+                    .set(documents::tags.eq(Some(new_tags)))
+                    .returning(Document::as_returning())
+                    .get_result::<Document>(conn)?;
+
+                updated.push(document);
+            }
+
+            Ok(updated)
+        })?;
+
+        let updated_documents = updated.len();
+
+        let live: Vec<Document> = updated.into_iter().filter(|d| d.deleted_at.is_none()).collect();
+        if !live.is_empty() {
+            search_service.batch_index_documents(live).await?;
+        }
+
+        Ok(TagRewriteResponse { updated_documents, skipped_documents })
+    }
+}
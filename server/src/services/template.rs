@@ -0,0 +1,104 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::template::DocumentTemplate;
+use crate::schema::{document_templates, group_members};
+use crate::services::OnlyOfficeService;
+
+pub struct TemplateService;
+
+impl TemplateService {
+    /// Storage key for an uploaded template file
+    pub fn template_key(file_name: &str) -> String {
+        format!("templates/{}/{}", Uuid::new_v4(), file_name)
+    }
+
+    /// Template kind for a file name, from its extension
+    pub fn kind_for_file_name(file_name: &str) -> Option<&'static str> {
+        let extension = std::path::Path::new(file_name)
+            .extension()
+            .and_then(|ext| ext.to_str())?
+            .to_lowercase();
+
+        match extension.as_str() {
+            "docx" | "odt" => Some("document"),
+            "xlsx" | "ods" => Some("spreadsheet"),
+            "pptx" | "odp" => Some("presentation"),
+            _ => None,
+        }
+    }
+
+    pub fn mime_type_for_file_name(file_name: &str) -> &'static str {
+        let extension = std::path::Path::new(file_name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+
+        OnlyOfficeService::mime_type_for_file_type(&extension)
+    }
+
+    /// Templates offered to a user: the shared ones plus those of their groups
+    pub fn visible_templates(
+        conn: &mut DbConnection,
+        user_id: Uuid,
+        kind: Option<&str>,
+    ) -> Result<Vec<DocumentTemplate>> {
+        let user_groups = group_members::table
+            .filter(group_members::user_id.eq(user_id))
+            .select(group_members::group_id.nullable());
+
+        let mut query = document_templates::table
+            .filter(
+                document_templates::group_id
+                    .is_null()
+                    .or(document_templates::group_id.eq_any(user_groups)),
+            )
+            .into_boxed();
+
+        if let Some(kind) = kind {
+            query = query.filter(document_templates::kind.eq(kind));
+        }
+
+        let templates = query
+            .order((document_templates::kind.asc(), document_templates::name.asc()))
+            .select(DocumentTemplate::as_select())
+            .load(conn)?;
+
+        Ok(templates)
+    }
+
+    /// A template the user is allowed to use
+    pub fn find_visible(conn: &mut DbConnection, user_id: Uuid, template_id: Uuid) -> Result<DocumentTemplate> {
+        Self::visible_templates(conn, user_id, None)?
+            .into_iter()
+            .find(|template| template.id == template_id)
+            .ok_or_else(|| AppError::NotFound("Template not found".to_string()))
+    }
+
+    /// Name for a document created from a template, keeping its extension
+    pub fn document_name(template: &DocumentTemplate, requested: Option<&str>) -> Result<String> {
+        let extension = std::path::Path::new(&template.file_name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
+
+        let name = requested
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&template.name);
+
+        if name.len() > 255 {
+            return Err(AppError::ValidationError("Document name is too long".to_string()));
+        }
+
+        let suffix = format!(".{}", extension);
+        if name.to_lowercase().ends_with(&suffix.to_lowercase()) {
+            Ok(name.to_string())
+        } else {
+            Ok(format!("{}{}", name, suffix))
+        }
+    }
+}
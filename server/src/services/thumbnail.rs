@@ -0,0 +1,287 @@
+use diesel::prelude::*;
+use image::ImageFormat;
+use std::io::Cursor;
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::config::ThumbnailConfig;
+use crate::db::{AppState, DbConnection};
+use crate::error::{AppError, Result};
+use crate::models::document::Document;
+use crate::models::thumbnail::{
+    DocumentThumbnail, NewDocumentThumbnail, THUMBNAIL_FAILED, THUMBNAIL_READY,
+    THUMBNAIL_UNSUPPORTED,
+};
+use crate::schema::{document_thumbnails, documents};
+use crate::services::StorageService;
+
+const THUMBNAIL_SIZE: u32 = 256;
+const PDF_RENDER_SIZE: u32 = 512;
+const BATCH_SIZE: i64 = 20;
+const MAX_BATCHES_PER_TICK: usize = 10;
+// 超过此大小的文件不生成缩略图，避免占用过多内存
+const MAX_SOURCE_BYTES: i64 = 50 * 1024 * 1024;
+// 渲染一页 PDF 的最长时间，超时的进程会被终止，避免恶意或损坏的文件卡住后台任务
+const PDFTOPPM_TIMEOUT_SECS: u64 = 30;
+
+const IMAGE_MIME_TYPES: [&str; 5] = ["image/png", "image/jpeg", "image/gif", "image/webp", "image/bmp"];
+const PDF_MIME_TYPE: &str = "application/pdf";
+
+pub struct ThumbnailService;
+
+impl ThumbnailService {
+    /// Storage key of a file's thumbnail, next to the file itself
+    pub fn thumbnail_key(file_path: &str) -> String {
+        format!("{}.thumbnail.png", file_path)
+    }
+
+    pub fn is_supported(mime_type: &str) -> bool {
+        mime_type == PDF_MIME_TYPE || IMAGE_MIME_TYPES.contains(&mime_type)
+    }
+
+    pub fn get_thumbnail(conn: &mut DbConnection, document_id: Uuid) -> Result<Option<DocumentThumbnail>> {
+        let thumbnail = document_thumbnails::table
+            .filter(document_thumbnails::document_id.eq(document_id))
+            .select(DocumentThumbnail::as_select())
+            .first(conn)
+            .optional()?;
+
+        Ok(thumbnail)
+    }
+
+    /// Documents with no thumbnail for their current version
+    pub fn pending_documents(conn: &mut DbConnection, limit: i64) -> Result<Vec<Document>> {
+        let pending = documents::table
+            .left_join(document_thumbnails::table)
+            .filter(documents::deleted_at.is_null())
+            .filter(documents::is_folder.eq(false))
+            .filter(
+                document_thumbnails::id
+                    .is_null()
+                    .or(document_thumbnails::version.ne(documents::version)),
+            )
+            .order(documents::updated_at.asc())
+            .limit(limit)
+            .select(Document::as_select())
+            .load(conn)?;
+
+        Ok(pending)
+    }
+
+    /// Generate and store the thumbnail for a document's current version.
+    ///
+    /// Unsupported types and generation failures are recorded too, so the
+    /// document isn't retried until a new version is uploaded.
+    pub async fn generate(
+        conn: &mut DbConnection,
+        storage_service: &StorageService,
+        config: &ThumbnailConfig,
+        document: &Document,
+    ) -> Result<DocumentThumbnail> {
+        let previous = Self::get_thumbnail(conn, document.id)?;
+
+        if !Self::is_supported(&document.mime_type) || document.file_size > MAX_SOURCE_BYTES {
+            return Self::save(conn, document, THUMBNAIL_UNSUPPORTED, None, None);
+        }
+
+        let rendered = match storage_service.download_file(&document.file_path).await {
+            Ok(data) => Self::render(config, &document.mime_type, data).await,
+            Err(e) => Err(e),
+        };
+
+        let png = match rendered {
+            Ok(png) => png,
+            Err(e) => {
+                tracing::warn!("Thumbnail generation failed for {}: {}", document.id, e);
+                return Self::save(conn, document, THUMBNAIL_FAILED, None, Some(e.to_string()));
+            }
+        };
+
+        let key = Self::thumbnail_key(&document.file_path);
+        storage_service.write_file(&key, png).await?;
+
+        let thumbnail = Self::save(conn, document, THUMBNAIL_READY, Some(key.clone()), None)?;
+
+        // The previous version's thumbnail is no longer served
+        if let Some(old_path) = previous.and_then(|t| t.file_path) {
+            if old_path != key {
+                let _ = storage_service.delete_file(&old_path).await;
+            }
+        }
+
+        Ok(thumbnail)
+    }
+
+    /// Delete a document's thumbnail record and file
+    pub async fn remove(
+        conn: &mut DbConnection,
+        storage_service: &StorageService,
+        document_id: Uuid,
+    ) -> Result<()> {
+        let previous = Self::get_thumbnail(conn, document_id)?;
+
+        diesel::delete(
+            document_thumbnails::table.filter(document_thumbnails::document_id.eq(document_id)),
+        )
+        .execute(conn)?;
+
+        if let Some(path) = previous.and_then(|t| t.file_path) {
+            storage_service.delete_file(&path).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Generate thumbnails for one batch of pending documents
+    pub async fn run_batch(state: &AppState) -> Result<usize> {
+        let mut conn = state.get_connection()?;
+        let storage_service = StorageService::new(&state.config.minio)?;
+
+        let pending = Self::pending_documents(&mut conn, BATCH_SIZE)?;
+        let count = pending.len();
+
+        for document in pending {
+            if let Err(e) =
+                Self::generate(&mut conn, &storage_service, &state.config.thumbnail, &document).await
+            {
+                tracing::error!("Failed to store thumbnail for {}: {}", document.id, e);
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Generate thumbnails forever at the configured interval; 0 turns it off
+    pub async fn run_scheduler(state: AppState) {
+        let interval_secs = state.config.thumbnail.interval_secs;
+        if interval_secs == 0 {
+            return;
+        }
+
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            // Work through a backlog, but never spin on documents that keep failing
+            for _ in 0..MAX_BATCHES_PER_TICK {
+                match Self::run_batch(&state).await {
+                    Ok(0) => break,
+                    Ok(count) => {
+                        tracing::info!("Processed {} thumbnails", count);
+                        if (count as i64) < BATCH_SIZE {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Thumbnail generation failed: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    fn save(
+        conn: &mut DbConnection,
+        document: &Document,
+        status: &str,
+        file_path: Option<String>,
+        error: Option<String>,
+    ) -> Result<DocumentThumbnail> {
+        let row = NewDocumentThumbnail {
+            document_id: document.id,
+            version: document.version,
+            status: status.to_string(),
+            file_path,
+            error,
+        };
+
+        let thumbnail = diesel::insert_into(document_thumbnails::table)
+            .values(&row)
+            .on_conflict(document_thumbnails::document_id)
+            .do_update()
+            .set((&row, document_thumbnails::updated_at.eq(diesel::dsl::now)))
+            .returning(DocumentThumbnail::as_returning())
+            .get_result(conn)?;
+
+        Ok(thumbnail)
+    }
+
+    async fn render(config: &ThumbnailConfig, mime_type: &str, data: Vec<u8>) -> Result<Vec<u8>> {
+        let source = if mime_type == PDF_MIME_TYPE {
+            Self::render_pdf_page(config, &data).await?
+        } else {
+            data
+        };
+
+        // Decoding and resizing are CPU bound
+        tokio::task::spawn_blocking(move || Self::resize_to_png(&source))
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Thumbnail task failed: {}", e)))?
+    }
+
+    fn resize_to_png(source: &[u8]) -> Result<Vec<u8>> {
+        let image = image::load_from_memory(source)
+            .map_err(|e| AppError::BadRequest(format!("Failed to decode image: {}", e)))?;
+
+        let mut png = Vec::new();
+        image
+            .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to encode thumbnail: {}", e)))?;
+
+        Ok(png)
+    }
+
+    // Render the first page of a PDF to PNG with pdftoppm
+    async fn render_pdf_page(config: &ThumbnailConfig, pdf: &[u8]) -> Result<Vec<u8>> {
+        let work_dir = std::env::temp_dir().join(format!("dms-thumbnail-{}", Uuid::new_v4()));
+        tokio::fs::create_dir_all(&work_dir)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to create temp dir: {}", e)))?;
+
+        let result = Self::run_pdftoppm(config, pdf, &work_dir).await;
+        let _ = tokio::fs::remove_dir_all(&work_dir).await;
+
+        result
+    }
+
+    async fn run_pdftoppm(config: &ThumbnailConfig, pdf: &[u8], work_dir: &Path) -> Result<Vec<u8>> {
+        let input = work_dir.join("source.pdf");
+        let output_prefix = work_dir.join("page");
+
+        tokio::fs::write(&input, pdf)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to write temp file: {}", e)))?;
+
+        let render = tokio::process::Command::new(&config.pdftoppm_path)
+            .arg("-png")
+            .args(["-f", "1", "-l", "1"])
+            .arg("-singlefile")
+            .args(["-scale-to", &PDF_RENDER_SIZE.to_string()])
+            .arg(&input)
+            .arg(&output_prefix)
+            // Giving up on the output drops it, which has to stop the process too
+            .kill_on_drop(true)
+            .output();
+
+        let output = tokio::time::timeout(std::time::Duration::from_secs(PDFTOPPM_TIMEOUT_SECS), render)
+            .await
+            .map_err(|_| {
+                AppError::BadRequest(format!("pdftoppm timed out after {} seconds", PDFTOPPM_TIMEOUT_SECS))
+            })?
+            .map_err(|e| AppError::InternalServerError(format!("Failed to run pdftoppm: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(AppError::BadRequest(format!(
+                "pdftoppm failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        tokio::fs::read(output_prefix.with_extension("png"))
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to read rendered page: {}", e)))
+    }
+}
@@ -0,0 +1,283 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use diesel::prelude::*;
+use rand::RngCore;
+use totp_rs::{Algorithm, Secret, TOTP};
+use uuid::Uuid;
+
+use crate::config::{JwtConfig, TotpConfig};
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::totp::{TotpChallengeResponse, TotpSetupResponse};
+use crate::models::user::User;
+use crate::schema::{totp_recovery_codes, users};
+use crate::utils::{encode_challenge, hash_token, ChallengeClaims};
+
+const DIGITS: usize = 6;
+const STEP_SECONDS: u64 = 30;
+// 允许前后各一个时间步的时钟偏差
+const ALLOWED_DRIFT_STEPS: u64 = 1;
+const SECRET_BYTES: usize = 20;
+const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_BYTES: usize = 8;
+
+pub struct TotpService;
+
+impl TotpService {
+    /// Challenge returned by the password step when the user needs a second factor
+    pub fn challenge(jwt: &JwtConfig, totp: &TotpConfig, user: &User) -> Result<TotpChallengeResponse> {
+        let claims = ChallengeClaims::new(user.id, ChallengeClaims::TOTP, totp.challenge_expiration);
+
+        Ok(TotpChallengeResponse {
+            totp_required: true,
+            challenge_token: encode_challenge(&claims, &jwt.secret)?,
+            expires_in: totp.challenge_expiration,
+            enrollment_required: !user.totp_enabled,
+        })
+    }
+
+    /// Store a fresh secret for the user; it takes effect once a code from it is confirmed
+    pub fn begin_enrollment(conn: &mut DbConnection, config: &TotpConfig, user: &User) -> Result<TotpSetupResponse> {
+        if user.totp_enabled {
+            return Err(AppError::BadRequest("Two-factor authentication is already enabled".to_string()));
+        }
+
+        let mut bytes = [0u8; SECRET_BYTES];
+        rand::thread_rng().fill_bytes(&mut bytes);
+
+        let secret = match Secret::Raw(bytes.to_vec()).to_encoded() {
+            Secret::Encoded(secret) => secret,
+            Secret::Raw(_) => unreachable!(),
+        };
+
+        let otpauth_uri = Self::totp(&secret, Some(config.issuer.clone()), &user.username)?.get_url();
+
+        diesel::update(users::table.find(user.id))
+            .set((
+                users::totp_secret.eq(&secret),
+                users::totp_last_step.eq(None::<i64>),
+                users::updated_at.eq(diesel::dsl::now),
+            ))
+            .execute(conn)?;
+
+        Ok(TotpSetupResponse { secret, otpauth_uri })
+    }
+
+    /// Turn on two-factor authentication once the user proves the authenticator works.
+    ///
+    /// Returns the new recovery codes; they are only ever shown here.
+    pub fn confirm_enrollment(conn: &mut DbConnection, user: &User, code: &str) -> Result<Vec<String>> {
+        if user.totp_enabled {
+            return Err(AppError::BadRequest("Two-factor authentication is already enabled".to_string()));
+        }
+
+        if user.totp_secret.is_none() {
+            return Err(AppError::BadRequest("Set up an authenticator first".to_string()));
+        }
+
+        if !Self::verify_code(conn, user, code)? {
+            return Err(AppError::Unauthorized("Invalid verification code".to_string()));
+        }
+
+        let codes = conn.transaction::<Vec<String>, diesel::result::Error, _>(|conn| {
+            diesel::update(users::table.find(user.id))
+                .set((
+                    users::totp_enabled.eq(true),
+                    users::updated_at.eq(diesel::dsl::now),
+                ))
+                .execute(conn)?;
+
+            Self::replace_recovery_codes(conn, user.id)
+        })?;
+
+        Ok(codes)
+    }
+
+    /// Check the second factor of a user with two-factor authentication enabled
+    pub fn check_second_factor(
+        conn: &mut DbConnection,
+        user: &User,
+        code: Option<&str>,
+        recovery_code: Option<&str>,
+    ) -> Result<()> {
+        let valid = match (code, recovery_code) {
+            (Some(code), _) => Self::verify_code(conn, user, code)?,
+            (None, Some(recovery_code)) => Self::use_recovery_code(conn, user.id, recovery_code)?,
+            (None, None) => {
+                return Err(AppError::BadRequest(
+                    "A verification code or recovery code is required".to_string(),
+                ))
+            }
+        };
+
+        if !valid {
+            return Err(AppError::Unauthorized("Invalid verification code".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Check a code against the user's secret.
+    ///
+    /// A code is accepted once: its time step is recorded and codes from that
+    /// step or earlier are rejected afterwards.
+    pub fn verify_code(conn: &mut DbConnection, user: &User, code: &str) -> Result<bool> {
+        let Some(secret) = user.totp_secret.as_deref() else {
+            return Ok(false);
+        };
+
+        let totp = Self::totp(secret, None, &user.username)?;
+        let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| AppError::InternalServerError(format!("System clock error: {}", e)))?
+            .as_secs();
+        let current_step = now / STEP_SECONDS;
+
+        let matched = (current_step - ALLOWED_DRIFT_STEPS..=current_step + ALLOWED_DRIFT_STEPS)
+            .find(|step| totp.check(&code, step * STEP_SECONDS));
+
+        let Some(step) = matched else {
+            return Ok(false);
+        };
+
+        // Conditional update, so two requests with the same code can't both win
+        let consumed = diesel::update(
+            users::table.find(user.id).filter(
+                users::totp_last_step
+                    .is_null()
+                    .or(users::totp_last_step.lt(step as i64)),
+            ),
+        )
+        .set(users::totp_last_step.eq(step as i64))
+        .execute(conn)?;
+
+        Ok(consumed > 0)
+    }
+
+    /// Issue a new set of recovery codes, invalidating the old ones
+    pub fn regenerate_recovery_codes(conn: &mut DbConnection, user_id: Uuid) -> Result<Vec<String>> {
+        let codes = conn.transaction::<Vec<String>, diesel::result::Error, _>(|conn| {
+            Self::replace_recovery_codes(conn, user_id)
+        })?;
+
+        Ok(codes)
+    }
+
+    pub fn remaining_recovery_codes(conn: &mut DbConnection, user_id: Uuid) -> Result<i64> {
+        let remaining = totp_recovery_codes::table
+            .filter(totp_recovery_codes::user_id.eq(user_id))
+            .filter(totp_recovery_codes::used_at.is_null())
+            .count()
+            .get_result(conn)?;
+
+        Ok(remaining)
+    }
+
+    /// Turn off two-factor authentication and forget the secret and recovery codes
+    pub fn disable(conn: &mut DbConnection, user_id: Uuid) -> Result<()> {
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            diesel::update(users::table.find(user_id))
+                .set((
+                    users::totp_secret.eq(None::<String>),
+                    users::totp_enabled.eq(false),
+                    users::totp_last_step.eq(None::<i64>),
+                    users::updated_at.eq(diesel::dsl::now),
+                ))
+                .execute(conn)?;
+
+            diesel::delete(totp_recovery_codes::table.filter(totp_recovery_codes::user_id.eq(user_id)))
+                .execute(conn)?;
+
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    pub fn set_required(conn: &mut DbConnection, user_id: Uuid, required: bool) -> Result<User> {
+        let user = diesel::update(users::table.find(user_id))
+            .set((
+                users::totp_required.eq(required),
+                users::updated_at.eq(diesel::dsl::now),
+            ))
+            .returning(User::as_returning())
+            .get_result(conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        Ok(user)
+    }
+
+    fn use_recovery_code(conn: &mut DbConnection, user_id: Uuid, recovery_code: &str) -> Result<bool> {
+        let used = diesel::update(
+            totp_recovery_codes::table
+                .filter(totp_recovery_codes::user_id.eq(user_id))
+                .filter(totp_recovery_codes::code_hash.eq(hash_token(&Self::normalize_recovery_code(recovery_code))))
+                .filter(totp_recovery_codes::used_at.is_null()),
+        )
+        .set(totp_recovery_codes::used_at.eq(diesel::dsl::now.nullable()))
+        .execute(conn)?;
+
+        Ok(used > 0)
+    }
+
+    fn replace_recovery_codes(
+        conn: &mut DbConnection,
+        user_id: Uuid,
+    ) -> std::result::Result<Vec<String>, diesel::result::Error> {
+        diesel::delete(totp_recovery_codes::table.filter(totp_recovery_codes::user_id.eq(user_id)))
+            .execute(conn)?;
+
+        let codes: Vec<String> = (0..RECOVERY_CODE_COUNT).map(|_| Self::generate_recovery_code()).collect();
+
+        let rows: Vec<_> = codes
+            .iter()
+            .map(|code| {
+                (
+                    totp_recovery_codes::user_id.eq(user_id),
+                    totp_recovery_codes::code_hash.eq(hash_token(&Self::normalize_recovery_code(code))),
+                )
+            })
+            .collect();
+
+        diesel::insert_into(totp_recovery_codes::table)
+            .values(&rows)
+            .execute(conn)?;
+
+        Ok(codes)
+    }
+
+    // Shown as groups of four hex digits, e.g. 3f9a-0c21-77be-d405
+    fn generate_recovery_code() -> String {
+        let mut bytes = [0u8; RECOVERY_CODE_BYTES];
+        rand::thread_rng().fill_bytes(&mut bytes);
+
+        hex::encode(bytes)
+            .as_bytes()
+            .chunks(4)
+            .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    // Users may type codes without dashes or in upper case
+    fn normalize_recovery_code(code: &str) -> String {
+        code.chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    }
+
+    fn totp(secret: &str, issuer: Option<String>, account: &str) -> Result<TOTP> {
+        let secret = Secret::Encoded(secret.to_string())
+            .to_bytes()
+            .map_err(|e| AppError::InternalServerError(format!("Invalid TOTP secret: {}", e)))?;
+
+        // Drift is handled by verify_code, which needs to know the matching step.
+        // The otpauth label uses ':' as its separator, so it can't appear in the account name.
+        TOTP::new(Algorithm::SHA1, DIGITS, 0, STEP_SECONDS, secret, issuer, account.replace(':', "_"))
+            .map_err(|e| AppError::InternalServerError(format!("Failed to build TOTP: {}", e)))
+    }
+}
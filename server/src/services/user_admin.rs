@@ -0,0 +1,293 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::admin::{AdminAuditLog, AdminUpdateUserRequest, NewAdminAuditLog};
+use crate::models::document::Document;
+use crate::models::user::{NewUser, User, UserRole};
+use crate::schema::{
+    admin_audit_logs, approval_steps, approval_workflows, document_permissions, document_renditions,
+    document_status_history, document_templates, document_versions, documents, group_permissions, groups,
+    legal_holds, metadata_schemas, retention_policies, share_links, storage_quotas, tags, users,
+};
+
+/// Filters for listing users
+#[derive(Debug, Default)]
+pub struct UserFilter {
+    /// Matched against username, email and full name
+    pub query: Option<String>,
+    pub role: Option<String>,
+    pub is_active: Option<bool>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Who is acting, for the audit log
+pub struct AdminActor<'a> {
+    pub id: Uuid,
+    pub username: &'a str,
+    pub ip_address: Option<String>,
+}
+
+pub struct UserAdminService;
+
+impl UserAdminService {
+    pub fn list(conn: &mut DbConnection, filter: &UserFilter) -> Result<Vec<User>> {
+        let mut query = users::table.select(User::as_select()).into_boxed();
+
+        if let Some(text) = filter.query.as_deref().map(str::trim).filter(|text| !text.is_empty()) {
+            let pattern = format!("%{}%", Self::escape_like(text));
+            query = query.filter(
+                users::username
+                    .ilike(pattern.clone())
+                    .or(users::email.ilike(pattern.clone()))
+                    .or(users::full_name.ilike(pattern)),
+            );
+        }
+
+        if let Some(role) = &filter.role {
+            query = query.filter(users::role.eq(Self::parse_role(role)?.as_str().to_string()));
+        }
+
+        if let Some(is_active) = filter.is_active {
+            query = query.filter(users::is_active.eq(is_active));
+        }
+
+        let users = query
+            .order(users::username.asc())
+            .limit(filter.limit)
+            .offset(filter.offset)
+            .load(conn)?;
+
+        Ok(users)
+    }
+
+    pub fn find(conn: &mut DbConnection, user_id: Uuid) -> Result<User> {
+        users::table
+            .find(user_id)
+            .select(User::as_select())
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    }
+
+    pub fn create(conn: &mut DbConnection, new_user: NewUser) -> Result<User> {
+        Self::parse_role(&new_user.role)?;
+        Self::ensure_available(conn, Some(&new_user.username), &new_user.email, None)?;
+
+        let user = diesel::insert_into(users::table)
+            .values(&new_user)
+            .returning(User::as_returning())
+            .get_result(conn)?;
+
+        Ok(user)
+    }
+
+    /// Apply an admin's changes to a user; admins can't demote or deactivate themselves
+    pub fn update(
+        conn: &mut DbConnection,
+        admin_id: Uuid,
+        user: &User,
+        request: &AdminUpdateUserRequest,
+    ) -> Result<User> {
+        let role = request.role.as_deref().map(Self::parse_role).transpose()?;
+
+        if user.id == admin_id {
+            let demoted = role.as_ref().is_some_and(|role| *role != UserRole::Admin);
+            if demoted || request.is_active == Some(false) {
+                return Err(AppError::BadRequest("You can't demote or deactivate yourself".to_string()));
+            }
+        }
+
+        if let Some(email) = &request.email {
+            Self::ensure_available(conn, None, email, Some(user.id))?;
+        }
+
+        let user = diesel::update(users::table.find(user.id))
+            .set((
+                users::email.eq(request.email.clone().unwrap_or_else(|| user.email.clone())),
+                users::full_name.eq(request.full_name.clone().or_else(|| user.full_name.clone())),
+                users::role.eq(role.map_or_else(|| user.role.clone(), |role| role.as_str().to_string())),
+                users::is_active.eq(request.is_active.unwrap_or(user.is_active)),
+                users::updated_at.eq(diesel::dsl::now),
+            ))
+            .returning(User::as_returning())
+            .get_result(conn)?;
+
+        Ok(user)
+    }
+
+    /// Delete a user, handing their documents and the records they created to another user.
+    ///
+    /// Returns the IDs of the transferred documents.
+    pub fn delete_with_transfer(conn: &mut DbConnection, user_id: Uuid, recipient_id: Uuid) -> Result<Vec<Uuid>> {
+        if user_id == recipient_id {
+            return Err(AppError::BadRequest("Documents can't be transferred to the user being deleted".to_string()));
+        }
+
+        Self::find(conn, recipient_id)
+            .map_err(|_| AppError::BadRequest("The user receiving the documents doesn't exist".to_string()))?;
+
+        let transferred = conn.transaction::<Vec<Uuid>, diesel::result::Error, _>(|conn| {
+            let transferred = diesel::update(documents::table.filter(documents::owner_id.eq(user_id)))
+                .set((
+                    documents::owner_id.eq(recipient_id),
+                    documents::updated_at.eq(diesel::dsl::now),
+                ))
+                .returning(documents::id)
+                .get_results::<Uuid>(conn)?;
+
+            // These columns don't cascade, so the user's name on them passes to the recipient
+            diesel::update(document_versions::table.filter(document_versions::created_by.eq(user_id)))
+                .set(document_versions::created_by.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(document_permissions::table.filter(document_permissions::granted_by.eq(user_id)))
+                .set(document_permissions::granted_by.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(group_permissions::table.filter(group_permissions::granted_by.eq(user_id)))
+                .set(group_permissions::granted_by.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(groups::table.filter(groups::created_by.eq(user_id)))
+                .set(groups::created_by.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(share_links::table.filter(share_links::created_by.eq(user_id)))
+                .set(share_links::created_by.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(storage_quotas::table.filter(storage_quotas::created_by.eq(user_id)))
+                .set(storage_quotas::created_by.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(document_status_history::table.filter(document_status_history::changed_by.eq(user_id)))
+                .set(document_status_history::changed_by.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(approval_workflows::table.filter(approval_workflows::submitted_by.eq(user_id)))
+                .set(approval_workflows::submitted_by.eq(recipient_id))
+                .execute(conn)?;
+            // Reviews assigned to the user would otherwise be deleted with them
+            diesel::update(approval_steps::table.filter(approval_steps::reviewer_user_id.eq(user_id)))
+                .set(approval_steps::reviewer_user_id.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(approval_steps::table.filter(approval_steps::decided_by.eq(user_id)))
+                .set(approval_steps::decided_by.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(retention_policies::table.filter(retention_policies::created_by.eq(user_id)))
+                .set(retention_policies::created_by.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(legal_holds::table.filter(legal_holds::created_by.eq(user_id)))
+                .set(legal_holds::created_by.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(legal_holds::table.filter(legal_holds::released_by.eq(user_id)))
+                .set(legal_holds::released_by.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(metadata_schemas::table.filter(metadata_schemas::created_by.eq(user_id)))
+                .set(metadata_schemas::created_by.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(tags::table.filter(tags::created_by.eq(user_id)))
+                .set(tags::created_by.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(document_renditions::table.filter(document_renditions::created_by.eq(user_id)))
+                .set(document_renditions::created_by.eq(recipient_id))
+                .execute(conn)?;
+            diesel::update(document_templates::table.filter(document_templates::created_by.eq(user_id)))
+                .set(document_templates::created_by.eq(recipient_id))
+                .execute(conn)?;
+
+            // Sessions, tokens, memberships and permissions granted to the user cascade
+            diesel::delete(users::table.find(user_id)).execute(conn)?;
+
+            Ok(transferred)
+        })?;
+
+        Ok(transferred)
+    }
+
+    /// Documents by ID, for re-indexing after a transfer
+    pub fn load_documents(conn: &mut DbConnection, document_ids: &[Uuid]) -> Result<Vec<Document>> {
+        let documents = documents::table
+            .filter(documents::id.eq_any(document_ids))
+            .filter(documents::deleted_at.is_null())
+            .select(Document::as_select())
+            .load(conn)?;
+
+        Ok(documents)
+    }
+
+    pub fn audit(
+        conn: &mut DbConnection,
+        actor: &AdminActor,
+        action: &str,
+        target: &User,
+        details: Option<String>,
+    ) -> Result<()> {
+        diesel::insert_into(admin_audit_logs::table)
+            .values(&NewAdminAuditLog {
+                admin_id: actor.id,
+                admin_username: actor.username.to_string(),
+                action: action.to_string(),
+                target_user_id: target.id,
+                target_username: target.username.clone(),
+                details,
+                ip_address: actor.ip_address.clone(),
+            })
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    pub fn list_audit_logs(
+        conn: &mut DbConnection,
+        target_user_id: Option<Uuid>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<AdminAuditLog>> {
+        let mut query = admin_audit_logs::table
+            .select(AdminAuditLog::as_select())
+            .into_boxed();
+
+        if let Some(target_user_id) = target_user_id {
+            query = query.filter(admin_audit_logs::target_user_id.eq(target_user_id));
+        }
+
+        let logs = query
+            .order(admin_audit_logs::created_at.desc())
+            .limit(limit)
+            .offset(offset)
+            .load(conn)?;
+
+        Ok(logs)
+    }
+
+    fn parse_role(role: &str) -> Result<UserRole> {
+        UserRole::from_str(role).ok_or_else(|| AppError::ValidationError(format!("Unknown role: {}", role)))
+    }
+
+    fn ensure_available(
+        conn: &mut DbConnection,
+        username: Option<&str>,
+        email: &str,
+        except: Option<Uuid>,
+    ) -> Result<()> {
+        let mut query = users::table.filter(users::email.eq(email)).into_boxed();
+
+        if let Some(username) = username {
+            query = query.or_filter(users::username.eq(username));
+        }
+
+        if let Some(user_id) = except {
+            query = query.filter(users::id.ne(user_id));
+        }
+
+        let taken = diesel::select(diesel::dsl::exists(query)).get_result::<bool>(conn)?;
+
+        if taken {
+            return Err(AppError::BadRequest("Username or email already exists".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // Searches are plain text, so LIKE wildcards in them are literal
+    fn escape_like(text: &str) -> String {
+        text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+    }
+}
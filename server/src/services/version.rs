@@ -0,0 +1,208 @@
+use diesel::prelude::*;
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::document::{Document, DocumentVersion, NewDocumentVersion};
+use crate::schema::{document_versions, documents};
+use crate::services::StorageService;
+
+/// How a version saved from the OnlyOffice editor came about
+#[derive(Debug, Default)]
+pub struct EditorSave {
+    /// Key of the editing session that produced the version
+    pub document_key: Option<String>,
+    pub is_forcesave: bool,
+    /// The callback's `history` object, fed back to the editor's history panel
+    pub history: Option<JsonValue>,
+    /// Share link used by an anonymous editor; `created_by` is then the link's creator
+    pub share_link_id: Option<Uuid>,
+}
+
+pub struct VersionService;
+
+impl VersionService {
+    /// Store `file_data` as the next version of a document.
+    ///
+    /// The previous file is kept in storage as a retained version. Documents
+    /// uploaded before versioning existed have no row for their current file,
+    /// so one is recorded before the new version is added.
+    pub async fn create_version(
+        conn: &mut DbConnection,
+        storage_service: &StorageService,
+        document: &Document,
+        file_data: &[u8],
+        comment: Option<String>,
+        created_by: Uuid,
+    ) -> Result<Document> {
+        Self::create_editor_version(
+            conn,
+            storage_service,
+            document,
+            file_data,
+            comment,
+            created_by,
+            EditorSave::default(),
+        )
+        .await
+    }
+
+    /// Like `create_version`, recording the OnlyOffice session details
+    pub async fn create_editor_version(
+        conn: &mut DbConnection,
+        storage_service: &StorageService,
+        document: &Document,
+        file_data: &[u8],
+        comment: Option<String>,
+        created_by: Uuid,
+        editor_save: EditorSave,
+    ) -> Result<Document> {
+        let new_file_path = storage_service
+            .upload_file(file_data, &document.name, &document.mime_type)
+            .await?;
+
+        let updated = conn.transaction::<Document, diesel::result::Error, _>(|conn| {
+            let has_current_row = diesel::select(diesel::dsl::exists(
+                document_versions::table
+                    .filter(document_versions::document_id.eq(document.id))
+                    .filter(document_versions::version.eq(document.version)),
+            ))
+            .get_result::<bool>(conn)?;
+
+            if !has_current_row {
+                let current_version = NewDocumentVersion {
+                    document_id: document.id,
+                    version: document.version,
+                    file_path: document.file_path.clone(),
+                    file_size: document.file_size,
+                    comment: None,
+                    created_by: document.owner_id,
+                    document_key: None,
+                    is_forcesave: false,
+                    history: None,
+                    share_link_id: None,
+                };
+
+                diesel::insert_into(document_versions::table)
+                    .values(&current_version)
+                    .execute(conn)?;
+            }
+
+            let new_version = NewDocumentVersion {
+                document_id: document.id,
+                version: document.version + 1,
+                file_path: new_file_path.clone(),
+                file_size: file_data.len() as i64,
+                comment,
+                created_by,
+                document_key: editor_save.document_key,
+                is_forcesave: editor_save.is_forcesave,
+                history: editor_save.history,
+                share_link_id: editor_save.share_link_id,
+            };
+
+            diesel::insert_into(document_versions::table)
+                .values(&new_version)
+                .execute(conn)?;
+
+            diesel::update(documents::table.find(document.id))
+                .set((
+                    documents::file_path.eq(&new_file_path),
+                    documents::file_size.eq(file_data.len() as i64),
+                    documents::version.eq(document.version + 1),
+                    documents::updated_at.eq(diesel::dsl::now),
+                ))
+                .returning(Document::as_returning())
+                .get_result(conn)
+        });
+
+        match updated {
+            Ok(document) => Ok(document),
+            Err(e) => {
+                // Don't leave an orphaned object behind if the database update failed
+                let _ = storage_service.delete_file(&new_file_path).await;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Remove a retained (non-current) version and its file
+    pub async fn purge_version(
+        conn: &mut DbConnection,
+        storage_service: &StorageService,
+        document: &Document,
+        version: i32,
+    ) -> Result<()> {
+        if version == document.version {
+            return Err(AppError::BadRequest("Cannot purge the current version".to_string()));
+        }
+
+        let (file_path, changes_path) = document_versions::table
+            .filter(document_versions::document_id.eq(document.id))
+            .filter(document_versions::version.eq(version))
+            .select((document_versions::file_path, document_versions::changes_path))
+            .first::<(String, Option<String>)>(conn)?;
+
+        diesel::delete(
+            document_versions::table
+                .filter(document_versions::document_id.eq(document.id))
+                .filter(document_versions::version.eq(version)),
+        )
+        .execute(conn)?;
+
+        if file_path != document.file_path {
+            storage_service.delete_file(&file_path).await?;
+        }
+
+        if let Some(changes_path) = changes_path {
+            storage_service.delete_file(&changes_path).await?;
+        }
+
+        Ok(())
+    }
+
+    /// All recorded versions of a document, oldest first
+    pub fn list_versions(conn: &mut DbConnection, document_id: Uuid) -> Result<Vec<DocumentVersion>> {
+        let versions = document_versions::table
+            .filter(document_versions::document_id.eq(document_id))
+            .order(document_versions::version.asc())
+            .select(DocumentVersion::as_select())
+            .load(conn)?;
+
+        Ok(versions)
+    }
+
+    /// The version row for a document's current file, if one was recorded
+    pub fn current_version(conn: &mut DbConnection, document: &Document) -> Result<Option<DocumentVersion>> {
+        let version = document_versions::table
+            .filter(document_versions::document_id.eq(document.id))
+            .filter(document_versions::version.eq(document.version))
+            .select(DocumentVersion::as_select())
+            .first(conn)
+            .optional()?;
+
+        Ok(version)
+    }
+
+    /// Store the OnlyOffice changes archive for the document's current version
+    pub async fn attach_changes(
+        conn: &mut DbConnection,
+        storage_service: &StorageService,
+        document: &Document,
+        changes: Vec<u8>,
+    ) -> Result<()> {
+        let changes_path = format!("{}.changes.zip", document.file_path);
+        storage_service.write_file(&changes_path, changes).await?;
+
+        diesel::update(
+            document_versions::table
+                .filter(document_versions::document_id.eq(document.id))
+                .filter(document_versions::version.eq(document.version)),
+        )
+        .set(document_versions::changes_path.eq(Some(&changes_path)))
+        .execute(conn)?;
+
+        Ok(())
+    }
+}
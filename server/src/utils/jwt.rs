@@ -10,12 +10,14 @@ pub struct Claims {
     pub sub: String, // user_id
     pub username: String,
     pub role: String,
+    /// Login session the token was issued for
+    pub sid: Uuid,
     pub exp: i64,
     pub iat: i64,
 }
 
 impl Claims {
-    pub fn new(user_id: Uuid, username: String, role: String, expiration_seconds: i64) -> Self {
+    pub fn new(user_id: Uuid, username: String, role: String, session_id: Uuid, expiration_seconds: i64) -> Self {
         let now = Utc::now();
         let exp = (now + Duration::seconds(expiration_seconds)).timestamp();
         
@@ -23,6 +25,7 @@ impl Claims {
             sub: user_id.to_string(),
             username,
             role,
+            sid: session_id,
             exp,
             iat: now.timestamp(),
         }
@@ -54,3 +57,60 @@ pub fn decode_jwt(token: &str, secret: &str) -> Result<Claims> {
     Ok(token_data.claims)
 }
 
+
+/// Short-lived token proving the password step of a two-step login.
+///
+/// It has none of the fields of `Claims`, so it can't be used as an access
+/// token, and access tokens can't be used as challenges.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChallengeClaims {
+    pub sub: String, // user_id
+    pub purpose: String,
+    pub exp: i64,
+    pub iat: i64,
+}
+
+impl ChallengeClaims {
+    pub const TOTP: &'static str = "totp";
+
+    pub fn new(user_id: Uuid, purpose: &str, expiration_seconds: i64) -> Self {
+        let now = Utc::now();
+
+        ChallengeClaims {
+            sub: user_id.to_string(),
+            purpose: purpose.to_string(),
+            exp: (now + Duration::seconds(expiration_seconds)).timestamp(),
+            iat: now.timestamp(),
+        }
+    }
+
+    pub fn user_id(&self) -> Result<Uuid> {
+        Uuid::parse_str(&self.sub)
+            .map_err(|_| AppError::Unauthorized("Invalid challenge token".to_string()))
+    }
+}
+
+pub fn encode_challenge(claims: &ChallengeClaims, secret: &str) -> Result<String> {
+    encode(
+        &Header::default(),
+        claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .map_err(|e| AppError::InternalServerError(format!("Failed to encode challenge token: {}", e)))
+}
+
+pub fn decode_challenge(token: &str, secret: &str, purpose: &str) -> Result<ChallengeClaims> {
+    let claims = decode::<ChallengeClaims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &Validation::default(),
+    )
+    .map_err(|_| AppError::Unauthorized("Invalid or expired challenge token".to_string()))?
+    .claims;
+
+    if claims.purpose != purpose {
+        return Err(AppError::Unauthorized("Invalid or expired challenge token".to_string()));
+    }
+
+    Ok(claims)
+}
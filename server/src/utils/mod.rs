@@ -1,6 +1,8 @@
 pub mod jwt;
 pub mod password;
+pub mod token;
 
-pub use jwt::{Claims, encode_jwt, decode_jwt};
+pub use jwt::{ChallengeClaims, Claims, decode_challenge, decode_jwt, encode_challenge, encode_jwt};
 pub use password::{hash_password, verify_password};
+pub use token::{generate_token, hash_token};
 